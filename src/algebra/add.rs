@@ -1,28 +1,40 @@
 use std::any::Any;
+use std::collections::BTreeMap;
 
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
 use crate::algebra::constant::Constant;
+use crate::algebra::display::{latex_operand, negated, typst_operand, Position};
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
 use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
 use crate::algebra::multiply::Multiply;
+use crate::algebra::number::Number;
+
+/// A numeric coefficient paired with the non-numeric factors it multiplies.
+type Term = (Number, Expr);
 
 /// `Add` struct represents an addition operation in an expression tree.
-/// It contains a vector of `Expression` trait objects.
+/// It contains a vector of `Expr` operands.
 pub struct Add {
-    pub ops: Vec<Box<dyn Expression>>,
+    pub ops: Vec<Expr>,
 }
 
 impl Add {
-    pub fn new(ops: Vec<Box<dyn Expression>>) -> Self {
+    pub fn new(ops: Vec<Expr>) -> Self {
         Self { ops }
     }
 
     /// Flattens nested `Add` expressions into a single-level `Add` expression.
     /// This is a helper method used in the `simplify` method.
-    fn flatten(&self) -> Vec<Box<dyn Expression>> {
+    fn flatten(&self) -> Vec<Expr> {
         // TODO: Add flatten to Expression trait
         let mut flattened_ops = Vec::new();
         for op in &self.ops {
-            if let Some(mul) = op.as_any().downcast_ref::<Add>() {
-                let more_flattened_ops = mul.flatten();
+            if let Expr::Add(add) = op {
+                let more_flattened_ops = add.flatten();
                 flattened_ops.extend(more_flattened_ops);
             } else {
                 flattened_ops.push(op.clone());
@@ -30,54 +42,121 @@ impl Add {
         }
         flattened_ops
     }
+
+    /// Splits a simplified term into its numeric coefficient and the remaining factors,
+    /// so `3 * x * y` becomes `(3, x * y)`. A term without a numeric factor has a coefficient of 1.
+    pub(crate) fn split_coefficient(term: Expr) -> Term {
+        if let Expr::Multiply(multiply) = &term {
+            let (numbers, mut factors): (Vec<_>, Vec<_>) = multiply
+                .ops
+                .iter()
+                .cloned()
+                .partition(|op| Constant::value_of(op).is_some());
+            if !numbers.is_empty() && !factors.is_empty() {
+                let coefficient = numbers
+                    .iter()
+                    .filter_map(Constant::value_of)
+                    .fold(Number::from(1), |product, number| &product * number);
+                let rest: Expr = if factors.len() == 1 {
+                    factors.pop().unwrap()
+                } else {
+                    Expr::from(Multiply::new(factors))
+                };
+                return (coefficient, rest);
+            }
+        }
+        (Number::from(1), term)
+    }
+
+    /// Multiplies the non-numeric factors of a term by its combined coefficient.
+    /// This is the inverse of `split_coefficient`, leaving out a coefficient of 1.
+    fn with_coefficient(coefficient: Number, rest: Expr) -> Expr {
+        if coefficient.is_one() {
+            return rest;
+        }
+        let mut ops: Vec<Expr> = vec![Expr::from(Constant::new(coefficient))];
+        match rest {
+            Expr::Multiply(multiply) => ops.extend(multiply.ops.iter().cloned()),
+            rest => ops.push(rest),
+        }
+        Expr::from(Multiply::new(ops))
+    }
 }
 
 impl Expression for Add {
-    /// Evaluates the expression and returns a new expression.
-    fn eval(&self) -> Box<dyn Expression> {
-        todo!()
+    /// Evaluates the expression to a number.
+    /// For an `Add`, it returns the sum of its evaluated operands.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        self.ops.iter().map(|op| op.eval(env)).sum()
     }
 
-    /// Simplifies the expression and returns a new simplified expression.
-    /// This method implements several algebraic simplification rules, such as eliminating addition by 0,
-    /// evaluating constant addition, and others.
-    fn simplify(&self) -> Box<dyn Expression> {
-        // Flatten nested Add expressions
-        let flattened_ops = self.flatten();
-
-        // Eliminate zero terms and simplify all operands
-        let ops: Vec<Box<dyn Expression>> = flattened_ops
-            .iter()
-            .map(|op| op.simplify())
-            .filter(|op| {
-                if let Some(op) = op.as_any().downcast_ref::<Constant>() {
-                    op.value != 0.0
-                } else {
-                    true
-                }
-            })
-            .collect();
+    /// Evaluates the expression to a complex number.
+    /// For an `Add`, it returns the sum of its evaluated operands.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        self.ops.iter().map(|op| op.eval_complex(env)).sum()
+    }
 
-        // Sum all constants
-        let (constants, mut ops): (Vec<_>, Vec<_>) = ops
-            .into_iter()
-            .partition(|op| op.as_any().downcast_ref::<Constant>().is_some());
-        let sum: f64 = constants
-            .iter()
-            .map(|op| op.as_any().downcast_ref::<Constant>().unwrap().value)
-            .sum();
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// For an `Add`, it returns the sum of its evaluated operands.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        self.ops.iter().try_fold(BigFloat::zero(), |sum, op| {
+            Ok(sum.add(&op.eval_big(env, precision)?, precision))
+        })
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// For an `Add`, it returns the sum of its evaluated operands.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        self.ops.iter().try_fold(Interval::point(0.0), |sum, op| {
+            Ok(sum.add(&op.eval_interval(env)?))
+        })
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// This method flattens nested additions, sums the numeric terms and combines like terms by
+    /// adding their coefficients, so `2x + 3x + y - y` becomes `5x`. Terms whose coefficients
+    /// cancel are dropped, and the remaining terms are sorted in the canonical order of their
+    /// non-numeric factors, so `y + x` and `x + y` give the same tree. The numeric term is placed
+    /// last, and a single remaining term is returned on its own.
+    fn simplify(&self) -> Expr {
+        // Simplify all operands, flattening any Add produced by simplification
+        let mut terms: Vec<Expr> = Vec::new();
+        for op in self.flatten() {
+            let op = op.simplify();
+            match op {
+                Expr::Add(add) => terms.extend(add.ops.iter().cloned()),
+                op => terms.push(op),
+            }
+        }
 
-        ops.push(Box::<Constant>::new(Constant::new(sum)));
+        // Sum the numeric terms and group the others by their non-numeric factors
+        let mut constant = Number::from(0);
+        let mut groups: BTreeMap<Expr, Number> = BTreeMap::new();
+        for term in terms {
+            if let Some(value) = Constant::value_of(&term) {
+                constant = &constant + value;
+                continue;
+            }
+            let (coefficient, rest) = Add::split_coefficient(term);
+            let sum = groups.entry(rest).or_insert_with(|| Number::from(0));
+            *sum = &*sum + &coefficient;
+        }
 
-        // Group like terms
-        // Combine constants
-        // Combine like variables
-        // Handle additive inverses
-        // Sort and reorganize the terms for readability (optional)
-        // Check for simplification to a single term
-        // Construct and return the simplified Add expression
+        // Rebuild the terms in canonical order, dropping those that cancelled out
+        let mut ops: Vec<Expr> = groups
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(rest, coefficient)| Add::with_coefficient(coefficient, rest))
+            .collect();
+        if !constant.is_zero() {
+            ops.push(Expr::from(Constant::new(constant)));
+        }
 
-        Box::new(Self { ops })
+        match ops.len() {
+            0 => Expr::from(Constant::new(0)),
+            1 => ops.pop().unwrap(),
+            _ => Expr::from(Self { ops }),
+        }
     }
 
     /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
@@ -96,20 +175,45 @@ impl Expression for Add {
     }
 
     /// Returns a Typist string for the expression.
+    /// Terms with a leading minus, such as `-2 x`, are written as subtractions, and a subtracted
+    /// sum or a term that still starts with a minus is wrapped in parentheses.
     fn to_typist(&self) -> String {
-        let mut parts: Vec<String> = Vec::new();
-        for op in &self.ops {
-            let part = op.to_typist();
-            // Nested expressions might need parentheses, but simple constants or variables do not.
-            if op.as_any().downcast_ref::<Multiply>().is_some()
-                || op.as_any().downcast_ref::<Add>().is_some()
-            {
-                parts.push(format!("({})", part));
-            } else {
-                parts.push(part);
+        let mut output = String::new();
+        for (index, op) in self.ops.iter().enumerate() {
+            if index == 0 {
+                output.push_str(&op.to_typist());
+                continue;
+            }
+            match negated(op) {
+                Some(positive) => output.push_str(&format!(
+                    " - {}",
+                    typst_operand(&positive, Position::Subtrahend)
+                )),
+                None => output.push_str(&format!(" + {}", typst_operand(op, Position::Addend))),
+            }
+        }
+        output
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For an `Add`, terms are joined by ` + `, and negated terms are written as subtractions,
+    /// such as `x - \left(-y\right)` for a subtracted negation.
+    fn to_latex(&self) -> String {
+        let mut output = String::new();
+        for (index, op) in self.ops.iter().enumerate() {
+            if index == 0 {
+                output.push_str(&op.to_latex());
+                continue;
+            }
+            match negated(op) {
+                Some(positive) => output.push_str(&format!(
+                    " - {}",
+                    latex_operand(&positive, Position::Subtrahend)
+                )),
+                None => output.push_str(&format!(" + {}", latex_operand(op, Position::Addend))),
             }
         }
-        parts.join(" + ")
+        output
     }
 }
 
@@ -124,29 +228,21 @@ impl Clone for Add {
 #[cfg(test)]
 mod tests {
     use crate::algebra::constant::Constant;
+    use crate::algebra::negate::Negate;
+    use crate::algebra::variable::Variable;
 
     use super::*;
 
     #[test]
     fn add_simplify_with_zero() {
         let add = Add::new(vec![
-            Box::new(Constant::new(1.0)),
-            Box::new(Constant::new(0.0)),
-            Box::new(Constant::new(2.0)),
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Constant::new(0.0)),
+            Expr::from(Constant::new(2.0)),
         ]);
         let simplified = add.simplify();
-        if let Some(simplified_add) = simplified.as_any().downcast_ref::<Add>() {
-            assert_eq!(
-                simplified_add
-                    .ops
-                    .first()
-                    .unwrap()
-                    .as_any()
-                    .downcast_ref::<Constant>()
-                    .unwrap()
-                    .value,
-                3.0
-            );
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 3.0);
         } else {
             panic!("Expected Constant, found {:?}", simplified);
         }
@@ -155,23 +251,13 @@ mod tests {
     #[test]
     fn add_simplify_with_no_zero() {
         let add = Add::new(vec![
-            Box::new(Constant::new(1.0)),
-            Box::new(Constant::new(2.0)),
-            Box::new(Constant::new(3.0)),
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Constant::new(3.0)),
         ]);
         let simplified = add.simplify();
-        if let Some(simplified_add) = simplified.as_any().downcast_ref::<Add>() {
-            assert_eq!(
-                simplified_add
-                    .ops
-                    .first()
-                    .unwrap()
-                    .as_any()
-                    .downcast_ref::<Constant>()
-                    .unwrap()
-                    .value,
-                6.0
-            )
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 6.0);
         } else {
             panic!("Expected Constant, found {:?}", simplified);
         }
@@ -180,23 +266,13 @@ mod tests {
     #[test]
     fn add_simplify_with_nested_add() {
         let nested_add = Add::new(vec![
-            Box::new(Constant::new(1.0)),
-            Box::new(Constant::new(2.0)),
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Constant::new(2.0)),
         ]);
-        let add = Add::new(vec![Box::new(Constant::new(3.0)), Box::new(nested_add)]);
+        let add = Add::new(vec![Expr::from(Constant::new(3.0)), Expr::from(nested_add)]);
         let simplified = add.simplify();
-        if let Some(simplified_add) = simplified.as_any().downcast_ref::<Add>() {
-            assert_eq!(
-                simplified_add
-                    .ops
-                    .first()
-                    .unwrap()
-                    .as_any()
-                    .downcast_ref::<Constant>()
-                    .unwrap()
-                    .value,
-                6.0
-            )
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 6.0);
         } else {
             panic!("Expected Constant, found {:?}", simplified);
         }
@@ -205,10 +281,131 @@ mod tests {
     #[test]
     fn add_simplify_with_negative_constant() {
         let add = Add::new(vec![
-            Box::new(Constant::new(5.0)),
-            Box::new(Constant::new(-3.0)),
+            Expr::from(Constant::new(5.0)),
+            Expr::from(Constant::new(-3.0)),
         ]);
         let simplified = add.simplify();
         assert_eq!(simplified.to_typist(), "2");
     }
+
+    #[test]
+    fn add_eval_with_environment() {
+        let add = Add::new(vec![
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Variable::new("y")),
+        ]);
+        let env = Environment::new().with("x", 1.5).with("y", -4.0);
+        assert_eq!(add.eval(&env), Ok(-0.5));
+    }
+
+    #[test]
+    fn add_eval_with_unbound_variable() {
+        let add = Add::new(vec![
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(2.0)),
+        ]);
+        assert_eq!(
+            add.eval(&Environment::new()),
+            Err(EvalError::UnboundVariable("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn add_simplify_combines_like_terms() {
+        let term = |coefficient: f64, name: &str| -> Expr {
+            Expr::from(Multiply::new(vec![
+                Expr::from(Constant::new(coefficient)),
+                Expr::from(Variable::new(name)),
+            ]))
+        };
+        // 2x + 3x + y - y
+        let add = Add::new(vec![
+            term(2.0, "x"),
+            term(3.0, "x"),
+            Expr::from(Variable::new("y")),
+            term(-1.0, "y"),
+        ]);
+        let simplified = add.simplify();
+        if let Expr::Multiply(multiply) = &simplified {
+            assert_eq!(multiply.to_typist(), "5 x");
+        } else {
+            panic!("Expected Multiply, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn add_simplify_returns_single_term_unwrapped() {
+        let add = Add::new(vec![
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(0.0)),
+        ]);
+        let simplified = add.simplify();
+        if let Expr::Variable(variable) = &simplified {
+            assert_eq!(variable.name, "x");
+        } else {
+            panic!("Expected Variable, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn add_simplify_cancels_to_zero() {
+        let add = Add::new(vec![
+            Expr::from(Variable::new("x")),
+            Expr::from(Multiply::new(vec![
+                Expr::from(Constant::new(-1.0)),
+                Expr::from(Variable::new("x")),
+            ])),
+        ]);
+        let simplified = add.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 0.0);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn add_simplify_places_constant_last() {
+        let add = Add::new(vec![
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(2.0)),
+        ]);
+        assert_eq!(add.simplify().to_typist(), "x + 3");
+    }
+
+    #[test]
+    fn add_simplify_is_canonical() {
+        let x = || Expr::from(Variable::new("x"));
+        let y = || Expr::from(Variable::new("y"));
+        let lhs = Add::new(vec![y(), Constant::new(1).into(), x()]).simplify();
+        let rhs = Add::new(vec![x(), y(), Constant::new(1).into()]).simplify();
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.to_typist(), "x + y + 1");
+    }
+
+    #[test]
+    fn add_to_typist_writes_subtractions() {
+        let x = || Expr::from(Variable::new("x"));
+        let add = Add::new(vec![
+            x(),
+            Expr::from(Multiply::new(vec![Expr::from(Constant::new(-2)), x()])),
+            Expr::from(Add::new(vec![x(), Expr::from(Constant::new(1))])),
+            Expr::from(Negate::new(Expr::from(Add::new(vec![
+                x(),
+                Expr::from(Constant::new(1)),
+            ])))),
+            Expr::from(Constant::new(Number::rational(-1, 2))),
+            Expr::from(Negate::new(Expr::from(Negate::new(x())))),
+        ]);
+        assert_eq!(
+            add.to_typist(),
+            "x - 2 x + x + 1 - (x + 1) - frac(1, 2) - (-x)"
+        );
+        assert_eq!(
+            add.to_latex(),
+            "x - 2 x + x + 1 - \\left(x + 1\\right) - \\frac{1}{2} - \\left(-x\\right)"
+        );
+    }
 }
@@ -0,0 +1,1074 @@
+use std::f64::consts::{FRAC_1_SQRT_2, LN_2};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Float, One, Signed, ToPrimitive, Zero};
+
+use crate::algebra::eval::EvalError;
+
+/// Extra bits carried through the elementary functions to absorb their rounding errors.
+const GUARD_BITS: u64 = 32;
+
+/// How many times the starting precision may grow before `evaluate_to_digits` gives up.
+const MAX_PRECISION_GROWTH: u64 = 64;
+
+/// `BigFloat` is an arbitrary-precision real number with a guaranteed error bound.
+///
+/// It stands for every value in `[(mantissa - radius) * 2^exponent, (mantissa + radius) * 2^exponent]`.
+/// Each operation rounds its result to a requested number of bits and widens the radius to
+/// cover the rounding error, so the true value always lies inside the interval. Evaluating at
+/// increasing precision until both ends agree to the requested digits, as
+/// [`BigFloat::evaluate_to_digits`] does, guarantees that every printed digit is correct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigFloat {
+    mantissa: BigInt,
+    radius: BigInt,
+    exponent: i64,
+}
+
+impl BigFloat {
+    /// Constructs the exact value `mantissa * 2^exponent`.
+    fn exact(mantissa: BigInt, exponent: i64) -> Self {
+        Self {
+            mantissa,
+            radius: BigInt::zero(),
+            exponent,
+        }
+    }
+
+    /// Returns the exact value 0.
+    pub fn zero() -> Self {
+        Self::exact(BigInt::zero(), 0)
+    }
+
+    /// Returns the exact value 1.
+    pub fn one() -> Self {
+        Self::exact(BigInt::one(), 0)
+    }
+
+    /// Returns a ball covering `[-2^exponent, 2^exponent]`, used when a result is only known to be bounded.
+    fn bounded_by(exponent: i64) -> Self {
+        Self {
+            mantissa: BigInt::zero(),
+            radius: BigInt::one(),
+            exponent,
+        }
+    }
+
+    /// Returns the exact value of an integer.
+    pub fn from_integer(value: impl Into<BigInt>) -> Self {
+        Self::exact(value.into(), 0)
+    }
+
+    /// Returns the exact value of a finite `f64`, or `None` for infinities and NaN.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let (mantissa, exponent, sign) = value.integer_decode();
+        Some(Self::exact(
+            BigInt::from(sign) * mantissa,
+            i64::from(exponent),
+        ))
+    }
+
+    /// Returns a rational number rounded to `precision` bits.
+    /// Fractions whose denominator is a power of two are represented exactly.
+    pub fn from_rational(value: &BigRational, precision: u64) -> Self {
+        let (numerator, denominator) = (value.numer(), value.denom());
+        if denominator.is_one() {
+            return Self::from_integer(numerator.clone());
+        }
+        let shift = (precision as i64 + denominator.bits() as i64 - numerator.bits() as i64).max(0);
+        let scaled = numerator.abs() << shift;
+        let (quotient, remainder) = (&scaled / denominator, &scaled % denominator);
+        Self {
+            mantissa: if numerator.is_negative() {
+                -quotient
+            } else {
+                quotient
+            },
+            radius: if remainder.is_zero() {
+                BigInt::zero()
+            } else {
+                BigInt::one()
+            },
+            exponent: -shift,
+        }
+    }
+
+    /// Returns the lower end of the interval in units of `2^exponent`.
+    fn lower(&self) -> BigInt {
+        &self.mantissa - &self.radius
+    }
+
+    /// Returns the upper end of the interval in units of `2^exponent`.
+    fn upper(&self) -> BigInt {
+        &self.mantissa + &self.radius
+    }
+
+    /// Returns `true` if the value is exactly zero.
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero() && self.radius.is_zero()
+    }
+
+    /// Returns `true` if every value in the interval is strictly positive.
+    pub fn is_positive(&self) -> bool {
+        self.lower().is_positive()
+    }
+
+    /// Returns `true` if every value in the interval is strictly negative.
+    pub fn is_negative(&self) -> bool {
+        self.upper().is_negative()
+    }
+
+    /// Returns the value if it is exactly an integer.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        if !self.radius.is_zero() {
+            return None;
+        }
+        if self.exponent >= 0 {
+            return Some(&self.mantissa << self.exponent);
+        }
+        let shift = (-self.exponent) as u64;
+        match self.mantissa.trailing_zeros() {
+            None => Some(BigInt::zero()),
+            Some(zeros) if zeros >= shift => Some(&self.mantissa >> shift),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer nearest to the midpoint of the interval.
+    fn round_to_integer(&self) -> BigInt {
+        if self.exponent >= 0 {
+            return &self.mantissa << self.exponent;
+        }
+        let shift = (-self.exponent) as u64;
+        (&self.mantissa + (BigInt::one() << (shift - 1))) >> shift
+    }
+
+    /// Returns the midpoint of the interval as the nearest `f64`, for estimates and messages.
+    pub fn to_f64(&self) -> f64 {
+        let shift = (self.mantissa.bits() as i64 - 64).max(0);
+        let mantissa = (&self.mantissa >> shift).to_f64().unwrap_or(0.0);
+        let exponent = (self.exponent + shift).clamp(-2200, 2200) as i32;
+        mantissa * 2f64.powi(exponent)
+    }
+
+    /// Returns the smallest `k` with every value in the interval below `2^k` in magnitude,
+    /// or `None` if the value is exactly zero.
+    fn magnitude_log2(&self) -> Option<i64> {
+        let magnitude = self.mantissa.abs() + &self.radius;
+        if magnitude.is_zero() {
+            None
+        } else {
+            Some(magnitude.bits() as i64 + self.exponent)
+        }
+    }
+
+    /// Rounds the value to at most `precision` bits, widening the radius to cover the rounding error.
+    fn round(mut self, precision: u64) -> Self {
+        let bits = self.mantissa.bits().max(self.radius.bits());
+        if bits > precision {
+            let excess = bits - precision;
+            // The radius is rounded up, with one more unit if the mantissa loses bits
+            let truncated = self
+                .mantissa
+                .trailing_zeros()
+                .is_some_and(|zeros| zeros < excess);
+            let unit = BigInt::one() << excess;
+            self.radius = (&self.radius + &unit - 1) >> excess;
+            if truncated {
+                self.radius += 1;
+            }
+            self.mantissa >>= excess;
+            self.exponent += excess as i64;
+        }
+        self
+    }
+
+    /// Shifts a non-zero mantissa left until it has at least `precision` bits, without changing the value.
+    fn widened(&self, precision: u64) -> Self {
+        let bits = self.mantissa.bits();
+        if bits == 0 || bits >= precision {
+            return self.clone();
+        }
+        let shift = precision - bits;
+        Self {
+            mantissa: &self.mantissa << shift,
+            radius: &self.radius << shift,
+            exponent: self.exponent - shift as i64,
+        }
+    }
+
+    /// Widens the radius by `2^log2`, the bound of an error made outside of the ball arithmetic
+    /// such as the remainder of a truncated series.
+    fn add_error(self, log2: i64, precision: u64) -> Self {
+        let mut value = self.widened(precision);
+        // An error below the last place is covered by a single unit
+        value.radius += if log2 < value.exponent {
+            BigInt::one()
+        } else {
+            BigInt::one() << (log2 - value.exponent) as u64
+        };
+        value.round(precision)
+    }
+
+    /// Multiplies the value by `2^shift`, which is exact.
+    pub fn mul_pow2(&self, shift: i64) -> Self {
+        Self {
+            exponent: self.exponent + shift,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the negated value, which is exact.
+    pub fn neg(&self) -> Self {
+        Self {
+            mantissa: -&self.mantissa,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the absolute value, which is exact unless the interval contains zero.
+    pub fn abs(&self) -> Self {
+        if !self.lower().is_negative() {
+            self.clone()
+        } else if !self.upper().is_positive() {
+            self.neg()
+        } else {
+            // The interval [0, |mantissa| + radius]
+            let bound = self.mantissa.abs() + &self.radius;
+            Self {
+                mantissa: bound.clone(),
+                radius: bound,
+                exponent: self.exponent - 1,
+            }
+        }
+    }
+
+    /// Returns the sum rounded to `precision` bits.
+    pub fn add(&self, other: &Self, precision: u64) -> Self {
+        if self.is_zero() {
+            return other.clone().round(precision);
+        }
+        if other.is_zero() {
+            return self.clone().round(precision);
+        }
+        let (lhs, rhs) = (self.widened(precision), other.widened(precision));
+        // An operand smaller than the last place of the other one only widens its radius
+        for (large, small) in [(&lhs, &rhs), (&rhs, &lhs)] {
+            if small
+                .magnitude_log2()
+                .is_some_and(|log2| log2 < large.exponent)
+            {
+                let mut sum = large.clone();
+                sum.radius += 1;
+                return sum.round(precision);
+            }
+        }
+        let exponent = lhs.exponent.min(rhs.exponent);
+        let align = |value: Self| {
+            let shift = (value.exponent - exponent) as u64;
+            (value.mantissa << shift, value.radius << shift)
+        };
+        let ((lhs_mantissa, lhs_radius), (rhs_mantissa, rhs_radius)) = (align(lhs), align(rhs));
+        Self {
+            mantissa: lhs_mantissa + rhs_mantissa,
+            radius: lhs_radius + rhs_radius,
+            exponent,
+        }
+        .round(precision)
+    }
+
+    /// Returns the difference rounded to `precision` bits.
+    pub fn sub(&self, other: &Self, precision: u64) -> Self {
+        self.add(&other.neg(), precision)
+    }
+
+    /// Returns the product rounded to `precision` bits.
+    pub fn mul(&self, other: &Self, precision: u64) -> Self {
+        Self {
+            mantissa: &self.mantissa * &other.mantissa,
+            radius: self.mantissa.abs() * &other.radius
+                + other.mantissa.abs() * &self.radius
+                + &self.radius * &other.radius,
+            exponent: self.exponent + other.exponent,
+        }
+        .round(precision)
+    }
+
+    /// Returns the quotient rounded to `precision` bits.
+    /// Returns `EvalError::DivisionByZero` if the divisor is exactly zero and
+    /// `EvalError::InsufficientPrecision` if it is too imprecise to rule out zero.
+    pub fn div(&self, other: &Self, precision: u64) -> Result<Self, EvalError> {
+        if other.is_zero() {
+            return Err(EvalError::DivisionByZero);
+        }
+        let divisor = other.mantissa.abs();
+        if divisor <= other.radius {
+            return Err(EvalError::InsufficientPrecision);
+        }
+        if self.is_zero() {
+            return Ok(Self::zero());
+        }
+        let shift = (precision as i64 + divisor.bits() as i64 - self.mantissa.bits() as i64 + 2)
+            .max(0) as u64;
+        let scaled = &self.mantissa << shift;
+        let quotient = &scaled / &other.mantissa;
+        // |a/b - m_a/m_b| <= (|m_a| r_b + |m_b| r_a) / (|m_b| (|m_b| - r_b)), rounded up
+        let spread = (self.mantissa.abs() * &other.radius + &divisor * &self.radius) << shift;
+        let mut radius = spread / (&divisor * (&divisor - &other.radius));
+        if !radius.is_zero() || !(scaled % &other.mantissa).is_zero() {
+            radius += 2;
+        }
+        Ok(Self {
+            mantissa: quotient,
+            radius,
+            exponent: self.exponent - other.exponent - shift as i64,
+        }
+        .round(precision))
+    }
+
+    /// Returns the value divided by a positive integer, rounded to `precision` bits.
+    fn div_integer(&self, divisor: u64, precision: u64) -> Self {
+        let shift = (precision as i64 + 64 - self.mantissa.bits() as i64).max(0) as u64;
+        Self {
+            mantissa: (&self.mantissa << shift) / divisor,
+            radius: (&self.radius << shift) / divisor + 2,
+            exponent: self.exponent - shift as i64,
+        }
+        .round(precision)
+    }
+
+    /// Returns the value multiplied by an integer, rounded to `precision` bits.
+    fn mul_integer(&self, factor: impl Into<BigInt>, precision: u64) -> Self {
+        self.mul(&Self::from_integer(factor), precision)
+    }
+
+    /// Returns the value raised to an integer power, rounded to `precision` bits.
+    pub fn powi(&self, exponent: &BigInt, precision: u64) -> Result<Self, EvalError> {
+        let Some(mut remaining) = exponent.abs().to_u32() else {
+            return Err(EvalError::Domain(format!(
+                "{}^{} overflows",
+                self.to_f64(),
+                exponent
+            )));
+        };
+        let working = precision + GUARD_BITS;
+        let (mut result, mut square) = (Self::one(), self.clone());
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.mul(&square, working);
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                square = square.mul(&square, working);
+            }
+        }
+        if exponent.is_negative() {
+            Self::one().div(&result, precision)
+        } else {
+            Ok(result.round(precision))
+        }
+    }
+
+    /// Returns the value raised to a real power, rounded to `precision` bits.
+    /// Integer powers are computed by repeated multiplication, any other power of a positive
+    /// base as `exp(exponent * ln(base))`.
+    pub fn pow(&self, exponent: &Self, precision: u64) -> Result<Self, EvalError> {
+        if let Some(integer) = exponent.to_integer() {
+            if self.is_zero() && integer.is_negative() {
+                return Err(EvalError::DivisionByZero);
+            }
+            return self.powi(&integer, precision);
+        }
+        if self.is_zero() {
+            return if exponent.is_positive() {
+                Ok(Self::zero())
+            } else if exponent.is_negative() {
+                Err(EvalError::DivisionByZero)
+            } else {
+                Err(EvalError::InsufficientPrecision)
+            };
+        }
+        if self.is_negative() {
+            return Err(EvalError::Domain(format!(
+                "{}^{} is not a real number",
+                self.to_f64(),
+                exponent.to_f64()
+            )));
+        }
+        // The relative error of the result grows with the size of the exponent of exp
+        let logarithm = self.ln(precision + GUARD_BITS)?;
+        let extra = exponent
+            .mul(&logarithm, 64)
+            .magnitude_log2()
+            .unwrap_or(0)
+            .max(0) as u64;
+        let working = precision + GUARD_BITS + extra;
+        exponent.mul(&self.ln(working)?, working).exp(precision)
+    }
+
+    /// Returns the square root rounded to `precision` bits.
+    /// An interval reaching below zero is assumed to stand for a non-negative value.
+    pub fn sqrt(&self, precision: u64) -> Result<Self, EvalError> {
+        if self.is_negative() {
+            return Err(EvalError::Domain(format!(
+                "sqrt is undefined for {}",
+                self.to_f64()
+            )));
+        }
+        // Shift so the root has `precision` bits and the exponent stays even
+        let root_of = |mantissa: &BigInt| {
+            let wanted = (2 * precision + 2) as i64 - mantissa.bits() as i64;
+            let shift = (wanted.max(0) as u64).next_multiple_of(2) + (self.exponent & 1) as u64;
+            (mantissa << shift, shift)
+        };
+        if !self.is_positive() {
+            // The interval [0, sqrt(upper)]
+            let upper = self.upper();
+            if upper.is_zero() {
+                return Ok(Self::zero());
+            }
+            let (scaled, shift) = root_of(&upper);
+            let bound: BigInt = scaled.sqrt() + 1;
+            return Ok(Self {
+                mantissa: bound.clone(),
+                radius: bound,
+                exponent: (self.exponent - shift as i64) / 2 - 1,
+            });
+        }
+        let (scaled, shift) = root_of(&self.mantissa);
+        let root: BigInt = scaled.sqrt();
+        // |sqrt(x) - sqrt(m)| <= |x - m| / sqrt(m)
+        let radius = (&self.radius << shift) / &root + 2;
+        Ok(Self {
+            mantissa: root,
+            radius,
+            exponent: (self.exponent - shift as i64) / 2,
+        }
+        .round(precision))
+    }
+
+    /// Returns `e^x` rounded to `precision` bits.
+    pub fn exp(&self, precision: u64) -> Result<Self, EvalError> {
+        let Some(log2) = self.magnitude_log2() else {
+            return Ok(Self::one());
+        };
+        if log2 > 40 {
+            let upper = self.upper();
+            if upper.is_negative() && upper.bits() as i64 - 1 + self.exponent >= 40 {
+                // e^x < 2^x <= 2^(-2^40) for every x in the interval
+                return Ok(Self {
+                    mantissa: BigInt::one(),
+                    radius: BigInt::one(),
+                    exponent: -(1 << 40) - 1,
+                });
+            }
+            if self.mantissa.is_negative() {
+                return Err(EvalError::InsufficientPrecision);
+            }
+            return Err(EvalError::Domain(format!(
+                "exp({}) overflows",
+                self.to_f64()
+            )));
+        }
+        let working = precision + GUARD_BITS;
+        // x = k ln(2) + r with |r| <= ln(2) / 2, then r is halved s times so the series converges fast
+        let k = (self.to_f64() / LN_2).round() as i64;
+        let reduced = self.sub(
+            &Self::ln2(working + 64).mul_integer(k, working + 64),
+            working,
+        );
+        let halvings = reduced.magnitude_log2().map_or(0, |log2| (log2 + 8).max(0));
+        let working = working + halvings as u64;
+        let t = reduced.mul_pow2(-halvings);
+
+        let (mut sum, mut term) = (Self::one(), Self::one());
+        for n in 1.. {
+            term = term.mul(&t, working).div_integer(n, working);
+            sum = sum.add(&term, working);
+            match term.magnitude_log2() {
+                // The remaining terms add up to less than the last one, as |t| < 1/2
+                Some(log2) if log2 >= -(working as i64) => {}
+                Some(log2) => {
+                    sum = sum.add_error(log2, working);
+                    break;
+                }
+                None => break,
+            }
+        }
+        for _ in 0..halvings {
+            sum = sum.mul(&sum, working);
+        }
+        Ok(sum.mul_pow2(k).round(precision))
+    }
+
+    /// Returns the natural logarithm rounded to `precision` bits.
+    pub fn ln(&self, precision: u64) -> Result<Self, EvalError> {
+        if !self.is_positive() {
+            return if self.upper().is_positive() {
+                Err(EvalError::InsufficientPrecision)
+            } else {
+                Err(EvalError::Domain(format!(
+                    "ln is undefined for {}",
+                    self.to_f64()
+                )))
+            };
+        }
+        let working = precision + GUARD_BITS;
+        // x = 2^k y with y between 1/sqrt(2) and sqrt(2), then ln(y) = 2 atanh((y - 1) / (y + 1))
+        let mut k = self.mantissa.bits() as i64 + self.exponent;
+        let mut y = self.mul_pow2(-k);
+        if y.to_f64() < FRAC_1_SQRT_2 {
+            k -= 1;
+            y = y.mul_pow2(1);
+        }
+        let z = y
+            .sub(&Self::one(), working)
+            .div(&y.add(&Self::one(), working), working)?;
+        let logarithm = Self::arctan_series(&z, working, false).mul_pow2(1);
+        Ok(logarithm
+            .add(
+                &Self::ln2(working + 64).mul_integer(k, working + 64),
+                working,
+            )
+            .round(precision))
+    }
+
+    /// Returns the base-10 logarithm rounded to `precision` bits.
+    pub fn log10(&self, precision: u64) -> Result<Self, EvalError> {
+        let working = precision + GUARD_BITS;
+        self.ln(working)?
+            .div(&Self::from_integer(10).ln(working)?, precision)
+    }
+
+    /// Returns `atan(z)` for `atanh(z)` if `alternating` is false, from the power series
+    /// `z -/+ z^3/3 + z^5/5 -/+ ...`. The argument must be at most `1/2` in magnitude.
+    fn arctan_series(z: &Self, precision: u64, alternating: bool) -> Self {
+        let square = z.mul(z, precision);
+        let (mut sum, mut power) = (z.clone(), z.clone());
+        for n in 1u64.. {
+            power = power.mul(&square, precision);
+            let term = power.div_integer(2 * n + 1, precision);
+            sum = if alternating && n % 2 == 1 {
+                sum.sub(&term, precision)
+            } else {
+                sum.add(&term, precision)
+            };
+            match power.magnitude_log2() {
+                // The remaining terms add up to less than twice the next power, as |z| <= 1/2
+                Some(log2) if log2 >= -(precision as i64) => {}
+                Some(log2) => return sum.add_error(log2 + 1, precision),
+                None => return sum,
+            }
+        }
+        unreachable!()
+    }
+
+    /// Returns the sine and cosine rounded to `precision` bits.
+    pub fn sin_cos(&self, precision: u64) -> Result<(Self, Self), EvalError> {
+        let Some(log2) = self.magnitude_log2() else {
+            return Ok((Self::zero(), Self::one()));
+        };
+        // An interval wider than a period only bounds the result
+        if !self.radius.is_zero() && self.radius.bits() as i64 + self.exponent > 1 {
+            return Ok((Self::bounded_by(0), Self::bounded_by(0)));
+        }
+        let working = precision + GUARD_BITS;
+        // x = q pi/2 + t with |t| <= pi/4, where pi needs extra bits for a large x
+        let extra = log2.max(0) as u64;
+        let half_pi = Self::pi(working + extra).mul_pow2(-1);
+        let quarter = self.div(&half_pi, 64 + extra)?.round_to_integer();
+        let t = self.sub(
+            &half_pi.mul_integer(quarter.clone(), working + extra),
+            working,
+        );
+        if t.magnitude_log2().is_some_and(|log2| log2 > 0) {
+            return Ok((Self::bounded_by(0), Self::bounded_by(0)));
+        }
+
+        let (mut sin, mut cos, mut term) = (Self::zero(), Self::one(), Self::one());
+        for n in 1u64.. {
+            term = term.mul(&t, working).div_integer(n, working);
+            let signed = if n % 4 < 2 { term.clone() } else { term.neg() };
+            if n % 2 == 1 {
+                sin = sin.add(&signed, working);
+            } else {
+                cos = cos.add(&signed, working);
+            }
+            match term.magnitude_log2() {
+                // The remaining terms add up to less than the last one, as |t| < 1
+                Some(log2) if log2 >= -(working as i64) => {}
+                Some(log2) => {
+                    sin = sin.add_error(log2, working);
+                    cos = cos.add_error(log2, working);
+                    break;
+                }
+                None => break,
+            }
+        }
+        let (sin, cos) = match (quarter % 4u32).to_i64().unwrap_or(0).rem_euclid(4) {
+            0 => (sin, cos),
+            1 => (cos, sin.neg()),
+            2 => (sin.neg(), cos.neg()),
+            _ => (cos.neg(), sin),
+        };
+        Ok((sin.round(precision), cos.round(precision)))
+    }
+
+    /// Returns the arctangent rounded to `precision` bits.
+    pub fn atan(&self, precision: u64) -> Result<Self, EvalError> {
+        if self.is_zero() {
+            return Ok(Self::zero());
+        }
+        let working = precision + GUARD_BITS;
+        // atan(x) = sign(x) pi/2 - atan(1/x) brings the argument within [-1, 1]
+        let mut x = self.clone();
+        let mut offset = None;
+        if self.to_f64().abs() > 1.0 {
+            let half_pi = Self::pi(working).mul_pow2(-1);
+            offset = Some(if self.to_f64() > 0.0 {
+                half_pi
+            } else {
+                half_pi.neg()
+            });
+            x = Self::one().div(self, working)?;
+        }
+        // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))) makes the series converge fast
+        let mut doublings = 0;
+        while x.magnitude_log2().is_some_and(|log2| log2 > -4) {
+            if doublings == 8 {
+                return Ok(Self::bounded_by(1));
+            }
+            let root = Self::one()
+                .add(&x.mul(&x, working), working)
+                .sqrt(working)?;
+            x = x.div(&Self::one().add(&root, working), working)?;
+            doublings += 1;
+        }
+        let mut result = Self::arctan_series(&x, working, true).mul_pow2(doublings);
+        if let Some(offset) = offset {
+            result = offset.sub(&result, working);
+        }
+        Ok(result.round(precision))
+    }
+
+    /// Returns the arcsine rounded to `precision` bits, as `atan(x / sqrt(1 - x^2))`.
+    pub fn asin(&self, precision: u64) -> Result<Self, EvalError> {
+        let working = precision + GUARD_BITS;
+        let complement = Self::one().sub(&self.mul(self, working), working);
+        if complement.is_negative() {
+            return Err(EvalError::Domain(format!(
+                "asin is undefined for {}",
+                self.to_f64()
+            )));
+        }
+        if complement.is_zero() {
+            let half_pi = Self::pi(precision).mul_pow2(-1);
+            return Ok(if self.is_negative() {
+                half_pi.neg()
+            } else {
+                half_pi
+            });
+        }
+        self.div(&complement.sqrt(working)?, working)?
+            .atan(precision)
+    }
+
+    /// Returns the arccosine rounded to `precision` bits, as `pi/2 - asin(x)`.
+    pub fn acos(&self, precision: u64) -> Result<Self, EvalError> {
+        let working = precision + GUARD_BITS;
+        let asin = self.asin(working).map_err(|error| match error {
+            EvalError::Domain(_) => {
+                EvalError::Domain(format!("acos is undefined for {}", self.to_f64()))
+            }
+            error => error,
+        })?;
+        Ok(Self::pi(working)
+            .mul_pow2(-1)
+            .sub(&asin, working)
+            .round(precision))
+    }
+
+    /// Returns the hyperbolic sine, cosine or tangent rounded to `precision` bits, computed from
+    /// `e^x` and `e^-x`.
+    pub fn sinh_cosh_tanh(&self, name: &str, precision: u64) -> Result<Self, EvalError> {
+        let working = precision + GUARD_BITS;
+        let exp = self.exp(working)?;
+        let inverse = Self::one().div(&exp, working)?;
+        let result = match name {
+            "sinh" => exp.sub(&inverse, working).mul_pow2(-1),
+            "cosh" => exp.add(&inverse, working).mul_pow2(-1),
+            _ => exp
+                .sub(&inverse, working)
+                .div(&exp.add(&inverse, working), working)?,
+        };
+        Ok(result.round(precision))
+    }
+
+    /// Returns `pi` rounded to `precision` bits, from Machin's formula `pi = 16 atan(1/5) - 4 atan(1/239)`.
+    pub fn pi(precision: u64) -> Self {
+        let working = precision + GUARD_BITS;
+        let atan_inverse =
+            |x: u64| Self::arctan_series(&Self::one().div_integer(x, working), working, true);
+        atan_inverse(5)
+            .mul_integer(16, working)
+            .sub(&atan_inverse(239).mul_integer(4, working), working)
+            .round(precision)
+    }
+
+    /// Returns `ln(2) = 2 atanh(1/3)` rounded to `precision` bits.
+    pub fn ln2(precision: u64) -> Self {
+        let working = precision + GUARD_BITS;
+        let third = Self::one().div_integer(3, working);
+        Self::arctan_series(&third, working, false)
+            .mul_pow2(1)
+            .round(precision)
+    }
+
+    /// Returns `e` rounded to `precision` bits.
+    pub fn e(precision: u64) -> Self {
+        Self::one().exp(precision).expect("e^1 is finite")
+    }
+
+    /// Returns the golden ratio `(1 + sqrt(5)) / 2` rounded to `precision` bits.
+    pub fn phi(precision: u64) -> Self {
+        let working = precision + GUARD_BITS;
+        let root = Self::from_integer(5).sqrt(working).expect("5 is positive");
+        Self::one()
+            .add(&root, working)
+            .mul_pow2(-1)
+            .round(precision)
+    }
+
+    /// Returns the Euler–Mascheroni constant rounded to `precision` bits, using the
+    /// Brent–McMillan algorithm with `n` a power of two.
+    pub fn euler_gamma(precision: u64) -> Self {
+        // Each of the many iterations costs a few units in the last place
+        let working = precision + GUARD_BITS + 2 * u64::from(u64::BITS - precision.leading_zeros());
+        // The error of U/V is below pi e^(-4n) < 2^(2 - 5n), so 5n needs to exceed the precision
+        let mut log2_n = 1u32;
+        while (1u64 << log2_n) * 5 < working + 2 {
+            log2_n += 1;
+        }
+        let n = 1u64 << log2_n;
+        let n_squared = Self::from_integer(BigInt::one() << (2 * log2_n));
+
+        let mut a = Self::ln2(working).mul_integer(log2_n, working).neg();
+        let mut b = Self::one();
+        let (mut u, mut v) = (a.clone(), b.clone());
+        for k in 1u64.. {
+            b = b.mul(&n_squared, working).div_integer(k * k, working);
+            a = a
+                .mul(&n_squared, working)
+                .div_integer(k, working)
+                .add(&b, working)
+                .div_integer(k, working);
+            u = u.add(&a, working);
+            v = v.add(&b, working);
+            // Past k = 2n the terms shrink by more than a factor of 4 each step
+            let tail = a.magnitude_log2().max(b.magnitude_log2());
+            let scale = v.magnitude_log2().unwrap_or(0);
+            if k > 2 * n && tail.is_none_or(|log2| log2 < scale - working as i64) {
+                if let Some(log2) = tail {
+                    u = u.add_error(log2 + 1, working);
+                    v = v.add_error(log2 + 1, working);
+                }
+                break;
+            }
+        }
+        u.div(&v, working)
+            .expect("V is positive")
+            .add_error(2 - 5 * n as i64, working)
+            .round(precision)
+    }
+
+    /// Returns the exact lower and upper ends of the interval.
+    pub fn bounds(&self) -> (BigRational, BigRational) {
+        let to_rational = |end: BigInt| {
+            if self.exponent >= 0 {
+                BigRational::from_integer(end << self.exponent)
+            } else {
+                BigRational::new(end, BigInt::one() << (-self.exponent) as u64)
+            }
+        };
+        (to_rational(self.lower()), to_rational(self.upper()))
+    }
+
+    /// Returns the largest integer at most the value, or `None` if the interval contains an integer
+    /// other than its lower end, so that the value may lie on either side of it.
+    pub fn floor(&self) -> Option<BigInt> {
+        let floor = |end: BigInt| {
+            if self.exponent >= 0 {
+                end << self.exponent
+            } else {
+                end >> (-self.exponent) as u64
+            }
+        };
+        let lower = floor(self.lower());
+        (lower == floor(self.upper())).then_some(lower)
+    }
+
+    /// Returns the value rounded to `digits` significant digits, or `None` if the interval is
+    /// too wide for the digits to be certain, that is if its two ends round differently.
+    pub fn to_digits(&self, digits: usize) -> Option<String> {
+        let lower = format_dyadic(&self.lower(), self.exponent, digits);
+        let upper = format_dyadic(&self.upper(), self.exponent, digits);
+        (lower == upper).then_some(lower)
+    }
+
+    /// Evaluates a value at increasing precision until it is known to `digits` significant digits.
+    /// This is how guaranteed digits are produced: `evaluate` is called with a precision in bits,
+    /// and retried with twice the precision while the result is too imprecise.
+    ///
+    /// # Arguments
+    ///
+    /// * `digits` - The number of significant digits, at least 1.
+    /// * `evaluate` - Computes the value at a given precision in bits.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, EvalError>` - The decimal digits, or `EvalError::InsufficientPrecision` if
+    ///   the value could not be pinned down, as happens for a value that is exactly zero without
+    ///   being written as zero.
+    pub fn evaluate_to_digits(
+        digits: usize,
+        mut evaluate: impl FnMut(u64) -> Result<Self, EvalError>,
+    ) -> Result<String, EvalError> {
+        let digits = digits.max(1);
+        let initial = (digits as f64 * std::f64::consts::LOG2_10).ceil() as u64 + GUARD_BITS;
+        let mut precision = initial;
+        loop {
+            match evaluate(precision) {
+                Ok(value) => {
+                    if let Some(digits) = value.to_digits(digits) {
+                        return Ok(digits);
+                    }
+                }
+                Err(EvalError::InsufficientPrecision) => {}
+                Err(error) => return Err(error),
+            }
+            if precision >= initial * MAX_PRECISION_GROWTH {
+                return Err(EvalError::InsufficientPrecision);
+            }
+            precision *= 2;
+        }
+    }
+}
+
+/// Formats `mantissa * 2^exponent` with `digits` significant digits, rounding half away from zero.
+fn format_dyadic(mantissa: &BigInt, exponent: i64, digits: usize) -> String {
+    if mantissa.is_zero() {
+        return "0".to_string();
+    }
+    // Enough decimal places for `digits + 1` significant digits, as 2^-k >= 10^-k
+    let leading = mantissa.bits() as i64 - 1 + exponent;
+    let places = digits as u32 + 1 + (-leading).max(0) as u32;
+    let scaled = mantissa.abs() * num_traits::pow(BigInt::from(10), places as usize);
+    let scaled = if exponent >= 0 {
+        scaled << exponent
+    } else {
+        scaled >> (-exponent) as u64
+    };
+    let scaled = if mantissa.is_negative() {
+        -scaled
+    } else {
+        scaled
+    };
+    format_significant(&scaled, places, digits as u32)
+}
+
+/// Formats `scaled / 10^precision` with `digits` significant digits, rounding half away from zero.
+fn format_significant(scaled: &BigInt, precision: u32, digits: u32) -> String {
+    let sign = if scaled.is_negative() { "-" } else { "" };
+    let all_digits = scaled.abs().to_string();
+    if all_digits == "0" {
+        return "0".to_string();
+    }
+    let digits = digits as usize;
+    // Position of the decimal point relative to the first significant digit
+    let mut point = all_digits.len() as i64 - i64::from(precision);
+
+    let mut significant: Vec<u8> = all_digits.bytes().take(digits).collect();
+    if all_digits
+        .as_bytes()
+        .get(digits)
+        .is_some_and(|next| *next >= b'5')
+    {
+        let mut index = significant.len();
+        loop {
+            if index == 0 {
+                significant.insert(0, b'1');
+                significant.truncate(digits);
+                point += 1;
+                break;
+            }
+            index -= 1;
+            if significant[index] == b'9' {
+                significant[index] = b'0';
+            } else {
+                significant[index] += 1;
+                break;
+            }
+        }
+    }
+    let significant = String::from_utf8(significant).unwrap();
+
+    if point <= 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-point) as usize), significant)
+    } else if point as usize >= significant.len() {
+        format!(
+            "{}{}{}",
+            sign,
+            significant,
+            "0".repeat(point as usize - significant.len())
+        )
+    } else {
+        let (whole, fraction) = significant.split_at(point as usize);
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates `f` to 30 significant digits.
+    fn digits(f: impl Fn(u64) -> Result<BigFloat, EvalError>) -> String {
+        BigFloat::evaluate_to_digits(30, f).unwrap()
+    }
+
+    #[test]
+    fn bigfloat_exact_arithmetic() {
+        let half = BigFloat::from_rational(&BigRational::new(1.into(), 2.into()), 64);
+        let three = BigFloat::from_integer(3);
+        assert_eq!(half.add(&three, 64).to_digits(5).unwrap(), "3.5000");
+        assert_eq!(half.mul(&three, 64).to_digits(3).unwrap(), "1.50");
+        assert_eq!(
+            three
+                .powi(&BigInt::from(-2), 64)
+                .unwrap()
+                .to_digits(4)
+                .unwrap(),
+            "0.1111"
+        );
+        assert_eq!(
+            BigFloat::from_f64(0.1).unwrap().to_digits(20).unwrap(),
+            "0.10000000000000000555"
+        );
+        assert_eq!(
+            BigFloat::from_integer(1).div(&BigFloat::zero(), 64),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn bigfloat_constants() {
+        assert_eq!(
+            digits(|p| Ok(BigFloat::pi(p))),
+            "3.14159265358979323846264338328"
+        );
+        assert_eq!(
+            digits(|p| Ok(BigFloat::e(p))),
+            "2.71828182845904523536028747135"
+        );
+        assert_eq!(
+            digits(|p| Ok(BigFloat::ln2(p))),
+            "0.693147180559945309417232121458"
+        );
+        assert_eq!(
+            digits(|p| Ok(BigFloat::euler_gamma(p))),
+            "0.577215664901532860606512090082"
+        );
+    }
+
+    #[test]
+    fn bigfloat_elementary_functions() {
+        let two = BigFloat::from_integer(2);
+        for (result, expected) in [
+            (digits(|p| two.sqrt(p)), "1.41421356237309504880168872421"),
+            (digits(|p| two.exp(p)), "7.38905609893065022723042746058"),
+            (digits(|p| two.ln(p)), "0.693147180559945309417232121458"),
+            (digits(|p| two.log10(p)), "0.301029995663981195213738894724"),
+            (
+                digits(|p| Ok(two.sin_cos(p)?.0)),
+                "0.909297426825681695396019865912",
+            ),
+            (
+                digits(|p| Ok(two.sin_cos(p)?.1)),
+                "-0.416146836547142386997568229501",
+            ),
+            (digits(|p| two.atan(p)), "1.10714871779409050301706546018"),
+            (
+                digits(|p| two.neg().exp(p)),
+                "0.135335283236612691893999494972",
+            ),
+            (
+                digits(|p| two.sinh_cosh_tanh("tanh", p)),
+                "0.964027580075816883946413724101",
+            ),
+            (
+                digits(|p| BigFloat::from_f64(0.5).unwrap().asin(p)),
+                "0.523598775598298873077107230547",
+            ),
+            (
+                digits(|p| two.pow(&BigFloat::from_f64(0.5).unwrap(), p)),
+                "1.41421356237309504880168872421",
+            ),
+        ] {
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn bigfloat_large_arguments() {
+        let large = BigFloat::from_integer(BigInt::from(10).pow(30));
+        assert_eq!(
+            digits(|p| Ok(large.sin_cos(p)?.0)),
+            "-0.0901169019121380580303864289530"
+        );
+        assert_eq!(
+            digits(|p| BigFloat::from_integer(100).exp(p)),
+            format!("268811714181613544841262555158{}", "0".repeat(14))
+        );
+    }
+
+    #[test]
+    fn bigfloat_exp_of_huge_arguments() {
+        let huge = BigFloat::from_integer(BigInt::one() << 50u32);
+        assert!(matches!(huge.exp(64), Err(EvalError::Domain(_))));
+        let tiny = huge.neg().exp(64).unwrap();
+        // The enclosure [0, 2^(-2^40)]
+        assert_eq!(
+            tiny.mul_pow2(1 << 40).bounds(),
+            (BigRational::zero(), BigRational::one())
+        );
+        assert_eq!(
+            digits(|p| Ok(BigFloat::one().add(&huge.neg().exp(p)?, p))),
+            format!("1.{}", "0".repeat(29))
+        );
+    }
+
+    #[test]
+    fn bigfloat_domain_errors() {
+        let minus_one = BigFloat::from_integer(-1);
+        assert!(matches!(minus_one.ln(64), Err(EvalError::Domain(_))));
+        assert!(matches!(minus_one.sqrt(64), Err(EvalError::Domain(_))));
+        assert!(matches!(
+            BigFloat::from_integer(2).asin(64),
+            Err(EvalError::Domain(_))
+        ));
+    }
+
+    #[test]
+    fn bigfloat_uncertain_zero() {
+        // ln(2) + ln(1/2) is exactly zero, but no precision can prove it
+        let result = BigFloat::evaluate_to_digits(10, |p| {
+            let two = BigFloat::from_integer(2);
+            Ok(two.ln(p)?.add(&BigFloat::one().div(&two, p)?.ln(p)?, p))
+        });
+        assert_eq!(result, Err(EvalError::InsufficientPrecision));
+    }
+}
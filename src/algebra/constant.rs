@@ -1,12 +1,20 @@
 use std::any::Any;
 
+use num_complex::Complex64;
+use num_traits::Signed;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
 use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::number::Number;
 
 /// `Constant` struct represents a constant value in an expression tree.
-/// It contains a `value` field which is a `f64`.
+/// It contains a `value` field which is a `Number`, exact unless a floating point number was requested.
 #[derive(Debug, Clone)]
 pub struct Constant {
-    pub value: f64,
+    pub value: Number,
 }
 
 impl Constant {
@@ -14,23 +22,121 @@ impl Constant {
     ///
     /// # Arguments
     ///
-    /// * `value` - A floating point number that represents the value of the constant.
-    pub fn new(value: f64) -> Self {
-        Self { value }
+    /// * `value` - The value of the constant. Integers and fractions are exact, an `f64` gives a floating point constant.
+    pub fn new(value: impl Into<Number>) -> Self {
+        Self {
+            value: value.into(),
+        }
+    }
+
+    /// Returns the value of an expression if it is a `Constant`.
+    pub fn value_of(op: &Expr) -> Option<&Number> {
+        match op {
+            Expr::Constant(constant) => Some(&constant.value),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the constant is written as a sum, such as `3 + 4i`,
+    /// and needs parentheses when it is an operand of anything but a sum.
+    pub fn is_sum(&self) -> bool {
+        !self.value.is_real() && !self.value.re().is_zero()
+    }
+
+    /// Returns `true` if the constant is written as a fraction or as a sum, such as `1/3`
+    /// or `3 + 4i`, and needs parentheses when it is the base of a power.
+    pub fn is_compound(&self) -> bool {
+        match &self.value {
+            Number::Exact(_) => !self.value.is_integer(),
+            Number::Float(_) => false,
+            value => !value.re().is_zero() || !value.im().is_integer(),
+        }
+    }
+}
+
+/// Returns the Typist string of a real number, writing an exact non-integer as a fraction.
+fn real_to_typist(value: &Number) -> String {
+    match value {
+        Number::Exact(fraction) if !fraction.is_integer() => {
+            let sign = if value.is_negative() { "-" } else { "" };
+            format!(
+                "{}frac({}, {})",
+                sign,
+                fraction.numer().abs(),
+                fraction.denom()
+            )
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Returns the LaTeX string of a real number, writing an exact non-integer as a fraction.
+fn real_to_latex(value: &Number) -> String {
+    match value {
+        Number::Exact(fraction) if !fraction.is_integer() => {
+            let sign = if value.is_negative() { "-" } else { "" };
+            format!(
+                "{}\\frac{{{}}}{{{}}}",
+                sign,
+                fraction.numer().abs(),
+                fraction.denom()
+            )
+        }
+        _ => value.to_string(),
     }
 }
 
 impl Expression for Constant {
-    /// Evaluates the expression and returns a new expression.
-    /// For a `Constant`, it returns a clone of itself.
-    fn eval(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    /// Evaluates the expression to a number.
+    /// For a `Constant`, it returns its value.
+    /// Returns an `EvalError::Domain` if the constant is not real.
+    fn eval(&self, _env: &Environment) -> Result<f64, EvalError> {
+        if !self.value.is_real() {
+            return Err(EvalError::Domain(format!(
+                "{} is not a real number",
+                self.value
+            )));
+        }
+        Ok(self.value.to_f64())
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// For a `Constant`, it returns its value.
+    fn eval_complex(&self, _env: &Environment) -> Result<Complex64, EvalError> {
+        Ok(self.value.to_complex64())
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// Exact values are rounded to `precision` bits and floating point values are taken as they are.
+    fn eval_big(&self, _env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        match &self.value {
+            Number::Exact(value) => Ok(BigFloat::from_rational(value, precision)),
+            Number::Float(value) => BigFloat::from_f64(*value)
+                .ok_or_else(|| EvalError::Domain(format!("{} is not a finite number", value))),
+            _ => Err(EvalError::Domain(format!(
+                "{} is not a real number",
+                self.value
+            ))),
+        }
+    }
+
+    /// Evaluates the expression to an interval enclosing its value.
+    /// Exact values are enclosed by the nearest `f64` on either side.
+    fn eval_interval(&self, _env: &Environment) -> Result<Interval, EvalError> {
+        match &self.value {
+            Number::Exact(value) => Ok(Interval::from_rational(value)),
+            Number::Float(value) => Ok(Interval::point(*value)),
+            _ => Err(EvalError::Domain(format!(
+                "{} is not a real number",
+                self.value
+            ))),
+        }
     }
 
     /// Simplifies the expression and returns a new simplified expression.
     /// For a `Constant`, it returns a clone of itself.
-    fn simplify(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    fn simplify(&self) -> Expr {
+        Expr::from(self.clone())
     }
 
     /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
@@ -48,8 +154,48 @@ impl Expression for Constant {
     }
 
     /// Returns a Typist string for the expression.
-    /// For a `Constant`, it directly returns the string representation of its value.
+    /// For a `Constant`, it returns its value, with exact non-integer parts written as fractions
+    /// such as `frac(1, 3)` and imaginary parts followed by `i`.
     fn to_typist(&self) -> String {
-        self.value.to_string()
+        if self.value.is_real() {
+            return real_to_typist(&self.value);
+        }
+        let (re, im) = (self.value.re(), self.value.im());
+        let magnitude = if im.is_negative() { -&im } else { im.clone() };
+        let imaginary = if magnitude.is_one() {
+            "i".to_string()
+        } else if magnitude.is_integer() || !magnitude.is_exact() {
+            format!("{}i", magnitude)
+        } else {
+            format!("{} i", real_to_typist(&magnitude))
+        };
+        match (re.is_zero(), im.is_negative()) {
+            (true, false) => imaginary,
+            (true, true) => format!("-{}", imaginary),
+            (false, false) => format!("{} + {}", real_to_typist(&re), imaginary),
+            (false, true) => format!("{} - {}", real_to_typist(&re), imaginary),
+        }
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Constant`, it returns its value, with exact non-integer parts written as fractions
+    /// such as `\frac{1}{3}` and imaginary parts followed by `i`.
+    fn to_latex(&self) -> String {
+        if self.value.is_real() {
+            return real_to_latex(&self.value);
+        }
+        let (re, im) = (self.value.re(), self.value.im());
+        let magnitude = if im.is_negative() { -&im } else { im.clone() };
+        let imaginary = if magnitude.is_one() {
+            "i".to_string()
+        } else {
+            format!("{}i", real_to_latex(&magnitude))
+        };
+        match (re.is_zero(), im.is_negative()) {
+            (true, false) => imaginary,
+            (true, true) => format!("-{}", imaginary),
+            (false, false) => format!("{} + {}", real_to_latex(&re), imaginary),
+            (false, true) => format!("{} - {}", real_to_latex(&re), imaginary),
+        }
     }
 }
@@ -0,0 +1,389 @@
+use std::fmt::{Display, Formatter};
+
+use crate::algebra::constant::Constant;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::number::Number;
+
+// Binding power of a written expression, matching the precedence of the parser for infix form.
+// An operand is parenthesized when it binds more loosely than its position requires.
+const SUM: u8 = 1;
+const PRODUCT: u8 = 2;
+const UNARY: u8 = 3;
+const POWER: u8 = 4;
+const ATOM: u8 = 5;
+
+/// How an expression is written: on a single line by `Display`, or typeset by the Typst, LaTeX
+/// and MathML output, where divisions and exact fractions are set as fractions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Layout {
+    Infix,
+    Typeset,
+}
+
+/// Where an operand is written within its parent node, which decides whether it is parenthesized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Position {
+    /// A term of a sum after the first one, written after a `+`.
+    Addend,
+    /// A term of a sum written after a `-`.
+    Subtrahend,
+    /// The first factor of a product that is not written with a leading minus.
+    FirstFactor,
+    /// A factor of a product after a leading minus or another factor.
+    Factor,
+    /// The operand of a negation.
+    Negated,
+    /// The numerator of a division.
+    Numerator,
+    /// The denominator of a division.
+    Denominator,
+    /// The base of a power.
+    Base,
+    /// The exponent of a power.
+    Exponent,
+}
+
+/// Returns how tightly `expr` binds when it is written in `layout`.
+/// A typeset fraction binds like a power, so it is only parenthesized as the base of a power.
+fn precedence(expr: &Expr, layout: Layout) -> u8 {
+    match expr {
+        Expr::Constant(constant) => number_precedence(&constant.value, layout),
+        Expr::NamedConstant(_) | Expr::Variable(_) | Expr::Function(_) | Expr::Custom(_) => ATOM,
+        Expr::Add(_) => SUM,
+        Expr::Multiply(_) => PRODUCT,
+        Expr::Divide(_) if layout == Layout::Infix => PRODUCT,
+        Expr::Divide(_) if negated(expr).is_some() => UNARY,
+        Expr::Divide(_) => POWER,
+        Expr::Negate(_) => UNARY,
+        Expr::Power(_) => POWER,
+    }
+}
+
+/// Returns how tightly a number binds when it is written in `layout`, e.g. `-2` as a negation,
+/// `1/3` and `4i` as products, and `3 + 4i` as a sum.
+fn number_precedence(value: &Number, layout: Layout) -> u8 {
+    match value {
+        Number::Exact(_) if !value.is_integer() && value.is_negative() => UNARY,
+        Number::Exact(_) if !value.is_integer() && layout == Layout::Infix => PRODUCT,
+        Number::Exact(_) if !value.is_integer() => POWER,
+        Number::Exact(_) | Number::Float(_) if value.is_negative() => UNARY,
+        Number::Exact(_) | Number::Float(_) => ATOM,
+        _ if !value.re().is_zero() => SUM,
+        _ if value.im().is_negative() => UNARY,
+        _ if value.im().is_one() => ATOM,
+        _ => PRODUCT,
+    }
+}
+
+/// Returns `true` if `expr` is written with a leading minus.
+fn starts_with_minus(expr: &Expr) -> bool {
+    negated(expr).is_some()
+        || matches!(expr, Expr::Constant(c) if c.value.re().is_zero() && c.value.im().is_negative())
+}
+
+/// Returns `true` if `expr` is parenthesized when it is written at `position` in `layout`.
+/// This is the single place deciding on parentheses for `Display` and the Typst, LaTeX and
+/// MathML output, so they only differ where their notation does.
+pub(crate) fn needs_parentheses(expr: &Expr, position: Position, layout: Layout) -> bool {
+    let precedence = precedence(expr, layout);
+    match (position, layout) {
+        (Position::Addend, Layout::Infix) | (Position::Subtrahend, _) => {
+            precedence <= SUM || starts_with_minus(expr)
+        }
+        // Typeset sums are associative, so `x + (y + z)` is set as `x + y + z`
+        (Position::Addend, Layout::Typeset) => starts_with_minus(expr),
+        (Position::FirstFactor, _) => precedence < PRODUCT,
+        (Position::Factor | Position::Negated, Layout::Infix) => precedence <= UNARY,
+        // A typeset product is written by juxtaposition, so `-2 x` needs no parentheses
+        (Position::Factor | Position::Negated, Layout::Typeset) => {
+            precedence <= SUM || starts_with_minus(expr)
+        }
+        (Position::Numerator, Layout::Infix) => precedence < PRODUCT,
+        (Position::Denominator | Position::Exponent, Layout::Infix) => precedence <= UNARY,
+        (Position::Base, _) => precedence <= POWER,
+        // The parts of a fraction and a superscript are grouped by the notation itself
+        (Position::Numerator | Position::Denominator | Position::Exponent, Layout::Typeset) => {
+            false
+        }
+    }
+}
+
+/// Returns the expression whose negation `expr` is, if `expr` is written with a leading minus
+/// that can be turned into a subtraction: a negation, a negative real constant, a product with
+/// a negative real coefficient, or a division with such a numerator.
+pub(crate) fn negated(expr: &Expr) -> Option<Expr> {
+    let negative = |op: &Expr| match Constant::value_of(op) {
+        Some(value) if value.is_real() && value.is_negative() => Some(-value),
+        _ => None,
+    };
+    match expr {
+        Expr::Negate(negate) => Some(negate.op.clone()),
+        Expr::Constant(_) => negative(expr).map(|value| Expr::from(Constant::new(value))),
+        Expr::Multiply(multiply) => {
+            let (coefficient, rest) = multiply.ops.split_first()?;
+            let coefficient = negative(coefficient)?;
+            match rest {
+                [] => None,
+                [op] if coefficient.is_one() => Some(op.clone()),
+                _ if coefficient.is_one() => Some(Expr::from(Multiply::new(rest.to_vec()))),
+                _ => {
+                    let mut ops = vec![Expr::from(Constant::new(coefficient))];
+                    ops.extend(rest.iter().cloned());
+                    Some(Expr::from(Multiply::new(ops)))
+                }
+            }
+        }
+        Expr::Divide(divide) => {
+            let numerator = negated(&divide.numerator)?;
+            Some(Expr::from(Divide::new(
+                numerator,
+                divide.denominator.clone(),
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the product `ops` without its leading coefficient if that is `-1`, which is written as
+/// a minus sign, together with `true` in that case.
+pub(crate) fn signed_factors(ops: &[Expr]) -> (bool, &[Expr]) {
+    match ops.split_first() {
+        Some((first, rest))
+            if !rest.is_empty() && Constant::value_of(first) == Some(&Number::from(-1)) =>
+        {
+            (true, rest)
+        }
+        _ => (false, ops),
+    }
+}
+
+/// Returns the Typst string of `expr`, in parentheses if it needs them at `position`.
+pub(crate) fn typst_operand(expr: &Expr, position: Position) -> String {
+    if needs_parentheses(expr, position, Layout::Typeset) {
+        format!("({})", expr.to_typist())
+    } else {
+        expr.to_typist()
+    }
+}
+
+/// Returns the LaTeX string of `expr`, in sized parentheses if it needs them at `position`.
+pub(crate) fn latex_operand(expr: &Expr, position: Position) -> String {
+    if needs_parentheses(expr, position, Layout::Typeset) {
+        format!("\\left({}\\right)", expr.to_latex())
+    } else {
+        expr.to_latex()
+    }
+}
+
+/// Writes `expr`, in parentheses if it needs them at `position`.
+fn write_operand(f: &mut Formatter<'_>, expr: &Expr, position: Position) -> std::fmt::Result {
+    if needs_parentheses(expr, position, Layout::Infix) {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+/// Writes the expression in infix notation, such as `x - 2*y` or `(x + 1)^2/sin(x)`, with
+/// parentheses only where the precedence of the operators requires them.
+/// The output of an expression the parser can produce is parsed back by
+/// [`parse_expression`](crate::algebra::parser::parse_expression) into an equal expression,
+/// although a subtraction or a negative coefficient may be read back as a `Negate` node.
+/// Other expressions are written the same way but may not read back as themselves: a call to a
+/// function that is not built in, such as `erf(x)`, is read back as a product, and one with
+/// several arguments not at all, while a variable named like a constant, such as `e`, `pi` or
+/// `i`, is read back as that constant. Floating point constants are written as their shortest
+/// decimal, with an exponent for very large and small magnitudes, which is read back as an exact
+/// number, and infinities and NaN are not read back at all. Custom nodes are written in their Typst form.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Constant(constant) => write!(f, "{}", constant.value),
+            Expr::NamedConstant(named) => write!(f, "{}", named.name()),
+            Expr::Variable(variable) => write!(f, "{}", variable.name),
+            Expr::Add(add) if add.ops.is_empty() => write!(f, "0"),
+            Expr::Add(add) => {
+                write!(f, "{}", add.ops[0])?;
+                for op in &add.ops[1..] {
+                    match negated(op) {
+                        Some(positive) => {
+                            write!(f, " - ")?;
+                            write_operand(f, &positive, Position::Subtrahend)?;
+                        }
+                        None => {
+                            write!(f, " + ")?;
+                            write_operand(f, op, Position::Addend)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Expr::Multiply(multiply) if multiply.ops.is_empty() => write!(f, "1"),
+            Expr::Multiply(multiply) => {
+                // A coefficient of -1 is written as a minus sign
+                let (signed, ops) = signed_factors(&multiply.ops);
+                if signed {
+                    write!(f, "-")?;
+                }
+                for (index, op) in ops.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "*")?;
+                    }
+                    let position = if index == 0 && !signed {
+                        Position::FirstFactor
+                    } else {
+                        Position::Factor
+                    };
+                    write_operand(f, op, position)?;
+                }
+                Ok(())
+            }
+            Expr::Divide(divide) => {
+                write_operand(f, &divide.numerator, Position::Numerator)?;
+                write!(f, "/")?;
+                write_operand(f, &divide.denominator, Position::Denominator)
+            }
+            Expr::Power(power) => {
+                write_operand(f, &power.base, Position::Base)?;
+                write!(f, "^")?;
+                write_operand(f, &power.exponent, Position::Exponent)
+            }
+            Expr::Negate(negate) => {
+                write!(f, "-")?;
+                write_operand(f, &negate.op, Position::Negated)
+            }
+            Expr::Function(function) => {
+                write!(f, "{}(", function.name)?;
+                for (index, arg) in function.args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Custom(custom) => write!(f, "{}", custom.to_typist()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::eval::Environment;
+    use crate::algebra::expression::Expression;
+    use crate::algebra::function::Function;
+    use crate::algebra::named_constant::NamedConstant;
+    use crate::algebra::negate::Negate;
+    use crate::algebra::parser::parse_expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    fn display(input: &str) -> String {
+        parse_expression(input).unwrap().to_string()
+    }
+
+    #[test]
+    fn display_omits_redundant_parentheses() {
+        assert_eq!(display("x - 2y"), "x - 2*y");
+        assert_eq!(display("((a + b) + c)"), "a + b + c");
+        assert_eq!(display("a + (b + c)"), "a + (b + c)");
+        assert_eq!(display("a - (b - c)"), "a - (b - c)");
+        assert_eq!(display("(a*b)/(c*d)"), "a*b/(c*d)");
+        assert_eq!(display("(x+1)^2 / sin(x)"), "(x + 1)^2/sin(x)");
+        assert_eq!(display("2^3^2"), "2^3^2");
+        assert_eq!(display("(2^3)^2"), "(2^3)^2");
+        assert_eq!(display("-x^2"), "-x^2");
+        assert_eq!(display("(-x)^2"), "(-x)^2");
+        assert_eq!(display("2^-x"), "2^(-x)");
+        assert_eq!(display("-(a + b)"), "-(a + b)");
+        assert_eq!(display("a * -b"), "a*(-b)");
+    }
+
+    #[test]
+    fn display_simplified_expressions() {
+        let simplified = |input: &str| parse_expression(input).unwrap().simplify().to_string();
+        assert_eq!(simplified("x - 2y"), "x - 2*y");
+        assert_eq!(simplified("-x + 1"), "-x + 1");
+        assert_eq!(simplified("y - x - 1"), "-x + y - 1");
+        assert_eq!(simplified("x/3"), "x/3");
+        assert_eq!(simplified("(3 + 4i) x"), "(3 + 4i)*x");
+        assert_eq!(simplified("2^(1/2)"), "2^(1/2)");
+    }
+
+    #[test]
+    fn display_round_trips_through_parser() {
+        let inputs = [
+            "x - 2y",
+            "a - (b + c) - -d",
+            "-(x^2)^3 / (1 - x)",
+            "sin(x)^2 + cos(x)^2",
+            "2^-1 + e^(i pi)",
+            "(a / b) / (c / d)",
+            "-3/4 x^-2",
+            "sqrt(-x) * ln(abs(-2x))",
+            "-(-2) - -(-x)",
+            "phi^2 - phi - 1 + gamma",
+        ];
+        for input in inputs {
+            let parsed = parse_expression(input).unwrap();
+            let reparsed = parse_expression(&parsed.to_string()).unwrap();
+            assert_eq!(reparsed, parsed, "{} was written as {}", input, parsed);
+            let simplified = parsed.simplify();
+            let reparsed = parse_expression(&simplified.to_string()).unwrap();
+            assert_eq!(reparsed.simplify(), simplified, "{}", simplified);
+        }
+        let negated = Expr::from(Negate::new(Expr::from(Constant::new(-2))));
+        assert_eq!(negated.to_string(), "-(-2)");
+        assert_eq!(
+            parse_expression("-(-2)").unwrap().simplify(),
+            negated.simplify()
+        );
+        let phi = Expr::from(NamedConstant::Phi);
+        assert_eq!(parse_expression(&phi.to_string()).unwrap(), phi);
+        // Floats are read back as the exact value of their shortest decimal
+        for value in [0.1, 1e300, -2.5e-7, 1234.5] {
+            let written = Expr::from(Constant::new(value)).to_string();
+            let reparsed = parse_expression(&written).unwrap().simplify();
+            assert_eq!(reparsed.eval(&Environment::new()), Ok(value), "{}", written);
+        }
+        assert_eq!(Expr::from(Constant::new(1e300)).to_string(), "1e300");
+    }
+
+    #[test]
+    fn display_does_not_round_trip_what_the_parser_reads_differently() {
+        let x = || Expr::from(Variable::new("x"));
+        let erf = Expr::from(Function::new("erf", vec![x()]));
+        assert_eq!(erf.to_string(), "erf(x)");
+        assert_ne!(parse_expression(&erf.to_string()).unwrap(), erf);
+
+        let g = Expr::from(Function::new("g", vec![x(), Variable::new("y").into()]));
+        assert_eq!(g.to_string(), "g(x, y)");
+        assert!(parse_expression(&g.to_string()).is_err());
+
+        for name in ["e", "pi", "i"] {
+            let variable = Expr::from(Variable::new(name));
+            assert_eq!(variable.to_string(), name);
+            assert_ne!(parse_expression(name).unwrap(), variable, "{}", name);
+        }
+    }
+
+    #[test]
+    fn display_negative_terms_as_subtraction() {
+        let x = Expr::from(Variable::new("x"));
+        let y = Expr::from(Variable::new("y"));
+        let expr = Expr::from(crate::algebra::add::Add::new(vec![
+            x.clone(),
+            Expr::from(Constant::new(-2)),
+            Expr::from(Multiply::new(vec![
+                Expr::from(Constant::new(-1)),
+                y.clone(),
+            ])),
+            Expr::from(Multiply::new(vec![Expr::from(Constant::new(-3.5)), x, y])),
+        ]));
+        assert_eq!(expr.to_string(), "x - 2 - y - 3.5*x*y");
+    }
+}
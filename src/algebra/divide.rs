@@ -0,0 +1,255 @@
+use std::any::Any;
+
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::display::negated;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+
+/// `Divide` struct represents a division operation in an expression tree.
+/// It contains a `numerator` and a `denominator`, both of which are `Expr` nodes.
+pub struct Divide {
+    pub numerator: Expr,
+    pub denominator: Expr,
+}
+
+impl Divide {
+    /// Constructs a new `Divide` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `numerator` - The expression being divided.
+    /// * `denominator` - The expression to divide by.
+    pub fn new(numerator: Expr, denominator: Expr) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+impl Expression for Divide {
+    /// Evaluates the expression to a number.
+    /// Returns `EvalError::DivisionByZero` if the denominator evaluates to zero.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        let numerator = self.numerator.eval(env)?;
+        let denominator = self.denominator.eval(env)?;
+        if denominator == 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+        Ok(numerator / denominator)
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// Returns `EvalError::DivisionByZero` if the denominator evaluates to zero.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        let numerator = self.numerator.eval_complex(env)?;
+        let denominator = self.denominator.eval_complex(env)?;
+        if denominator == Complex64::new(0.0, 0.0) {
+            return Err(EvalError::DivisionByZero);
+        }
+        Ok(numerator / denominator)
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// Returns `EvalError::DivisionByZero` if the denominator is exactly zero.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        let numerator = self.numerator.eval_big(env, precision)?;
+        let denominator = self.denominator.eval_big(env, precision)?;
+        numerator.div(&denominator, precision)
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// A denominator containing zero gives an unbounded interval, and
+    /// `EvalError::DivisionByZero` is returned if it is exactly zero.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        let numerator = self.numerator.eval_interval(env)?;
+        let denominator = self.denominator.eval_interval(env)?;
+        numerator.div(&denominator)
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// This method eliminates division by 1, reduces `0 / x` to 0 and evaluates constant division.
+    /// Quotients of exact constants stay exact, so `1/3` is a `Constant` holding the fraction,
+    /// and division by a constant zero is left untouched.
+    fn simplify(&self) -> Expr {
+        let numerator = self.numerator.simplify();
+        let denominator = self.denominator.simplify();
+
+        match (
+            Constant::value_of(&numerator),
+            Constant::value_of(&denominator),
+        ) {
+            (_, Some(d)) if d.is_zero() => Expr::from(Self::new(numerator, denominator)),
+            (_, Some(d)) if d.is_one() => numerator,
+            (Some(n), _) if n.is_zero() => numerator,
+            (Some(n), Some(d)) => match n.checked_div(d) {
+                Some(quotient) => Expr::from(Constant::new(quotient)),
+                None => Expr::from(Self::new(numerator, denominator)),
+            },
+            _ => Expr::from(Self::new(numerator, denominator)),
+        }
+    }
+
+    /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        let mut output = format!("{}Divide {{\n", " ".repeat(indent));
+        output.push_str(&self.numerator.debug(indent + 2));
+        output.push_str(&self.denominator.debug(indent + 2));
+        output.push_str(&format!("{}}}\n", " ".repeat(indent)));
+        output
+    }
+
+    /// Returns a Typist string for the expression.
+    /// For a `Divide`, it returns a `frac`, whose operands never need parentheses.
+    /// A leading minus of the numerator is written before the fraction, as in `-frac(1, 2)`.
+    fn to_typist(&self) -> String {
+        match negated(&self.numerator) {
+            Some(numerator) => format!(
+                "-frac({}, {})",
+                numerator.to_typist(),
+                self.denominator.to_typist()
+            ),
+            None => format!(
+                "frac({}, {})",
+                self.numerator.to_typist(),
+                self.denominator.to_typist()
+            ),
+        }
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Divide`, it returns a fraction such as `\frac{x}{2}`, with a leading minus of the
+    /// numerator written before the fraction, as in `-\frac{1}{2}`.
+    fn to_latex(&self) -> String {
+        match negated(&self.numerator) {
+            Some(numerator) => format!(
+                "-\\frac{{{}}}{{{}}}",
+                numerator.to_latex(),
+                self.denominator.to_latex()
+            ),
+            None => format!(
+                "\\frac{{{}}}{{{}}}",
+                self.numerator.to_latex(),
+                self.denominator.to_latex()
+            ),
+        }
+    }
+}
+
+impl Clone for Divide {
+    fn clone(&self) -> Self {
+        Divide {
+            numerator: self.numerator.clone(),
+            denominator: self.denominator.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::negate::Negate;
+    use crate::algebra::number::Number;
+    use crate::algebra::power::Power;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    #[test]
+    fn divide_simplify_with_constants() {
+        let divide = Divide::new(Expr::from(Constant::new(6)), Expr::from(Constant::new(4)));
+        let simplified = divide.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, Number::rational(3, 2));
+            assert!(constant.value.is_exact());
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn divide_simplify_with_fractional_constants() {
+        let divide = Divide::new(
+            Expr::from(Constant::new(0.5)),
+            Expr::from(Constant::new(4.0)),
+        );
+        let simplified = divide.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 0.125);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn divide_simplify_by_one() {
+        let divide = Divide::new(
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(1.0)),
+        );
+        let simplified = divide.simplify();
+        if let Expr::Variable(variable) = &simplified {
+            assert_eq!(variable.name, "x");
+        } else {
+            panic!("Expected Variable, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn divide_simplify_by_zero_is_preserved() {
+        let divide = Divide::new(
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Constant::new(0.0)),
+        );
+        let simplified = divide.simplify();
+        assert!(matches!(simplified, Expr::Divide(_)));
+    }
+
+    #[test]
+    fn divide_eval_by_zero() {
+        let divide = Divide::new(
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Variable::new("x")),
+        );
+        let env = Environment::new().with("x", 0.0);
+        assert_eq!(divide.eval(&env), Err(EvalError::DivisionByZero));
+        let env = Environment::new().with("x", 4.0);
+        assert_eq!(divide.eval(&env), Ok(0.25));
+    }
+
+    #[test]
+    fn divide_to_typist_writes_fractions() {
+        let divide = Divide::new(
+            Expr::from(Variable::new("x")),
+            Expr::from(Divide::new(
+                Expr::from(Constant::new(1)),
+                Expr::from(Variable::new("y")),
+            )),
+        );
+        assert_eq!(divide.to_typist(), "frac(x, frac(1, y))");
+    }
+
+    #[test]
+    fn divide_writes_the_sign_before_the_fraction() {
+        let x = Expr::from(Variable::new("x"));
+        let divide = Divide::new(Expr::from(Constant::new(-1)), Expr::from(Constant::new(2)));
+        assert_eq!(divide.to_typist(), "-frac(1, 2)");
+        assert_eq!(divide.to_latex(), "-\\frac{1}{2}");
+        let divide = Divide::new(
+            Expr::from(Negate::new(x.clone())),
+            Expr::from(Constant::new(2)),
+        );
+        assert_eq!(divide.to_latex(), "-\\frac{x}{2}");
+        let power = Power::new(x, Expr::from(divide));
+        assert_eq!(power.to_latex(), "x^{-\\frac{x}{2}}");
+    }
+}
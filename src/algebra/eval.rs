@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use num_complex::Complex64;
+
+use crate::algebra::interval::Interval;
+
+/// `Environment` maps variable names to the values they take during evaluation.
+/// Values may be complex; a variable bound to a non-real value can only be used by `eval_complex`.
+/// A variable may instead be bound to an interval of values, which only `eval_interval` accepts.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    bindings: HashMap<String, Complex64>,
+    intervals: HashMap<String, Interval>,
+}
+
+impl Environment {
+    /// Constructs a new, empty `Environment` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the environment with `name` bound to `value`, replacing any previous binding.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable, as stored in `Variable::name`.
+    /// * `value` - The value the variable takes.
+    pub fn with(mut self, name: &str, value: impl Into<Complex64>) -> Self {
+        self.bind(name, value);
+        self
+    }
+
+    /// Binds `name` to `value`, replacing any previous binding.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable, as stored in `Variable::name`.
+    /// * `value` - The value the variable takes.
+    pub fn bind(&mut self, name: &str, value: impl Into<Complex64>) {
+        self.intervals.remove(name);
+        self.bindings.insert(name.to_string(), value.into());
+    }
+
+    /// Returns the environment with `name` bound to every value in `interval`, replacing any
+    /// previous binding.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable, as stored in `Variable::name`.
+    /// * `interval` - The values the variable may take.
+    pub fn with_interval(mut self, name: &str, interval: Interval) -> Self {
+        self.bind_interval(name, interval);
+        self
+    }
+
+    /// Binds `name` to every value in `interval`, replacing any previous binding.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable, as stored in `Variable::name`.
+    /// * `interval` - The values the variable may take.
+    pub fn bind_interval(&mut self, name: &str, interval: Interval) {
+        self.bindings.remove(name);
+        self.intervals.insert(name.to_string(), interval);
+    }
+
+    /// Returns the value bound to `name`, if any.
+    pub fn get(&self, name: &str) -> Option<Complex64> {
+        self.bindings.get(name).copied()
+    }
+
+    /// Returns the interval bound to `name`, if any.
+    pub fn get_interval(&self, name: &str) -> Option<Interval> {
+        self.intervals.get(name).copied()
+    }
+}
+
+/// `EvalError` describes why an expression could not be evaluated to a number.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// The expression contains a variable that has no value in the environment.
+    UnboundVariable(String),
+    /// The expression divides by zero, including raising zero to a negative power.
+    DivisionByZero,
+    /// A function or power was applied outside of the domain where it has a value, or a
+    /// real evaluation met a value that is not real.
+    Domain(String),
+    /// The expression calls a function that has no numeric implementation.
+    UnknownFunction(String),
+    /// A built-in function was called with the wrong number of arguments.
+    WrongArity {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// The value could not be determined to the requested number of digits, as happens for a
+    /// value that is exactly zero without being written as zero.
+    InsufficientPrecision,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::UnboundVariable(name) => write!(f, "variable '{}' has no value", name),
+            EvalError::DivisionByZero => f.write_str("division by zero"),
+            EvalError::Domain(message) => write!(f, "domain error: {}", message),
+            EvalError::UnknownFunction(name) => {
+                write!(f, "function '{}' cannot be evaluated", name)
+            }
+            EvalError::WrongArity {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function '{}' takes {} argument(s) but {} were given",
+                name, expected, found
+            ),
+            EvalError::InsufficientPrecision => {
+                f.write_str("the value could not be determined to the requested precision")
+            }
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::expression::Expression;
+    use crate::algebra::interval::Interval;
+    use crate::algebra::parser::parse_expression;
+
+    use super::*;
+
+    #[test]
+    fn environment_binds_and_replaces_values() {
+        let mut env = Environment::new().with("x", 1.0).with("y", 2.0);
+        env.bind("x", 3.0);
+        assert_eq!(env.get("x"), Some(Complex64::new(3.0, 0.0)));
+        assert_eq!(env.get("y"), Some(Complex64::new(2.0, 0.0)));
+        assert_eq!(env.get("z"), None);
+    }
+
+    #[test]
+    fn eval_parsed_expression() {
+        let expr = parse_expression("2x^2 - 3x/y + sqrt(x_1)").unwrap();
+        let env = Environment::new()
+            .with("x", 3.0)
+            .with("y", 2.0)
+            .with("x_1", 16.0);
+        assert_eq!(expr.eval(&env), Ok(17.5));
+    }
+
+    #[test]
+    fn eval_reports_unbound_variable() {
+        let expr = parse_expression("x + y").unwrap();
+        let env = Environment::new().with("x", 1.0);
+        assert_eq!(
+            expr.eval(&env),
+            Err(EvalError::UnboundVariable("y".to_string()))
+        );
+    }
+
+    #[test]
+    fn eval_error_messages() {
+        assert_eq!(
+            EvalError::UnboundVariable("x".to_string()).to_string(),
+            "variable 'x' has no value"
+        );
+        assert_eq!(
+            EvalError::Domain("ln is undefined for -1".to_string()).to_string(),
+            "domain error: ln is undefined for -1"
+        );
+    }
+
+    #[test]
+    fn eval_complex_transfer_function() {
+        // H(s) = 1 / (s^2 + 2s + 2) at s = i
+        let expr = parse_expression("1 / (s^2 + 2s + 2)").unwrap();
+        let env = Environment::new().with("s", Complex64::new(0.0, 1.0));
+        let value = expr.eval_complex(&env).unwrap();
+        assert!((value - Complex64::new(0.2, -0.4)).norm() < 1e-12);
+        assert!(matches!(expr.eval(&env), Err(EvalError::Domain(_))));
+    }
+
+    #[test]
+    fn eval_digits_beyond_f64() {
+        let env = Environment::new();
+        let expr = parse_expression("exp(pi sqrt(163))").unwrap();
+        assert_eq!(
+            expr.eval_digits(&env, 40),
+            Ok("262537412640768743.9999999999992500725972".to_string())
+        );
+        let expr = parse_expression("sin(1/3) + ln(0.1)").unwrap();
+        assert_eq!(
+            expr.eval_digits(&env, 30),
+            Ok("-1.97539039619789343984464736942".to_string())
+        );
+    }
+
+    #[test]
+    fn eval_digits_with_environment() {
+        let expr = parse_expression("2x^2 - 3x/y + sqrt(x_1) - x + x").unwrap();
+        let env = Environment::new()
+            .with("x", 3.0)
+            .with("y", 2.0)
+            .with("x_1", 16.0);
+        assert_eq!(expr.eval_digits(&env, 5), Ok("17.500".to_string()));
+        assert!(matches!(
+            parse_expression("ln(x - 4)").unwrap().eval_digits(&env, 5),
+            Err(EvalError::Domain(_))
+        ));
+        assert_eq!(
+            parse_expression("1 / (x - 3)")
+                .unwrap()
+                .eval_digits(&env, 5),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn eval_interval_with_bound_intervals() {
+        let env = Environment::new()
+            .with_interval("x", Interval::new(1.0, 2.0))
+            .with("y", 0.5);
+        let expr = parse_expression("x^2 - 2x").unwrap();
+        assert_eq!(expr.eval_interval(&env), Ok(Interval::new(-3.0, 2.0)));
+        let expr = parse_expression("1 / (x - 1)").unwrap();
+        assert_eq!(
+            expr.eval_interval(&env),
+            Ok(Interval::new(1.0, f64::INFINITY))
+        );
+        let enclosure = parse_expression("sqrt(x) + sin(y)")
+            .unwrap()
+            .eval_interval(&env)
+            .unwrap();
+        assert!(
+            enclosure.contains(1.0 + 0.5f64.sin())
+                && enclosure.contains(2f64.sqrt() + 0.5f64.sin())
+        );
+        assert_eq!(
+            parse_expression("x").unwrap().eval(&env),
+            Err(EvalError::UnboundVariable("x".to_string()))
+        );
+    }
+}
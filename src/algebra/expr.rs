@@ -0,0 +1,553 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::algebra::add::Add;
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::divide::Divide;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expression::Expression;
+use crate::algebra::function::Function;
+use crate::algebra::interner::Operand;
+use crate::algebra::interval::Interval;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::negate::Negate;
+use crate::algebra::number::Number;
+use crate::algebra::power::Power;
+use crate::algebra::variable::Variable;
+
+/// `Expr` is a node of an expression tree.
+/// Each built-in node type has its own variant, so simplification rules can match on the shape of
+/// a tree exhaustively. Node types defined outside of this crate implement [`Expression`] and are
+/// stored in the `Custom` variant.
+///
+/// Nodes are reference counted, so cloning an `Expr` shares its subexpressions instead of copying
+/// them, and a tree may be a DAG in which a subexpression appears many times but is stored once.
+/// An [`Interner`](crate::algebra::interner::Interner) shares every repeated subexpression.
+///
+/// Expressions compare, order and hash structurally, so `x + 1` equals `x + 1` but not `1 + x`,
+/// and the exact constant `1` differs from the floating point constant `1.0`. The canonical order
+/// puts constants first, then named constants, then variables alphabetically and then any other
+/// expression by its degree, e.g. `2 < pi < x < y < x y < x^3`.
+#[derive(Clone)]
+pub enum Expr {
+    Constant(Rc<Constant>),
+    NamedConstant(NamedConstant),
+    Variable(Rc<Variable>),
+    Add(Rc<Add>),
+    Multiply(Rc<Multiply>),
+    Divide(Rc<Divide>),
+    Power(Rc<Power>),
+    Negate(Rc<Negate>),
+    Function(Rc<Function>),
+    /// A node type that is not built in, which is only known through its `Expression` implementation.
+    /// Simplification rules treat it as an opaque operand.
+    Custom(Rc<dyn Expression>),
+}
+
+impl Expr {
+    /// Constructs an `Expr` holding a custom node type.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node, which can be recovered with `as_any().downcast_ref()`.
+    pub fn custom(node: impl Expression + 'static) -> Self {
+        Expr::Custom(Rc::new(node))
+    }
+
+    /// Returns the node as an `Expression` trait object, which every variant implements.
+    fn node(&self) -> &dyn Expression {
+        match self {
+            Expr::Constant(constant) => constant.as_ref(),
+            Expr::NamedConstant(named) => named,
+            Expr::Variable(variable) => variable.as_ref(),
+            Expr::Add(add) => add.as_ref(),
+            Expr::Multiply(multiply) => multiply.as_ref(),
+            Expr::Divide(divide) => divide.as_ref(),
+            Expr::Power(power) => power.as_ref(),
+            Expr::Negate(negate) => negate.as_ref(),
+            Expr::Function(function) => function.as_ref(),
+            Expr::Custom(custom) => custom.as_ref(),
+        }
+    }
+
+    /// Returns `true` if both expressions are the same shared node, which implies they are equal.
+    /// Expressions interned by the same [`Interner`](crate::algebra::interner::Interner) are
+    /// equal exactly when this returns `true`, so comparing them takes constant time.
+    pub fn ptr_eq(&self, other: &Expr) -> bool {
+        match (self, other) {
+            (Expr::Constant(lhs), Expr::Constant(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::NamedConstant(lhs), Expr::NamedConstant(rhs)) => lhs == rhs,
+            (Expr::Variable(lhs), Expr::Variable(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Add(lhs), Expr::Add(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Multiply(lhs), Expr::Multiply(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Divide(lhs), Expr::Divide(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Power(lhs), Expr::Power(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Negate(lhs), Expr::Negate(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Function(lhs), Expr::Function(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Expr::Custom(lhs), Expr::Custom(rhs)) => Rc::ptr_eq(lhs, rhs),
+            _ => false,
+        }
+    }
+
+    /// Returns the degree of the expression as a polynomial in its variables, e.g. 3 for `x^2 y`.
+    /// Constants have degree 0, and function calls and custom nodes count as a single variable.
+    /// A power with an exponent that is not an exact number has the degree of its base.
+    /// Shared nodes are only visited once, so this takes time proportional to the number of
+    /// distinct nodes.
+    pub fn degree(&self) -> BigRational {
+        self.degree_shared(&mut HashMap::new())
+    }
+
+    /// Returns the degree of the expression, looking up shared nodes already visited in `degrees`.
+    fn degree_shared(&self, degrees: &mut HashMap<Operand, BigRational>) -> BigRational {
+        match self {
+            Expr::Constant(_) | Expr::NamedConstant(_) => return BigRational::zero(),
+            Expr::Variable(_) | Expr::Function(_) | Expr::Custom(_) => return BigRational::one(),
+            _ => {}
+        }
+        let identity = Operand::of(self);
+        if let Some(degree) = degrees.get(&identity) {
+            return degree.clone();
+        }
+        let degree = match self {
+            Expr::Add(add) => add
+                .ops
+                .iter()
+                .map(|op| op.degree_shared(degrees))
+                .max()
+                .unwrap_or_else(BigRational::zero),
+            Expr::Multiply(multiply) => multiply
+                .ops
+                .iter()
+                .map(|op| op.degree_shared(degrees))
+                .sum(),
+            Expr::Divide(divide) => {
+                divide.numerator.degree_shared(degrees) - divide.denominator.degree_shared(degrees)
+            }
+            Expr::Power(power) => match Constant::value_of(&power.exponent) {
+                Some(Number::Exact(exponent)) => power.base.degree_shared(degrees) * exponent,
+                _ => power.base.degree_shared(degrees),
+            },
+            Expr::Negate(negate) => negate.op.degree_shared(degrees),
+            _ => unreachable!("leaves are handled above"),
+        };
+        degrees.insert(identity, degree.clone());
+        degree
+    }
+
+    /// Returns a hash of the structure of the expression, looking up shared nodes already
+    /// hashed in `digests`. Each node is hashed from the digests of its operands, so equal
+    /// expressions have equal digests however much of them is shared.
+    fn digest(&self, digests: &mut HashMap<Operand, u64>) -> u64 {
+        let identity = Operand::of(self);
+        if let Some(digest) = digests.get(&identity) {
+            return *digest;
+        }
+        let mut hasher = DefaultHasher::new();
+        self.rank().hash(&mut hasher);
+        let mut hash_all = |ops: &[Expr], hasher: &mut DefaultHasher| {
+            ops.len().hash(hasher);
+            for op in ops {
+                op.digest(digests).hash(hasher);
+            }
+        };
+        match self {
+            Expr::Constant(constant) => constant.value.total_hash(&mut hasher),
+            Expr::NamedConstant(named) => named.hash(&mut hasher),
+            Expr::Variable(variable) => variable.name.hash(&mut hasher),
+            Expr::Add(add) => hash_all(&add.ops, &mut hasher),
+            Expr::Multiply(multiply) => hash_all(&multiply.ops, &mut hasher),
+            Expr::Divide(divide) => hash_all(
+                &[divide.numerator.clone(), divide.denominator.clone()],
+                &mut hasher,
+            ),
+            Expr::Power(power) => {
+                hash_all(&[power.base.clone(), power.exponent.clone()], &mut hasher)
+            }
+            Expr::Negate(negate) => hash_all(std::slice::from_ref(&negate.op), &mut hasher),
+            Expr::Function(function) => {
+                function.name.hash(&mut hasher);
+                hash_all(&function.args, &mut hasher);
+            }
+            Expr::Custom(custom) => custom.debug(0).hash(&mut hasher),
+        }
+        let digest = hasher.finish();
+        digests.insert(identity, digest);
+        digest
+    }
+
+    /// Returns the position of the variant in the canonical order. Constants, named constants and
+    /// variables come first, the other variants only break ties between expressions of equal degree.
+    fn rank(&self) -> u8 {
+        match self {
+            Expr::Constant(_) => 0,
+            Expr::NamedConstant(_) => 1,
+            Expr::Variable(_) => 2,
+            Expr::Power(_) => 3,
+            Expr::Multiply(_) => 4,
+            Expr::Divide(_) => 5,
+            Expr::Add(_) => 6,
+            Expr::Negate(_) => 7,
+            Expr::Function(_) => 8,
+            Expr::Custom(_) => 9,
+        }
+    }
+
+    /// Returns `true` if the expression is a constant, a named constant or a variable.
+    fn is_atom(&self) -> bool {
+        self.rank() <= 2
+    }
+}
+
+impl Expression for Expr {
+    /// Evaluates the expression to a number by evaluating the node it holds.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        self.node().eval(env)
+    }
+
+    /// Evaluates the expression to a complex number by evaluating the node it holds.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        self.node().eval_complex(env)
+    }
+
+    /// Evaluates the expression to `precision` bits by evaluating the node it holds.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        self.node().eval_big(env, precision)
+    }
+
+    /// Evaluates the expression to an interval by evaluating the node it holds.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        self.node().eval_interval(env)
+    }
+
+    /// Simplifies the node it holds and returns a new simplified expression.
+    fn simplify(&self) -> Expr {
+        self.node().simplify()
+    }
+
+    /// Returns a reference to the node it holds as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self.node().as_any()
+    }
+
+    /// Returns a debug string for the node it holds. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        self.node().debug(indent)
+    }
+
+    /// Returns a Typist string for the node it holds.
+    fn to_typist(&self) -> String {
+        self.node().to_typist()
+    }
+
+    /// Returns a LaTeX string for the node it holds.
+    fn to_latex(&self) -> String {
+        self.node().to_latex()
+    }
+}
+
+/// Implement `Debug` for `Expr` to allow for printing expressions.
+impl Debug for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.debug(0))
+    }
+}
+
+/// Compares expressions in the canonical order, remembering the degree of every node and the
+/// order of every pair of nodes it has compared, so that shared nodes are compared only once.
+#[derive(Default)]
+struct Comparison {
+    degrees: HashMap<Operand, BigRational>,
+    orderings: HashMap<(Operand, Operand), Ordering>,
+}
+
+impl Comparison {
+    /// Compares `lhs` with `rhs`, see [`Ord for Expr`](Expr#impl-Ord-for-Expr).
+    fn cmp(&mut self, lhs: &Expr, rhs: &Expr) -> Ordering {
+        if lhs.ptr_eq(rhs) {
+            return Ordering::Equal;
+        }
+        if lhs.is_atom() || rhs.is_atom() {
+            return match (lhs, rhs) {
+                (Expr::Constant(lhs), Expr::Constant(rhs)) => lhs.value.total_cmp(&rhs.value),
+                (Expr::NamedConstant(lhs), Expr::NamedConstant(rhs)) => lhs.cmp(rhs),
+                (Expr::Variable(lhs), Expr::Variable(rhs)) => lhs.name.cmp(&rhs.name),
+                _ => lhs.rank().cmp(&rhs.rank()),
+            };
+        }
+        let pair = (Operand::of(lhs), Operand::of(rhs));
+        if let Some(ordering) = self.orderings.get(&pair) {
+            return *ordering;
+        }
+        let ordering = lhs
+            .degree_shared(&mut self.degrees)
+            .cmp(&rhs.degree_shared(&mut self.degrees))
+            .then_with(|| lhs.rank().cmp(&rhs.rank()))
+            .then_with(|| match (lhs, rhs) {
+                (Expr::Add(lhs), Expr::Add(rhs)) => self.cmp_all(&lhs.ops, &rhs.ops),
+                (Expr::Multiply(lhs), Expr::Multiply(rhs)) => self.cmp_all(&lhs.ops, &rhs.ops),
+                (Expr::Divide(lhs), Expr::Divide(rhs)) => self
+                    .cmp(&lhs.numerator, &rhs.numerator)
+                    .then_with(|| self.cmp(&lhs.denominator, &rhs.denominator)),
+                (Expr::Power(lhs), Expr::Power(rhs)) => self
+                    .cmp(&lhs.base, &rhs.base)
+                    .then_with(|| self.cmp(&lhs.exponent, &rhs.exponent)),
+                (Expr::Negate(lhs), Expr::Negate(rhs)) => self.cmp(&lhs.op, &rhs.op),
+                (Expr::Function(lhs), Expr::Function(rhs)) => lhs
+                    .name
+                    .cmp(&rhs.name)
+                    .then_with(|| self.cmp_all(&lhs.args, &rhs.args)),
+                (Expr::Custom(lhs), Expr::Custom(rhs)) => lhs.debug(0).cmp(&rhs.debug(0)),
+                // Expressions of different variants were already ordered by their rank
+                _ => Ordering::Equal,
+            });
+        self.orderings.insert(pair, ordering);
+        ordering
+    }
+
+    /// Compares two lists of operands lexicographically, like slices are compared.
+    fn cmp_all(&mut self, lhs: &[Expr], rhs: &[Expr]) -> Ordering {
+        lhs.iter()
+            .zip(rhs)
+            .map(|(lhs, rhs)| self.cmp(lhs, rhs))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| lhs.len().cmp(&rhs.len()))
+    }
+}
+
+/// Orders expressions canonically: constants first, then named constants, then variables
+/// alphabetically, then any other expression by its degree. Expressions of the same degree are
+/// ordered by their variant and then by their operands, and custom nodes by their debug string.
+/// Shared nodes are equal without being compared, and a pair of nodes is compared at most once,
+/// so comparing DAGs takes time proportional to their number of distinct nodes rather than paths.
+impl Ord for Expr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Comparison::default().cmp(self, other)
+    }
+}
+
+impl PartialOrd for Expr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Expressions are equal if they have the same structure, see [`Ord for Expr`](Expr#impl-Ord-for-Expr).
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Expr {}
+
+/// Hashes the structure of the expression, consistently with its equality.
+/// Every distinct node is hashed once, so hashing a DAG takes time proportional to its number of
+/// distinct nodes.
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.digest(&mut HashMap::new()).hash(state);
+    }
+}
+
+impl From<Constant> for Expr {
+    fn from(constant: Constant) -> Self {
+        Expr::Constant(Rc::new(constant))
+    }
+}
+
+impl From<NamedConstant> for Expr {
+    fn from(named: NamedConstant) -> Self {
+        Expr::NamedConstant(named)
+    }
+}
+
+impl From<Variable> for Expr {
+    fn from(variable: Variable) -> Self {
+        Expr::Variable(Rc::new(variable))
+    }
+}
+
+impl From<Add> for Expr {
+    fn from(add: Add) -> Self {
+        Expr::Add(Rc::new(add))
+    }
+}
+
+impl From<Multiply> for Expr {
+    fn from(multiply: Multiply) -> Self {
+        Expr::Multiply(Rc::new(multiply))
+    }
+}
+
+impl From<Divide> for Expr {
+    fn from(divide: Divide) -> Self {
+        Expr::Divide(Rc::new(divide))
+    }
+}
+
+impl From<Power> for Expr {
+    fn from(power: Power) -> Self {
+        Expr::Power(Rc::new(power))
+    }
+}
+
+impl From<Negate> for Expr {
+    fn from(negate: Negate) -> Self {
+        Expr::Negate(Rc::new(negate))
+    }
+}
+
+impl From<Function> for Expr {
+    fn from(function: Function) -> Self {
+        Expr::Function(Rc::new(function))
+    }
+}
+
+impl From<Box<dyn Expression>> for Expr {
+    fn from(custom: Box<dyn Expression>) -> Self {
+        Expr::Custom(Rc::from(custom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A custom node for twice its operand, which the crate knows nothing about.
+    #[derive(Clone)]
+    struct Double {
+        op: Expr,
+    }
+
+    impl Expression for Double {
+        fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+            Ok(2.0 * self.op.eval(env)?)
+        }
+
+        fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+            Ok(2.0 * self.op.eval_complex(env)?)
+        }
+
+        fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+            Ok(self.op.eval_big(env, precision)?.mul_pow2(1))
+        }
+
+        fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+            Ok(self.op.eval_interval(env)?.mul(&Interval::point(2.0)))
+        }
+
+        fn simplify(&self) -> Expr {
+            Expr::custom(Double {
+                op: self.op.simplify(),
+            })
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn debug(&self, indent: usize) -> String {
+            let mut output = format!("{}Double {{\n", " ".repeat(indent));
+            output.push_str(&self.op.debug(indent + 2));
+            output.push_str(&format!("{}}}\n", " ".repeat(indent)));
+            output
+        }
+
+        fn to_typist(&self) -> String {
+            format!("2 ({})", self.op.to_typist())
+        }
+    }
+
+    #[test]
+    fn expr_custom_node() {
+        let double = Expr::custom(Double {
+            op: Add::new(vec![Variable::new("x").into(), Constant::new(1).into()]).into(),
+        });
+        let env = Environment::new().with("x", 2.0);
+        assert_eq!(double.eval(&env), Ok(6.0));
+        assert_eq!(double.eval_digits(&env, 3), Ok("6.00".to_string()));
+
+        // Custom nodes are opaque operands of the built-in simplification rules
+        let sum = Add::new(vec![double.clone(), double, Constant::new(0).into()]);
+        let simplified = sum.simplify();
+        assert_eq!(simplified.to_typist(), "2 dot 2 (x + 1)");
+        // Without a LaTeX form of its own, a custom node is set as text
+        assert_eq!(simplified.to_latex(), "2 \\text{2 (x + 1)}");
+        if let Expr::Multiply(multiply) = &simplified {
+            assert!(multiply.ops[1].as_any().downcast_ref::<Double>().is_some());
+        } else {
+            panic!("Expected Multiply, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn expr_structural_equality() {
+        let sum = |lhs: Expr, rhs: Expr| Expr::from(Add::new(vec![lhs, rhs]));
+        let x = || Expr::from(Variable::new("x"));
+        assert_eq!(
+            sum(x(), Constant::new(1).into()),
+            sum(x(), Constant::new(1).into())
+        );
+        assert_ne!(
+            sum(x(), Constant::new(1).into()),
+            sum(Constant::new(1).into(), x())
+        );
+        assert_ne!(Expr::from(Constant::new(1)), Expr::from(Constant::new(1.0)));
+
+        let set: std::collections::HashSet<Expr> = [x(), x(), Constant::new(2).into(), x()]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn expr_canonical_order() {
+        let x = || Expr::from(Variable::new("x"));
+        let power = |exponent: i64| Expr::from(Power::new(x(), Constant::new(exponent).into()));
+        let mut exprs = [
+            power(3),
+            Multiply::new(vec![x(), Variable::new("y").into()]).into(),
+            Variable::new("y").into(),
+            NamedConstant::Pi.into(),
+            x(),
+            Constant::new(2).into(),
+            Function::new("sin", vec![x()]).into(),
+        ];
+        exprs.sort();
+        let typist: Vec<String> = exprs.iter().map(Expression::to_typist).collect();
+        assert_eq!(typist, ["2", "pi", "x", "y", "sin(x)", "x y", "x^3"]);
+        assert_eq!(power(3).degree(), BigRational::from_integer(3.into()));
+    }
+
+    #[test]
+    fn expr_compare_and_hash_shared_nodes() {
+        // Each level multiplies the previous level by itself, so the tree has 2^64 leaves
+        let doubled = |name: &str| {
+            let mut expr = Expr::from(Variable::new(name));
+            for _ in 0..64 {
+                expr = Expr::from(Multiply::new(vec![expr.clone(), expr]));
+            }
+            expr
+        };
+        let (x, also_x, y) = (doubled("x"), doubled("x"), doubled("y"));
+        assert_eq!(x.degree(), BigRational::from_integer(2.into()).pow(64));
+        assert!(!x.ptr_eq(&also_x));
+        assert_eq!(x, also_x);
+        assert!(x < y);
+
+        let set: std::collections::HashSet<Expr> = [x, also_x, y].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -2,17 +2,50 @@ use dyn_clone::DynClone;
 use std::any::Any;
 use std::fmt::Debug;
 
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::interval::Interval;
+
 /// The `Expression` trait represents an algebraic expression.
 /// It provides methods for evaluating and simplifying the expression,
-/// as well as converting the expression to a debug string or a Typist string.
+/// as well as converting the expression to a debug string, a Typist string or a LaTeX string.
+/// It is implemented by every node type and by [`Expr`], and a type defined outside of this
+/// crate can implement it to be used as a node through `Expr::Custom`.
 pub trait Expression: DynClone {
-    /// Evaluates the expression and returns a new expression.
-    fn eval(&self) -> Box<dyn Expression>;
+    /// Evaluates the expression to a number, looking up variables in `env`.
+    /// Returns an `EvalError` if a variable is unbound or an operation has no real value.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError>;
+
+    /// Evaluates the expression to a complex number, looking up variables in `env`.
+    /// Returns an `EvalError` if a variable is unbound or an operation has no value.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError>;
+
+    /// Evaluates the expression to a real number with `precision` bits and a guaranteed error
+    /// bound, looking up variables in `env`.
+    /// Returns `EvalError::InsufficientPrecision` if the precision is too low to rule out a
+    /// division by zero or an argument outside of a function's domain.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError>;
+
+    /// Evaluates the expression to an interval enclosing every value it takes when its variables
+    /// range over the intervals bound in `env`. The interval is rounded outward, so it is a
+    /// guaranteed bound even though it may be wider than the exact range of values.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError>;
+
+    /// Evaluates the expression to `digits` significant digits, all of which are guaranteed to be
+    /// correct, looking up variables in `env`. Variables take the exact value of the `f64` they are
+    /// bound to, and the expression is evaluated at increasing precision until its digits are certain.
+    fn eval_digits(&self, env: &Environment, digits: usize) -> Result<String, EvalError> {
+        BigFloat::evaluate_to_digits(digits, |precision| self.eval_big(env, precision))
+    }
 
     /// Simplifies the expression and returns a new simplified expression.
-    fn simplify(&self) -> Box<dyn Expression>;
+    fn simplify(&self) -> Expr;
 
     /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    /// This is how a custom node type is recovered from `Expr::Custom`.
     fn as_any(&self) -> &dyn Any;
 
     /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
@@ -20,24 +53,36 @@ pub trait Expression: DynClone {
 
     /// Returns a Typist string for the expression.
     fn to_typist(&self) -> String;
-}
 
-// This allows for cloning a Box<dyn Expression>
-dyn_clone::clone_trait_object!(Expression);
-
-/// The `AsAny` trait provides a method for converting a type to `dyn Any`.
-trait AsAny {
-    /// Returns a reference to the type as a `dyn Any`, which can be downcast to its concrete type.
-    fn as_any(&self) -> &dyn Any;
+    /// Returns a LaTeX string for the expression, to be set in math mode.
+    /// By default, the Typist string is set as text, so a custom node only needs to implement
+    /// this to be typeset as math.
+    fn to_latex(&self) -> String {
+        format!("\\text{{{}}}", escape_latex_text(&self.to_typist()))
+    }
 }
 
-/// Implement `AsAny` for all types that implement `Expression`.
-impl<T: Expression + 'static> AsAny for T {
-    fn as_any(&self) -> &dyn Any {
-        self
+/// Escapes the characters of `text` that have a meaning in LaTeX text.
+fn escape_latex_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '{' | '}' | '_' | '$' | '&' | '%' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
     }
+    escaped
 }
 
+// This allows for cloning a Box<dyn Expression>
+dyn_clone::clone_trait_object!(Expression);
+
 /// Implement `Debug` for `dyn Expression` to allow for printing expressions.
 impl Debug for dyn Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
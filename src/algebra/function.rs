@@ -0,0 +1,588 @@
+use std::any::Any;
+
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::number::Number;
+
+/// Names of the built-in elementary functions. Each of them takes a single argument.
+pub const BUILTIN_FUNCTIONS: [&str; 19] = [
+    "sin", "cos", "tan", "asin", "acos", "atan", "sinh", "cosh", "tanh", "exp", "ln", "log",
+    "sqrt", "abs", "sign", "conj", "re", "im", "arg",
+];
+
+/// `Function` struct represents a function call in an expression tree.
+/// It contains the `name` of the function and its arguments as `Expr` nodes.
+/// Built-in functions (see [`BUILTIN_FUNCTIONS`]) get simplification rules and numeric evaluation,
+/// any other name is treated as an opaque function.
+pub struct Function {
+    pub name: String,
+    pub args: Vec<Expr>,
+}
+
+impl Function {
+    /// Constructs a new `Function` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that represents the name of the function.
+    /// * `args` - The arguments the function is applied to.
+    pub fn new(name: &str, args: Vec<Expr>) -> Self {
+        Self {
+            name: name.to_string(),
+            args,
+        }
+    }
+
+    /// Returns `true` if `name` is one of the [`BUILTIN_FUNCTIONS`].
+    pub fn is_builtin(name: &str) -> bool {
+        BUILTIN_FUNCTIONS.contains(&name)
+    }
+
+    /// Returns the single argument of a built-in function.
+    /// Returns an `EvalError` for unknown functions or the wrong number of arguments.
+    fn unary_argument<T: Clone>(&self, values: &[T]) -> Result<T, EvalError> {
+        if !Function::is_builtin(&self.name) {
+            return Err(EvalError::UnknownFunction(self.name.clone()));
+        }
+        match values {
+            [x] => Ok(x.clone()),
+            _ => Err(EvalError::WrongArity {
+                name: self.name.clone(),
+                expected: 1,
+                found: values.len(),
+            }),
+        }
+    }
+
+    /// Applies the function to numeric arguments.
+    /// Returns an `EvalError` for unknown functions, the wrong number of arguments, or an argument
+    /// outside of the domain where the function has a finite real value.
+    fn apply(&self, values: &[f64]) -> Result<f64, EvalError> {
+        let x = self.unary_argument(values)?;
+        let in_domain = match self.name.as_str() {
+            "asin" | "acos" => (-1.0..=1.0).contains(&x),
+            "ln" | "log" => x > 0.0,
+            "sqrt" => x >= 0.0,
+            _ => true,
+        };
+        if !in_domain {
+            return Err(EvalError::Domain(format!(
+                "{} is undefined for {}",
+                self.name, x
+            )));
+        }
+        let result = match self.name.as_str() {
+            "sin" => x.sin(),
+            "cos" => x.cos(),
+            "tan" => x.tan(),
+            "asin" => x.asin(),
+            "acos" => x.acos(),
+            "atan" => x.atan(),
+            "sinh" => x.sinh(),
+            "cosh" => x.cosh(),
+            "tanh" => x.tanh(),
+            "exp" => x.exp(),
+            "ln" => x.ln(),
+            "log" => x.log10(),
+            "sqrt" => x.sqrt(),
+            "abs" => x.abs(),
+            "sign" if x == 0.0 => 0.0,
+            "sign" => x.signum(),
+            "conj" | "re" => x,
+            "im" => 0.0,
+            _ => Complex64::new(x, 0.0).arg(),
+        };
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(EvalError::Domain(format!("{}({}) overflows", self.name, x)))
+        }
+    }
+
+    /// Applies the function to arguments with guaranteed error bounds, rounding to `precision` bits.
+    /// Returns an `EvalError` for unknown functions, the wrong number of arguments, an argument
+    /// outside of the domain where the function has a real value, or an argument too imprecise
+    /// to decide it.
+    fn apply_big(&self, values: &[BigFloat], precision: u64) -> Result<BigFloat, EvalError> {
+        let x = self.unary_argument(values)?;
+        match self.name.as_str() {
+            "sin" => Ok(x.sin_cos(precision)?.0),
+            "cos" => Ok(x.sin_cos(precision)?.1),
+            "tan" => {
+                let (sin, cos) = x.sin_cos(precision + 16)?;
+                sin.div(&cos, precision)
+            }
+            "asin" => x.asin(precision),
+            "acos" => x.acos(precision),
+            "atan" => x.atan(precision),
+            "sinh" | "cosh" | "tanh" => x.sinh_cosh_tanh(&self.name, precision),
+            "exp" => x.exp(precision),
+            "ln" => x.ln(precision),
+            "log" => x.log10(precision),
+            "sqrt" => x.sqrt(precision),
+            "abs" => Ok(x.abs()),
+            "conj" | "re" => Ok(x),
+            "im" => Ok(BigFloat::zero()),
+            // sign and arg only depend on the sign of the argument
+            _ if x.is_zero() => Ok(BigFloat::zero()),
+            "sign" if x.is_positive() => Ok(BigFloat::one()),
+            "sign" if x.is_negative() => Ok(BigFloat::one().neg()),
+            _ if x.is_positive() => Ok(BigFloat::zero()),
+            _ if x.is_negative() => Ok(BigFloat::pi(precision)),
+            _ => Err(EvalError::InsufficientPrecision),
+        }
+    }
+
+    /// Applies the function to intervals of arguments, returning an interval enclosing every value.
+    /// Functions with a restricted domain are applied to the part of the interval inside it.
+    /// Returns an `EvalError` for unknown functions, the wrong number of arguments, or an interval
+    /// entirely outside of the domain where the function has a real value.
+    fn apply_interval(&self, values: &[Interval]) -> Result<Interval, EvalError> {
+        let x = self.unary_argument(values)?;
+        match self.name.as_str() {
+            "sin" => x.sin(),
+            "cos" => x.cos(),
+            "tan" => x.tan(),
+            "asin" => x.asin(),
+            "acos" => x.acos(),
+            "atan" => x.atan(),
+            "sinh" => x.sinh(),
+            "cosh" => x.cosh(),
+            "tanh" => x.tanh(),
+            "exp" => x.exp(),
+            "ln" => x.ln(),
+            "log" => x.log10(),
+            "sqrt" => x.sqrt(),
+            "abs" => Ok(x.abs()),
+            "sign" => Ok(x.sign()),
+            "conj" | "re" => Ok(x),
+            "im" => Ok(Interval::point(0.0)),
+            _ => Ok(x.arg()),
+        }
+    }
+
+    /// Applies the function to complex arguments, using the principal branch of multivalued functions.
+    /// Returns an `EvalError` for unknown functions, the wrong number of arguments, a logarithm of zero,
+    /// or a result that is not finite.
+    fn apply_complex(&self, values: &[Complex64]) -> Result<Complex64, EvalError> {
+        let z = self.unary_argument(values)?;
+        if matches!(self.name.as_str(), "ln" | "log") && z == Complex64::new(0.0, 0.0) {
+            return Err(EvalError::Domain(format!(
+                "{} is undefined for 0",
+                self.name
+            )));
+        }
+        let result = match self.name.as_str() {
+            "sin" => z.sin(),
+            "cos" => z.cos(),
+            "tan" => z.tan(),
+            "asin" => z.asin(),
+            "acos" => z.acos(),
+            "atan" => z.atan(),
+            "sinh" => z.sinh(),
+            "cosh" => z.cosh(),
+            "tanh" => z.tanh(),
+            "exp" => z.exp(),
+            "ln" => z.ln(),
+            "log" => z.ln() / std::f64::consts::LN_10,
+            "sqrt" => z.sqrt(),
+            "abs" => Complex64::new(z.norm(), 0.0),
+            "sign" if z == Complex64::new(0.0, 0.0) => z,
+            "sign" => z / z.norm(),
+            "conj" => z.conj(),
+            "re" => Complex64::new(z.re, 0.0),
+            "im" => Complex64::new(z.im, 0.0),
+            _ => Complex64::new(z.arg(), 0.0),
+        };
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(EvalError::Domain(format!("{}({}) overflows", self.name, z)))
+        }
+    }
+
+    /// Returns the exact value of `sin`, `cos` or `tan` at `multiple * pi`, if it is rational,
+    /// such as `sin(pi/6) = 1/2` or `cos(pi) = -1`.
+    fn trigonometric_value(&self, multiple: &BigRational) -> Option<Number> {
+        let reduce = |period: i64| {
+            let period = BigRational::from_integer(period.into());
+            let reduced = multiple - (multiple / &period).floor() * &period;
+            Some((reduced.numer().to_i64()?, reduced.denom().to_i64()?))
+        };
+        let value = match (self.name.as_str(), reduce(2)?) {
+            ("sin", (0, 1) | (1, 1)) | ("cos", (1, 2) | (3, 2)) => Number::from(0),
+            ("sin", (1, 2)) | ("cos", (0, 1)) => Number::from(1),
+            ("sin", (3, 2)) | ("cos", (1, 1)) => Number::from(-1),
+            ("sin", (1, 6) | (5, 6)) | ("cos", (1, 3) | (5, 3)) => Number::rational(1, 2),
+            ("sin", (7, 6) | (11, 6)) | ("cos", (2, 3) | (4, 3)) => Number::rational(-1, 2),
+            ("tan", _) => match reduce(1)? {
+                (0, 1) => Number::from(0),
+                (1, 4) => Number::from(1),
+                (3, 4) => Number::from(-1),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Applies the simplification rules for a built-in function of one simplified argument.
+    /// Returns `None` if no rule applies.
+    fn simplify_unary(&self, arg: &Expr) -> Option<Expr> {
+        if let Expr::Function(inner) = arg {
+            match (
+                self.name.as_str(),
+                inner.name.as_str(),
+                inner.args.as_slice(),
+            ) {
+                // exp and ln are inverses of each other
+                ("exp", "ln", [x]) | ("ln", "exp", [x]) => return Some(x.clone()),
+                // conjugation is an involution
+                ("conj", "conj", [x]) => return Some(x.clone()),
+                // abs and sign are idempotent
+                ("abs", "abs", _) | ("sign", "sign", _) => return Some(arg.clone()),
+                _ => {}
+            }
+        }
+
+        if let Some(multiple) = NamedConstant::pi_multiple(arg)
+            .filter(|_| matches!(self.name.as_str(), "sin" | "cos" | "tan"))
+        {
+            return self
+                .trigonometric_value(&multiple)
+                .map(|value| Expr::from(Constant::new(value)));
+        }
+        if self.name == "ln" {
+            // ln(e) = 1 and ln(e^x) = x
+            match arg {
+                Expr::NamedConstant(NamedConstant::E) => return Some(Expr::from(Constant::new(1))),
+                Expr::Power(power)
+                    if matches!(power.base, Expr::NamedConstant(NamedConstant::E)) =>
+                {
+                    return Some(power.exponent.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let value = Constant::value_of(arg)?;
+        let result = match self.name.as_str() {
+            "sin" | "tan" | "asin" | "atan" | "sinh" | "tanh" | "sqrt" if value.is_zero() => {
+                Number::from(0)
+            }
+            "cos" | "cosh" | "exp" if value.is_zero() => Number::from(1),
+            "acos" | "ln" | "log" if value.is_one() => Number::from(0),
+            "log" if *value == Number::from(10) => Number::from(1),
+            "sqrt" => value
+                .sqrt()
+                .filter(|root| root.is_exact() || root.is_integer())?,
+            "abs" => value.abs()?,
+            "sign" => value.signum()?,
+            "conj" => value.conj(),
+            "re" => value.re(),
+            "im" => value.im(),
+            "arg" if value.is_real() && !value.is_negative() => Number::from(0),
+            _ => return None,
+        };
+        Some(Expr::from(Constant::new(result)))
+    }
+}
+
+/// Returns `name` with its first letter in upper case, e.g. `Re` for `re`.
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+impl Expression for Function {
+    /// Evaluates the expression to a number.
+    /// The arguments are evaluated first and the built-in function is applied to their values.
+    /// Opaque functions cannot be evaluated and return `EvalError::UnknownFunction`.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        let values = self
+            .args
+            .iter()
+            .map(|arg| arg.eval(env))
+            .collect::<Result<Vec<f64>, EvalError>>()?;
+        self.apply(&values)
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// The arguments are evaluated first and the built-in function is applied to their values.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        let values = self
+            .args
+            .iter()
+            .map(|arg| arg.eval_complex(env))
+            .collect::<Result<Vec<Complex64>, EvalError>>()?;
+        self.apply_complex(&values)
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// The arguments are evaluated first and the built-in function is applied to their values.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        let values = self
+            .args
+            .iter()
+            .map(|arg| arg.eval_big(env, precision))
+            .collect::<Result<Vec<BigFloat>, EvalError>>()?;
+        self.apply_big(&values, precision)
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// The arguments are evaluated first and the built-in function is applied to their intervals.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        let values = self
+            .args
+            .iter()
+            .map(|arg| arg.eval_interval(env))
+            .collect::<Result<Vec<Interval>, EvalError>>()?;
+        self.apply_interval(&values)
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// This method simplifies the arguments and applies the known values of the built-in functions,
+    /// such as `sin(0) = 0`, `ln(1) = 0`, `sin(pi/6) = 1/2`, `ln(e) = 1` and `exp(ln(x)) = x`.
+    /// Other values are left exact.
+    fn simplify(&self) -> Expr {
+        let args: Vec<Expr> = self.args.iter().map(|arg| arg.simplify()).collect();
+        if let [arg] = args.as_slice() {
+            if let Some(simplified) = self.simplify_unary(arg) {
+                return simplified;
+            }
+        }
+        Expr::from(Self {
+            name: self.name.clone(),
+            args,
+        })
+    }
+
+    /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        let mut output = format!("{}Function {} {{\n", " ".repeat(indent), self.name);
+        for arg in &self.args {
+            output.push_str(&arg.debug(indent + 2));
+        }
+        output.push_str(&format!("{}}}\n", " ".repeat(indent)));
+        output
+    }
+
+    /// Returns a Typist string for the expression.
+    /// Functions Typst knows are emitted by name, `sqrt` and `abs` use Typst's own functions,
+    /// and any other multi-letter name is set upright with `op`.
+    fn to_typist(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_typist()).collect();
+        let args = args.join(", ");
+        match self.name.as_str() {
+            "asin" | "acos" | "atan" => format!("arc{}({})", &self.name[1..], args),
+            "sign" => format!("op(\"sgn\")({})", args),
+            "conj" => format!("overline({})", args),
+            "re" | "im" => format!("op(\"{}\")({})", capitalize(&self.name), args),
+            name if Function::is_builtin(name) || name.chars().count() == 1 => {
+                format!("{}({})", name, args)
+            }
+            name => format!("op(\"{}\")({})", name, args),
+        }
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// Functions LaTeX has a command for are emitted as that command, `sqrt`, `abs` and `conj`
+    /// use their own notation, and any other multi-letter name is set with `\operatorname`.
+    fn to_latex(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_latex()).collect();
+        let args = args.join(", ");
+        match self.name.as_str() {
+            "sqrt" => format!("\\sqrt{{{}}}", args),
+            "abs" => format!("\\left|{}\\right|", args),
+            "conj" => format!("\\overline{{{}}}", args),
+            "asin" | "acos" | "atan" => format!("\\arc{}\\left({}\\right)", &self.name[1..], args),
+            "sign" => format!("\\operatorname{{sgn}}\\left({}\\right)", args),
+            "re" | "im" => format!(
+                "\\operatorname{{{}}}\\left({}\\right)",
+                capitalize(&self.name),
+                args
+            ),
+            name if Function::is_builtin(name) => format!("\\{}\\left({}\\right)", name, args),
+            name if name.chars().count() == 1 => format!("{}\\left({}\\right)", name, args),
+            name => format!("\\operatorname{{{}}}\\left({}\\right)", name, args),
+        }
+    }
+}
+
+impl Clone for Function {
+    fn clone(&self) -> Self {
+        Function {
+            name: self.name.clone(),
+            args: self.args.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::divide::Divide;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    fn call(name: &str, arg: Expr) -> Function {
+        Function::new(name, vec![arg])
+    }
+
+    #[test]
+    fn function_simplify_known_values() {
+        for (name, arg, expected) in [
+            ("sin", 0.0, 0.0),
+            ("cos", 0.0, 1.0),
+            ("ln", 1.0, 0.0),
+            ("exp", 0.0, 1.0),
+            ("sqrt", 16.0, 4.0),
+            ("abs", -3.0, 3.0),
+        ] {
+            let simplified = call(name, Expr::from(Constant::new(arg))).simplify();
+            if let Expr::Constant(constant) = &simplified {
+                assert_eq!(constant.value, expected, "simplifying {}({})", name, arg);
+            } else {
+                panic!("Expected Constant, found {:?}", simplified);
+            }
+        }
+    }
+
+    #[test]
+    fn function_simplify_keeps_inexact_values() {
+        let simplified = call("sin", Expr::from(Constant::new(1.0))).simplify();
+        assert!(matches!(simplified, Expr::Function(_)));
+        let simplified = call("sqrt", Expr::from(Constant::new(2.0))).simplify();
+        assert!(matches!(simplified, Expr::Function(_)));
+    }
+
+    #[test]
+    fn function_simplify_inverse_functions() {
+        let ln = call("ln", Expr::from(Variable::new("x")));
+        let simplified = call("exp", Expr::from(ln)).simplify();
+        if let Expr::Variable(variable) = &simplified {
+            assert_eq!(variable.name, "x");
+        } else {
+            panic!("Expected Variable, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn function_eval_numeric() {
+        let env = Environment::new().with("x", 2.0);
+        let evaluated = call("sqrt", Expr::from(Variable::new("x"))).eval(&env);
+        assert_eq!(evaluated, Ok(2.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn function_eval_errors() {
+        let env = Environment::new();
+        assert_eq!(
+            call("ln", Expr::from(Constant::new(-1.0))).eval(&env),
+            Err(EvalError::Domain("ln is undefined for -1".to_string()))
+        );
+        assert_eq!(
+            call("f", Expr::from(Constant::new(1.0))).eval(&env),
+            Err(EvalError::UnknownFunction("f".to_string()))
+        );
+        let sin = Function::new(
+            "sin",
+            vec![
+                Expr::from(Constant::new(1.0)),
+                Expr::from(Constant::new(2.0)),
+            ],
+        );
+        assert_eq!(
+            sin.eval(&env),
+            Err(EvalError::WrongArity {
+                name: "sin".to_string(),
+                expected: 1,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn function_to_typist() {
+        let x = || Expr::from(Variable::new("x"));
+        assert_eq!(call("sin", x()).to_typist(), "sin(x)");
+        assert_eq!(call("atan", x()).to_typist(), "arctan(x)");
+        assert_eq!(call("sqrt", x()).to_typist(), "sqrt(x)");
+        assert_eq!(call("f", x()).to_typist(), "f(x)");
+        assert_eq!(call("erf", x()).to_typist(), "op(\"erf\")(x)");
+    }
+
+    #[test]
+    fn function_to_latex() {
+        let x = || Expr::from(Variable::new("x"));
+        assert_eq!(call("sin", x()).to_latex(), "\\sin\\left(x\\right)");
+        assert_eq!(call("atan", x()).to_latex(), "\\arctan\\left(x\\right)");
+        assert_eq!(call("sqrt", x()).to_latex(), "\\sqrt{x}");
+        assert_eq!(call("abs", x()).to_latex(), "\\left|x\\right|");
+        assert_eq!(call("f", x()).to_latex(), "f\\left(x\\right)");
+        assert_eq!(
+            call("erf", x()).to_latex(),
+            "\\operatorname{erf}\\left(x\\right)"
+        );
+    }
+
+    #[test]
+    fn function_simplify_complex_parts() {
+        let z = || Expr::from(Constant::new(Number::complex(3, -4)));
+        for (name, expected) in [("conj", "3 + 4i"), ("re", "3"), ("im", "-4"), ("abs", "5")] {
+            assert_eq!(call(name, z()).simplify().to_typist(), expected, "{}", name);
+        }
+        let conj = call("conj", Expr::from(Variable::new("z")));
+        let simplified = call("conj", Expr::from(conj)).simplify();
+        assert!(matches!(simplified, Expr::Variable(_)));
+    }
+
+    #[test]
+    fn function_eval_complex() {
+        let env = Environment::new().with("z", Complex64::new(-1.0, 0.0));
+        let sqrt = call("sqrt", Expr::from(Variable::new("z")));
+        assert_eq!(sqrt.eval_complex(&env), Ok(Complex64::new(0.0, 1.0)));
+        assert!(matches!(sqrt.eval(&env), Err(EvalError::Domain(_))));
+        let arg = call("arg", Expr::from(Variable::new("z")));
+        assert_eq!(arg.eval(&env), Ok(std::f64::consts::PI));
+    }
+
+    #[test]
+    fn function_simplify_named_constants() {
+        let pi_over = |denominator: i64| -> Expr {
+            Expr::from(Divide::new(
+                Expr::from(NamedConstant::Pi),
+                Expr::from(Constant::new(denominator)),
+            ))
+        };
+        for (name, arg, expected) in [
+            ("sin", Expr::from(NamedConstant::Pi), "0"),
+            ("cos", Expr::from(NamedConstant::Pi), "-1"),
+            ("sin", pi_over(6), "frac(1, 2)"),
+            ("cos", pi_over(3), "frac(1, 2)"),
+            ("tan", pi_over(4), "1"),
+            ("ln", Expr::from(NamedConstant::E), "1"),
+        ] {
+            assert_eq!(call(name, arg).simplify().to_typist(), expected, "{}", name);
+        }
+        let simplified = call("sin", pi_over(5)).simplify();
+        assert!(matches!(simplified, Expr::Function(_)));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::{discriminant, Discriminant};
+use std::rc::Rc;
+
+use crate::algebra::add::Add;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::function::Function;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::negate::Negate;
+use crate::algebra::power::Power;
+
+/// Identifies an operand of an interned node: a named constant by its value,
+/// and any other node by the address of its shared allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Operand {
+    Named(NamedConstant),
+    Shared(*const ()),
+}
+
+impl Operand {
+    /// Returns the identity of `expr`.
+    pub(crate) fn of(expr: &Expr) -> Self {
+        let address: *const () = match expr {
+            Expr::NamedConstant(named) => return Operand::Named(*named),
+            Expr::Constant(constant) => Rc::as_ptr(constant).cast(),
+            Expr::Variable(variable) => Rc::as_ptr(variable).cast(),
+            Expr::Add(add) => Rc::as_ptr(add).cast(),
+            Expr::Multiply(multiply) => Rc::as_ptr(multiply).cast(),
+            Expr::Divide(divide) => Rc::as_ptr(divide).cast(),
+            Expr::Power(power) => Rc::as_ptr(power).cast(),
+            Expr::Negate(negate) => Rc::as_ptr(negate).cast(),
+            Expr::Function(function) => Rc::as_ptr(function).cast(),
+            Expr::Custom(custom) => Rc::as_ptr(custom).cast(),
+        };
+        Operand::Shared(address)
+    }
+}
+
+/// Identifies an interned node. Leaves are compared structurally, and any other node by its
+/// variant, its name and the identity of its interned operands, so looking up a node never
+/// descends into its subexpressions.
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Leaf(Expr),
+    Node(Discriminant<Expr>, String, Vec<Operand>),
+}
+
+impl Key {
+    /// Constructs the key of a node of the same variant as `expr` with the interned `operands`.
+    fn node(expr: &Expr, name: &str, operands: &[&Expr]) -> Self {
+        Key::Node(
+            discriminant(expr),
+            name.to_string(),
+            operands.iter().map(|op| Operand::of(op)).collect(),
+        )
+    }
+}
+
+/// `Interner` hash-conses expressions into a DAG in which every distinct subexpression is stored once.
+///
+/// Interning an expression returns an equal expression built from shared nodes, and interning a
+/// structurally equal expression again returns the very same nodes. Expressions interned by the
+/// same `Interner` are therefore equal exactly when [`Expr::ptr_eq`] returns `true`, which takes
+/// constant time. Custom nodes are interned as a whole, as their operands are unknown.
+#[derive(Default)]
+pub struct Interner {
+    nodes: HashMap<Key, Expr>,
+    /// Identities of the nodes in `nodes`, to recognise expressions that are already interned.
+    interned: HashSet<Operand>,
+}
+
+impl Interner {
+    /// Constructs a new, empty `Interner`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct subexpressions that have been interned.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if nothing has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the shared form of `expr`, interning any of its subexpressions not seen before.
+    /// A subexpression that appears several times in `expr` is only interned once,
+    /// so a DAG is interned in time proportional to its number of distinct nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to intern.
+    pub fn intern(&mut self, expr: &Expr) -> Expr {
+        self.intern_shared(expr, &mut HashMap::new())
+    }
+
+    /// Interns `expr`, looking up nodes of `expr` that were already interned in this call in `seen`.
+    fn intern_shared(&mut self, expr: &Expr, seen: &mut HashMap<Operand, Expr>) -> Expr {
+        let identity = Operand::of(expr);
+        if self.interned.contains(&identity) {
+            return expr.clone();
+        }
+        if let Some(interned) = seen.get(&identity) {
+            return interned.clone();
+        }
+
+        let interned = match expr {
+            Expr::Constant(_) | Expr::NamedConstant(_) | Expr::Variable(_) | Expr::Custom(_) => {
+                self.insert(Key::Leaf(expr.clone()), || expr.clone())
+            }
+            Expr::Add(add) => {
+                let ops = self.intern_all(&add.ops, seen);
+                let key = Key::node(expr, "", &ops.iter().collect::<Vec<_>>());
+                self.insert(key, || Expr::from(Add::new(ops)))
+            }
+            Expr::Multiply(multiply) => {
+                let ops = self.intern_all(&multiply.ops, seen);
+                let key = Key::node(expr, "", &ops.iter().collect::<Vec<_>>());
+                self.insert(key, || Expr::from(Multiply::new(ops)))
+            }
+            Expr::Divide(divide) => {
+                let numerator = self.intern_shared(&divide.numerator, seen);
+                let denominator = self.intern_shared(&divide.denominator, seen);
+                let key = Key::node(expr, "", &[&numerator, &denominator]);
+                self.insert(key, || Expr::from(Divide::new(numerator, denominator)))
+            }
+            Expr::Power(power) => {
+                let base = self.intern_shared(&power.base, seen);
+                let exponent = self.intern_shared(&power.exponent, seen);
+                let key = Key::node(expr, "", &[&base, &exponent]);
+                self.insert(key, || Expr::from(Power::new(base, exponent)))
+            }
+            Expr::Negate(negate) => {
+                let op = self.intern_shared(&negate.op, seen);
+                let key = Key::node(expr, "", &[&op]);
+                self.insert(key, || Expr::from(Negate::new(op)))
+            }
+            Expr::Function(function) => {
+                let args = self.intern_all(&function.args, seen);
+                let key = Key::node(expr, &function.name, &args.iter().collect::<Vec<_>>());
+                self.insert(key, || Expr::from(Function::new(&function.name, args)))
+            }
+        };
+        seen.insert(identity, interned.clone());
+        interned
+    }
+
+    /// Interns each of `ops`.
+    fn intern_all(&mut self, ops: &[Expr], seen: &mut HashMap<Operand, Expr>) -> Vec<Expr> {
+        ops.iter().map(|op| self.intern_shared(op, seen)).collect()
+    }
+
+    /// Returns the node stored under `key`, storing the node returned by `build` if there is none.
+    fn insert(&mut self, key: Key, build: impl FnOnce() -> Expr) -> Expr {
+        if let Some(node) = self.nodes.get(&key) {
+            return node.clone();
+        }
+        let node = build();
+        self.interned.insert(Operand::of(&node));
+        self.nodes.insert(key, node.clone());
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::constant::Constant;
+    use crate::algebra::eval::Environment;
+    use crate::algebra::expression::Expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    fn x_plus_one() -> Expr {
+        Expr::from(Add::new(vec![
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(1)),
+        ]))
+    }
+
+    #[test]
+    fn interner_shares_equal_subexpressions() {
+        let mut interner = Interner::new();
+        let square = Expr::from(Multiply::new(vec![x_plus_one(), x_plus_one()]));
+        let interned = interner.intern(&square);
+        assert_eq!(interned, square);
+        // x, 1, x + 1 and the product
+        assert_eq!(interner.len(), 4);
+        if let Expr::Multiply(multiply) = &interned {
+            assert!(multiply.ops[0].ptr_eq(&multiply.ops[1]));
+        } else {
+            panic!("Expected Multiply, found {:?}", interned);
+        }
+
+        let again = interner.intern(&Expr::from(Multiply::new(vec![x_plus_one(), x_plus_one()])));
+        assert!(again.ptr_eq(&interned));
+        assert!(interner.intern(&interned).ptr_eq(&interned));
+        assert!(!interner.intern(&x_plus_one()).ptr_eq(&interned));
+        assert_eq!(interner.len(), 4);
+    }
+
+    #[test]
+    fn interner_handles_deep_dags() {
+        // Each level adds the previous level to itself, so the tree has 2^64 leaves
+        let doubled = |levels: usize| {
+            let mut expr = Expr::from(Variable::new("x"));
+            for _ in 0..levels {
+                expr = Expr::from(Add::new(vec![expr.clone(), expr]));
+            }
+            expr
+        };
+        let mut interner = Interner::new();
+        let lhs = interner.intern(&doubled(64));
+        let rhs = interner.intern(&doubled(64));
+        assert_eq!(interner.len(), 65);
+        assert!(lhs.ptr_eq(&rhs));
+        assert_eq!(lhs, rhs);
+        let env = Environment::new().with("x", 1.0);
+        assert_eq!(interner.intern(&doubled(3)).eval(&env), Ok(8.0));
+    }
+}
@@ -0,0 +1,713 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::eval::EvalError;
+
+/// Precision in bits used to bound the elementary functions at the ends of an interval.
+const ENDPOINT_PRECISION: u64 = 64;
+
+/// Magnitude below which the rounding error of a product, quotient or square root may not be
+/// representable, so the result is widened by a unit in both directions.
+const TINY: f64 = 1.0e-290;
+
+/// `Interval` is a closed range of real numbers `[lower, upper]` that is guaranteed to contain
+/// a value. The ends may be infinite to stand for unbounded ranges.
+///
+/// Every operation rounds its lower end down and its upper end up, so the result always
+/// encloses every value the operation can take for arguments inside the operand intervals.
+/// Basic arithmetic and `sqrt` are rounded exactly one unit outward when inexact, and the
+/// elementary functions are bounded using [`BigFloat`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Interval {
+    /// Constructs a new `Interval` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - The lower end of the interval.
+    /// * `upper` - The upper end of the interval, at least `lower`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lower` is greater than `upper` or either end is NaN.
+    pub fn new(lower: f64, upper: f64) -> Self {
+        assert!(lower <= upper, "invalid interval [{}, {}]", lower, upper);
+        Self { lower, upper }
+    }
+
+    /// Returns the interval containing only `value`.
+    pub fn point(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Returns the interval of all real numbers.
+    pub fn entire() -> Self {
+        Self::new(f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    /// Returns the smallest interval of `f64` ends containing a rational number.
+    pub fn from_rational(value: &BigRational) -> Self {
+        Self::new(round_rational(value, false), round_rational(value, true))
+    }
+
+    /// Returns the smallest interval of `f64` ends containing every value of a `BigFloat`.
+    pub fn enclosing(value: &BigFloat) -> Self {
+        let (lower, upper) = value.bounds();
+        Self::new(round_rational(&lower, false), round_rational(&upper, true))
+    }
+
+    /// Returns `true` if `value` lies in the interval.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+
+    /// Returns the width `upper - lower`, rounded up.
+    pub fn width(&self) -> f64 {
+        add_rounded(self.upper, -self.lower).1
+    }
+
+    /// Returns the smallest interval containing both intervals.
+    pub fn hull(&self, other: &Self) -> Self {
+        Self::new(self.lower.min(other.lower), self.upper.max(other.upper))
+    }
+
+    /// Returns the negated interval, which is exact.
+    pub fn neg(&self) -> Self {
+        Self::new(-self.upper, -self.lower)
+    }
+
+    /// Returns the sum of two intervals.
+    pub fn add(&self, other: &Self) -> Self {
+        Self::new(
+            add_rounded(self.lower, other.lower).0,
+            add_rounded(self.upper, other.upper).1,
+        )
+    }
+
+    /// Returns the difference of two intervals.
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    /// Returns the product of two intervals, the hull of the products of their ends.
+    pub fn mul(&self, other: &Self) -> Self {
+        let products = [
+            mul_rounded(self.lower, other.lower),
+            mul_rounded(self.lower, other.upper),
+            mul_rounded(self.upper, other.lower),
+            mul_rounded(self.upper, other.upper),
+        ];
+        Self::new(
+            products
+                .iter()
+                .map(|(down, _)| *down)
+                .fold(f64::INFINITY, f64::min),
+            products
+                .iter()
+                .map(|(_, up)| *up)
+                .fold(f64::NEG_INFINITY, f64::max),
+        )
+    }
+
+    /// Returns the quotient of two intervals.
+    ///
+    /// A divisor containing zero gives the smallest interval enclosing every quotient, which is
+    /// unbounded, such as `[1, 2] / [0, 1] = [1, inf]` or `[1, 2] / [-1, 1] = [-inf, inf]`.
+    /// Returns `EvalError::DivisionByZero` if the divisor is exactly zero.
+    pub fn div(&self, other: &Self) -> Result<Self, EvalError> {
+        if other.lower == 0.0 && other.upper == 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+        if self.lower == 0.0 && self.upper == 0.0 {
+            return Ok(*self);
+        }
+        if other.contains(0.0) {
+            let positive = self.lower >= 0.0;
+            let negative = self.upper <= 0.0;
+            return Ok(if other.lower == 0.0 && positive {
+                Self::new(div_rounded(self.lower, other.upper).0, f64::INFINITY)
+            } else if other.lower == 0.0 && negative {
+                Self::new(f64::NEG_INFINITY, div_rounded(self.upper, other.upper).1)
+            } else if other.upper == 0.0 && positive {
+                Self::new(f64::NEG_INFINITY, div_rounded(self.lower, other.lower).1)
+            } else if other.upper == 0.0 && negative {
+                Self::new(div_rounded(self.upper, other.lower).0, f64::INFINITY)
+            } else {
+                Self::entire()
+            });
+        }
+        let quotients = [
+            div_rounded(self.lower, other.lower),
+            div_rounded(self.lower, other.upper),
+            div_rounded(self.upper, other.lower),
+            div_rounded(self.upper, other.upper),
+        ];
+        Ok(Self::new(
+            quotients
+                .iter()
+                .map(|(down, _)| *down)
+                .fold(f64::INFINITY, f64::min),
+            quotients
+                .iter()
+                .map(|(_, up)| *up)
+                .fold(f64::NEG_INFINITY, f64::max),
+        ))
+    }
+
+    /// Returns the interval raised to an integer power.
+    /// Even powers of an interval containing zero start at zero.
+    pub fn powi(&self, exponent: i64) -> Result<Self, EvalError> {
+        if exponent < 0 {
+            return Self::point(1.0).div(&self.powi(-exponent)?);
+        }
+        let exponent_big = BigInt::from(exponent);
+        let power = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x.is_infinite() {
+                let value = if x > 0.0 || exponent % 2 == 0 {
+                    f64::INFINITY
+                } else {
+                    f64::NEG_INFINITY
+                };
+                return Ok((value, value));
+            }
+            endpoint(x, |x| x.powi(&exponent_big, ENDPOINT_PRECISION))
+        };
+        if exponent == 0 {
+            return Ok(Self::point(1.0));
+        }
+        if exponent % 2 == 1 {
+            return Ok(Self::new(power(self.lower)?.0, power(self.upper)?.1));
+        }
+        let (smallest, largest) = self.magnitudes();
+        Ok(Self::new(power(smallest)?.0, power(largest)?.1))
+    }
+
+    /// Returns the interval raised to the power of another interval.
+    /// A point integer exponent uses `powi`, any other power is `exp(exponent * ln(base))` for the
+    /// part of the base that is not negative.
+    pub fn pow(&self, exponent: &Self) -> Result<Self, EvalError> {
+        if exponent.lower == exponent.upper
+            && exponent.lower.fract() == 0.0
+            && exponent.lower.abs() <= i32::MAX as f64
+        {
+            return self.powi(exponent.lower as i64);
+        }
+        if self.upper < 0.0 {
+            return Err(EvalError::Domain(format!(
+                "{}^{} is not a real number",
+                self, exponent
+            )));
+        }
+        if self.upper == 0.0 {
+            return if exponent.lower > 0.0 {
+                Ok(Self::point(0.0))
+            } else if exponent.upper < 0.0 {
+                Err(EvalError::DivisionByZero)
+            } else {
+                Ok(Self::new(0.0, f64::INFINITY))
+            };
+        }
+        exponent.mul(&self.ln()?).exp()
+    }
+
+    /// Returns the smallest and the largest magnitude of the values in the interval.
+    fn magnitudes(&self) -> (f64, f64) {
+        let largest = self.lower.abs().max(self.upper.abs());
+        if self.contains(0.0) {
+            (0.0, largest)
+        } else {
+            (self.lower.abs().min(self.upper.abs()), largest)
+        }
+    }
+
+    /// Returns the absolute values of the interval.
+    pub fn abs(&self) -> Self {
+        let (smallest, largest) = self.magnitudes();
+        Self::new(smallest, largest)
+    }
+
+    /// Returns the signs of the values in the interval, within `[-1, 1]`.
+    pub fn sign(&self) -> Self {
+        let sign = |x: f64| if x == 0.0 { 0.0 } else { x.signum() };
+        Self::new(sign(self.lower), sign(self.upper))
+    }
+
+    /// Returns the arguments of the values in the interval, 0 for non-negative values and `pi`
+    /// for negative ones.
+    pub fn arg(&self) -> Self {
+        let pi = Self::enclosing(&BigFloat::pi(ENDPOINT_PRECISION));
+        if self.upper < 0.0 {
+            pi
+        } else if self.lower >= 0.0 {
+            Self::point(0.0)
+        } else {
+            Self::new(0.0, pi.upper)
+        }
+    }
+
+    /// Returns the square roots of the non-negative part of the interval.
+    /// Returns an `EvalError::Domain` if every value is negative.
+    pub fn sqrt(&self) -> Result<Self, EvalError> {
+        if self.upper < 0.0 {
+            return Err(EvalError::Domain(format!("sqrt is undefined for {}", self)));
+        }
+        Ok(Self::new(
+            sqrt_rounded(self.lower.max(0.0)).0,
+            sqrt_rounded(self.upper).1,
+        ))
+    }
+
+    /// Returns the exponentials of the interval.
+    pub fn exp(&self) -> Result<Self, EvalError> {
+        // Beyond these the value is below the smallest positive f64 or above the largest one
+        let exp = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x < -746.0 {
+                Ok((0.0, f64::from_bits(1)))
+            } else if x > 710.0 {
+                Ok((f64::MAX, f64::INFINITY))
+            } else {
+                endpoint(x, |x| x.exp(ENDPOINT_PRECISION))
+            }
+        };
+        Ok(Self::new(exp(self.lower)?.0, exp(self.upper)?.1))
+    }
+
+    /// Returns the natural logarithms of the positive part of the interval.
+    /// Returns an `EvalError::Domain` if no value is positive.
+    pub fn ln(&self) -> Result<Self, EvalError> {
+        self.logarithm("ln", |x| x.ln(ENDPOINT_PRECISION))
+    }
+
+    /// Returns the base-10 logarithms of the positive part of the interval.
+    /// Returns an `EvalError::Domain` if no value is positive.
+    pub fn log10(&self) -> Result<Self, EvalError> {
+        self.logarithm("log", |x| x.log10(ENDPOINT_PRECISION))
+    }
+
+    /// Applies a logarithm to the positive part of the interval.
+    fn logarithm(
+        &self,
+        name: &str,
+        logarithm: impl Fn(&BigFloat) -> Result<BigFloat, EvalError>,
+    ) -> Result<Self, EvalError> {
+        if self.upper <= 0.0 {
+            return Err(EvalError::Domain(format!(
+                "{} is undefined for {}",
+                name, self
+            )));
+        }
+        let lower = if self.lower <= 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            endpoint(self.lower, &logarithm)?.0
+        };
+        let upper = if self.upper == f64::INFINITY {
+            f64::INFINITY
+        } else {
+            endpoint(self.upper, &logarithm)?.1
+        };
+        Ok(Self::new(lower, upper))
+    }
+
+    /// Returns the sines of the interval.
+    pub fn sin(&self) -> Result<Self, EvalError> {
+        // The sine peaks at quarter turns 1 mod 4 and bottoms out at quarter turns 3 mod 4
+        self.periodic(1, |x| Ok(x.sin_cos(ENDPOINT_PRECISION)?.0))
+    }
+
+    /// Returns the cosines of the interval.
+    pub fn cos(&self) -> Result<Self, EvalError> {
+        // The cosine peaks at quarter turns 0 mod 4 and bottoms out at quarter turns 2 mod 4
+        self.periodic(0, |x| Ok(x.sin_cos(ENDPOINT_PRECISION)?.1))
+    }
+
+    /// Bounds a sine or cosine over the interval by its ends and any extremum in between.
+    /// The maximum lies at the multiples `k` of `pi/2` with `k = peak mod 4`, the minimum at
+    /// `k = peak + 2 mod 4`.
+    fn periodic(
+        &self,
+        peak: i64,
+        f: impl Fn(&BigFloat) -> Result<BigFloat, EvalError>,
+    ) -> Result<Self, EvalError> {
+        let unit = Self::new(-1.0, 1.0);
+        let (Some(first), Some(last)) = (quarter_turns(self.lower), quarter_turns(self.upper))
+        else {
+            return Ok(unit);
+        };
+        let turns = (&last - &first).to_i64().unwrap_or(i64::MAX);
+        if turns >= 4 {
+            return Ok(unit);
+        }
+        let (lower_end, upper_end) = (endpoint(self.lower, &f)?, endpoint(self.upper, &f)?);
+        let mut lower = lower_end.0.min(upper_end.0);
+        let mut upper = lower_end.1.max(upper_end.1);
+        for k in 1..=turns {
+            let quarter = ((&first + k) % 4u32).to_i64().unwrap_or(0).rem_euclid(4);
+            if quarter == peak {
+                upper = 1.0;
+            } else if quarter == (peak + 2) % 4 {
+                lower = -1.0;
+            }
+        }
+        Ok(Self::new(lower.max(-1.0), upper.min(1.0)))
+    }
+
+    /// Returns the tangents of the interval, which is unbounded if it contains a pole.
+    pub fn tan(&self) -> Result<Self, EvalError> {
+        let (Some(first), Some(last)) = (quarter_turns(self.lower), quarter_turns(self.upper))
+        else {
+            return Ok(Self::entire());
+        };
+        // Poles lie at odd multiples of pi/2, and tan increases between them
+        let turns = &last - &first;
+        if turns > BigInt::from(1) || (turns == BigInt::from(1) && last.bit(0)) {
+            return Ok(Self::entire());
+        }
+        let tan = |x: &BigFloat| {
+            let (sin, cos) = x.sin_cos(ENDPOINT_PRECISION + 16)?;
+            sin.div(&cos, ENDPOINT_PRECISION)
+        };
+        Ok(Self::new(
+            endpoint(self.lower, tan)?.0,
+            endpoint(self.upper, tan)?.1,
+        ))
+    }
+
+    /// Returns the arcsines of the part of the interval within `[-1, 1]`.
+    /// Returns an `EvalError::Domain` if the interval lies outside of `[-1, 1]`.
+    pub fn asin(&self) -> Result<Self, EvalError> {
+        let x = self.unit_part("asin")?;
+        let asin = |x: &BigFloat| x.asin(ENDPOINT_PRECISION);
+        Ok(Self::new(
+            endpoint(x.lower, asin)?.0,
+            endpoint(x.upper, asin)?.1,
+        ))
+    }
+
+    /// Returns the arccosines of the part of the interval within `[-1, 1]`.
+    /// Returns an `EvalError::Domain` if the interval lies outside of `[-1, 1]`.
+    pub fn acos(&self) -> Result<Self, EvalError> {
+        let x = self.unit_part("acos")?;
+        let acos = |x: &BigFloat| x.acos(ENDPOINT_PRECISION);
+        // acos is decreasing
+        Ok(Self::new(
+            endpoint(x.upper, acos)?.0,
+            endpoint(x.lower, acos)?.1,
+        ))
+    }
+
+    /// Returns the part of the interval within `[-1, 1]`, the domain of `asin` and `acos`.
+    fn unit_part(&self, name: &str) -> Result<Self, EvalError> {
+        if self.upper < -1.0 || self.lower > 1.0 {
+            return Err(EvalError::Domain(format!(
+                "{} is undefined for {}",
+                name, self
+            )));
+        }
+        Ok(Self::new(self.lower.max(-1.0), self.upper.min(1.0)))
+    }
+
+    /// Returns the arctangents of the interval.
+    pub fn atan(&self) -> Result<Self, EvalError> {
+        let half_pi = Self::enclosing(&BigFloat::pi(ENDPOINT_PRECISION).mul_pow2(-1));
+        let atan = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x == f64::INFINITY {
+                Ok((half_pi.lower, half_pi.upper))
+            } else if x == f64::NEG_INFINITY {
+                Ok((-half_pi.upper, -half_pi.lower))
+            } else {
+                endpoint(x, |x| x.atan(ENDPOINT_PRECISION))
+            }
+        };
+        Ok(Self::new(atan(self.lower)?.0, atan(self.upper)?.1))
+    }
+
+    /// Returns the hyperbolic sines of the interval.
+    pub fn sinh(&self) -> Result<Self, EvalError> {
+        // Beyond 711 in magnitude the value is beyond the largest f64
+        let sinh = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x > 711.0 {
+                Ok((f64::MAX, f64::INFINITY))
+            } else if x < -711.0 {
+                Ok((f64::NEG_INFINITY, f64::MIN))
+            } else {
+                endpoint(x, |x| x.sinh_cosh_tanh("sinh", ENDPOINT_PRECISION))
+            }
+        };
+        Ok(Self::new(sinh(self.lower)?.0, sinh(self.upper)?.1))
+    }
+
+    /// Returns the hyperbolic cosines of the interval, which are smallest at the value nearest zero.
+    pub fn cosh(&self) -> Result<Self, EvalError> {
+        let cosh = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x > 711.0 {
+                Ok((f64::MAX, f64::INFINITY))
+            } else {
+                endpoint(x, |x| x.sinh_cosh_tanh("cosh", ENDPOINT_PRECISION))
+            }
+        };
+        let (smallest, largest) = self.magnitudes();
+        Ok(Self::new(cosh(smallest)?.0, cosh(largest)?.1))
+    }
+
+    /// Returns the hyperbolic tangents of the interval.
+    pub fn tanh(&self) -> Result<Self, EvalError> {
+        // Beyond 20 in magnitude the value is within a unit of 1 in the last place
+        let tanh = |x: f64| -> Result<(f64, f64), EvalError> {
+            if x > 20.0 {
+                Ok((1.0f64.next_down(), 1.0))
+            } else if x < -20.0 {
+                Ok((-1.0, (-1.0f64).next_up()))
+            } else {
+                endpoint(x, |x| x.sinh_cosh_tanh("tanh", ENDPOINT_PRECISION))
+            }
+        };
+        Ok(Self::new(tanh(self.lower)?.0, tanh(self.upper)?.1))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.lower, self.upper)
+    }
+}
+
+impl From<f64> for Interval {
+    fn from(value: f64) -> Self {
+        Self::point(value)
+    }
+}
+
+/// Returns the bounds `(lower, upper)` of a function at a finite end of an interval, evaluated
+/// with a guaranteed error bound.
+fn endpoint(
+    x: f64,
+    f: impl Fn(&BigFloat) -> Result<BigFloat, EvalError>,
+) -> Result<(f64, f64), EvalError> {
+    let x = BigFloat::from_f64(x).ok_or(EvalError::InsufficientPrecision)?;
+    let value = Interval::enclosing(&f(&x)?);
+    Ok((value.lower, value.upper))
+}
+
+/// Returns the number of whole quarter turns `floor(x / (pi/2))` up to a finite `x`, or `None` if
+/// `x` is infinite. As `x` is rational, it is never exactly on a multiple of `pi/2` other than 0.
+fn quarter_turns(x: f64) -> Option<BigInt> {
+    let x = BigFloat::from_f64(x)?;
+    // A large x needs as many extra bits of pi as it has bits before the point
+    let extra = x.to_f64().abs().log2().max(0.0) as u64;
+    let precision = ENDPOINT_PRECISION + extra;
+    let half_pi = BigFloat::pi(precision).mul_pow2(-1);
+    x.div(&half_pi, precision).ok()?.floor()
+}
+
+/// Rounds a rational number to the nearest `f64` in the direction of `up`.
+fn round_rational(value: &BigRational, up: bool) -> f64 {
+    let nearest = value.to_f64().unwrap_or(0.0);
+    let ordering = match BigRational::from_float(nearest) {
+        Some(rounded) => rounded.cmp(value),
+        None if nearest > 0.0 => Ordering::Greater,
+        None => Ordering::Less,
+    };
+    match ordering {
+        Ordering::Less if up => nearest.next_up(),
+        Ordering::Greater if !up => nearest.next_down(),
+        _ => nearest,
+    }
+}
+
+/// Returns the bounds `(down, up)` of a result rounded to nearest, given the sign of its rounding
+/// error, the exact result minus `value`.
+fn bracket(value: f64, error: f64) -> (f64, f64) {
+    if error > 0.0 {
+        (value, value.next_up())
+    } else if error < 0.0 {
+        (value.next_down(), value)
+    } else {
+        (value, value)
+    }
+}
+
+/// Returns the bounds of a result that overflowed to `value`, an infinity.
+fn overflowed(value: f64) -> (f64, f64) {
+    if value > 0.0 {
+        (f64::MAX, f64::INFINITY)
+    } else {
+        (f64::NEG_INFINITY, f64::MIN)
+    }
+}
+
+/// Returns `x + y` rounded down and up, using Knuth's TwoSum for the exact rounding error.
+fn add_rounded(x: f64, y: f64) -> (f64, f64) {
+    let sum = x + y;
+    if sum.is_infinite() {
+        return if x.is_finite() && y.is_finite() {
+            overflowed(sum)
+        } else {
+            (sum, sum)
+        };
+    }
+    let y_part = sum - x;
+    let x_part = sum - y_part;
+    bracket(sum, (x - x_part) + (y - y_part))
+}
+
+/// Returns `x * y` rounded down and up, using a fused multiply-add for the exact rounding error.
+/// Zero times an infinite end is zero.
+fn mul_rounded(x: f64, y: f64) -> (f64, f64) {
+    if x == 0.0 || y == 0.0 {
+        return (0.0, 0.0);
+    }
+    let product = x * y;
+    if product.is_infinite() {
+        return if x.is_finite() && y.is_finite() {
+            overflowed(product)
+        } else {
+            (product, product)
+        };
+    }
+    if product.abs() < TINY {
+        return (product.next_down(), product.next_up());
+    }
+    bracket(product, x.mul_add(y, -product))
+}
+
+/// Returns `x / y` rounded down and up for a non-zero `y`, using a fused multiply-add for the
+/// exact remainder.
+fn div_rounded(x: f64, y: f64) -> (f64, f64) {
+    if x == 0.0 {
+        return (0.0, 0.0);
+    }
+    let quotient = x / y;
+    if quotient.is_nan() {
+        return (f64::NEG_INFINITY, f64::INFINITY);
+    }
+    if !x.is_finite() || !y.is_finite() {
+        return (quotient, quotient);
+    }
+    if quotient.is_infinite() {
+        return overflowed(quotient);
+    }
+    if quotient.abs() < TINY {
+        return (quotient.next_down(), quotient.next_up());
+    }
+    // x / y - quotient has the sign of (x - quotient * y) / y
+    let remainder = (-quotient).mul_add(y, x);
+    bracket(quotient, remainder * y.signum())
+}
+
+/// Returns `sqrt(x)` rounded down and up for a non-negative `x`.
+fn sqrt_rounded(x: f64) -> (f64, f64) {
+    let root = x.sqrt();
+    if x == 0.0 || x.is_infinite() {
+        return (root, root);
+    }
+    if x < TINY {
+        return (root.next_down(), root.next_up());
+    }
+    // sqrt(x) - root has the sign of x - root^2
+    bracket(root, (-root).mul_add(root, x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_arithmetic_rounds_outward() {
+        let sum = Interval::point(0.1).add(&Interval::point(0.2));
+        assert_eq!(sum.lower.next_up(), sum.upper);
+        let exact = BigRational::from_float(0.1).unwrap() + BigRational::from_float(0.2).unwrap();
+        assert!(BigRational::from_float(sum.lower).unwrap() < exact);
+        assert!(exact < BigRational::from_float(sum.upper).unwrap());
+
+        let third = Interval::point(1.0).div(&Interval::point(3.0)).unwrap();
+        assert_eq!(third.lower.next_up(), third.upper);
+        assert_eq!(
+            Interval::new(1.0, 2.0).mul(&Interval::new(-3.0, 4.0)),
+            Interval::new(-6.0, 8.0)
+        );
+        assert_eq!(
+            Interval::new(1.0, 4.0).sqrt().unwrap(),
+            Interval::new(1.0, 2.0)
+        );
+        let root = Interval::point(2.0).sqrt().unwrap();
+        let square = |x: f64| BigRational::from_float(x).unwrap().pow(2);
+        let two = BigRational::from_integer(2.into());
+        assert!(square(root.lower) < two && two < square(root.upper));
+    }
+
+    #[test]
+    fn interval_division_by_zero() {
+        let x = Interval::new(1.0, 2.0);
+        assert_eq!(
+            x.div(&Interval::new(0.0, 4.0)),
+            Ok(Interval::new(0.25, f64::INFINITY))
+        );
+        assert_eq!(
+            x.div(&Interval::new(-4.0, 0.0)),
+            Ok(Interval::new(f64::NEG_INFINITY, -0.25))
+        );
+        assert_eq!(x.div(&Interval::new(-1.0, 1.0)), Ok(Interval::entire()));
+        assert_eq!(x.div(&Interval::point(0.0)), Err(EvalError::DivisionByZero));
+        assert_eq!(
+            Interval::new(-1.0, 2.0).powi(2),
+            Ok(Interval::new(0.0, 4.0))
+        );
+        assert_eq!(Interval::new(-2.0, 1.0).powi(-1), Ok(Interval::entire()));
+    }
+
+    #[test]
+    fn interval_functions() {
+        let pi = Interval::enclosing(&BigFloat::pi(64));
+        assert!(pi.contains(std::f64::consts::PI));
+
+        let sin = Interval::new(0.0, 3.0).sin().unwrap();
+        assert_eq!(sin.lower, 0.0);
+        assert_eq!(sin.upper, 1.0);
+        let cos = Interval::new(1.0, 4.0).cos().unwrap();
+        assert_eq!(cos.lower, -1.0);
+        assert!(cos.contains(1.0f64.cos()) && cos.upper < 0.6);
+        assert_eq!(
+            Interval::new(-10.0, 10.0).sin(),
+            Ok(Interval::new(-1.0, 1.0))
+        );
+        assert_eq!(Interval::new(1.0, 2.0).tan(), Ok(Interval::entire()));
+
+        let exp = Interval::new(0.0, 1.0).exp().unwrap();
+        assert_eq!(exp.lower, 1.0);
+        assert!(exp.contains(std::f64::consts::E));
+        assert_eq!(
+            Interval::new(-1.0, 1.0).ln().map(|x| x.lower),
+            Ok(f64::NEG_INFINITY)
+        );
+        assert!(matches!(
+            Interval::new(-2.0, -1.0).ln(),
+            Err(EvalError::Domain(_))
+        ));
+        assert_eq!(Interval::new(-4.0, 1.0).sqrt(), Ok(Interval::new(0.0, 1.0)));
+        let cosh = Interval::new(-1.0, 2.0).cosh().unwrap();
+        assert_eq!(cosh.lower, 1.0);
+        assert!(cosh.contains(2.0f64.cosh()));
+    }
+
+    #[test]
+    fn interval_encloses_large_arguments() {
+        let sin = Interval::point(1.0e22).sin().unwrap();
+        // sin(1e22) = -0.8522008497671888017727...
+        assert!(sin.contains(-0.852_200_849_767_188_8));
+        assert!(sin.width() < 1.0e-15);
+        assert_eq!(
+            Interval::new(800.0, 900.0).exp(),
+            Ok(Interval::new(f64::MAX, f64::INFINITY))
+        );
+    }
+}
@@ -0,0 +1,917 @@
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{alpha1, anychar, char, digit1, multispace0, satisfy};
+use nom::combinator::{map, map_opt, opt, recognize, value, verify};
+use nom::multi::many0_count;
+use nom::sequence::{delimited, preceded, tuple};
+
+use crate::algebra::constant::Constant;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::function::Function;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::negate::Negate;
+use crate::algebra::number::Number;
+use crate::algebra::parse_error::ParseError;
+use crate::algebra::parser::{
+    expect_operand, failure, Operator, ParseResult, SyntaxError, SyntaxErrorKind, UNARY_PRECEDENCE,
+};
+use crate::algebra::power::Power;
+use crate::algebra::variable::{greek_letter_index, Variable};
+
+/// Commands that only change spacing or style, which are skipped like whitespace.
+const IGNORED_COMMANDS: [&str; 9] = [
+    "\\,",
+    "\\:",
+    "\\;",
+    "\\!",
+    "\\ ",
+    "\\qquad",
+    "\\quad",
+    "\\displaystyle",
+    "\\textstyle",
+];
+
+/// Math mode delimiters that may surround the input, as copied from a document or a notebook.
+const MATH_DELIMITERS: [(&str, &str); 4] =
+    [("$$", "$$"), ("$", "$"), ("\\[", "\\]"), ("\\(", "\\)")];
+
+/// Opening and closing delimiters of an expression, and whether they denote its absolute value.
+const DELIMITERS: [(&str, &str, bool); 6] = [
+    ("(", ")", false),
+    ("[", "]", false),
+    ("\\{", "\\}", false),
+    ("|", "|", true),
+    ("\\lvert", "\\rvert", true),
+    ("\\vert", "\\vert", true),
+];
+
+/// Names of commands that cannot start an operand, such as operators and closing delimiters.
+const NON_OPERAND_COMMANDS: [&str; 9] = [
+    "right", "rvert", "vert", "cdot", "times", "div", "}", ")", "]",
+];
+
+/// Functions written as a command, such as `\sin`, or with `\operatorname` or `\mathrm`, and the
+/// names of the built-in functions they are parsed as.
+const LATEX_FUNCTIONS: [(&str, &str); 16] = [
+    ("sin", "sin"),
+    ("cos", "cos"),
+    ("tan", "tan"),
+    ("arcsin", "asin"),
+    ("arccos", "acos"),
+    ("arctan", "atan"),
+    ("sinh", "sinh"),
+    ("cosh", "cosh"),
+    ("tanh", "tanh"),
+    ("exp", "exp"),
+    ("ln", "ln"),
+    ("log", "log"),
+    ("arg", "arg"),
+    ("Re", "re"),
+    ("Im", "im"),
+    ("sgn", "sign"),
+];
+
+/// Variant forms of Greek letters and the letters they are parsed as.
+const GREEK_VARIANTS: [(&str, &str); 6] = [
+    ("varepsilon", "epsilon"),
+    ("vartheta", "theta"),
+    ("varkappa", "kappa"),
+    ("varrho", "rho"),
+    ("varsigma", "sigma"),
+    ("varphi", "phi"),
+];
+
+/// Skips whitespace and the [`IGNORED_COMMANDS`] at the start of the input.
+fn skip_space(mut input: &str) -> &str {
+    loop {
+        let trimmed = input.trim_start();
+        match IGNORED_COMMANDS
+            .iter()
+            .find_map(|command| trimmed.strip_prefix(command))
+        {
+            Some(rest) => input = rest,
+            None => return trimmed,
+        }
+    }
+}
+
+/// Returns an unrecoverable "invalid input" error at the start of `input`.
+fn invalid<T>(input: &str) -> ParseResult<'_, T> {
+    failure(input, SyntaxErrorKind::Invalid)
+}
+
+/// Returns a recoverable error at the start of `input`, for a parser that does not apply to it.
+fn mismatch<T>(input: &str) -> ParseResult<'_, T> {
+    Err(nom::Err::Error(SyntaxError {
+        input,
+        kind: SyntaxErrorKind::Invalid,
+    }))
+}
+
+/// Parses a command from the input string, such as `\frac` or `\,`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a backslash.
+///
+/// # Returns
+///
+/// * `ParseResult<&str>` - On success, the function returns the remaining input and the name of the command
+///   without the backslash. The name is either a sequence of letters or a single other character.
+fn parse_command(input: &str) -> ParseResult<'_, &str> {
+    preceded(char('\\'), alt((alpha1, recognize(anychar))))(input)
+}
+
+/// Returns a parser for the command called `name`, which does not accept longer names starting with `name`.
+fn command<'a>(name: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    verify(parse_command, move |found: &str| found == name)
+}
+
+/// Returns the text of `input` consumed up to `rest`.
+fn consumed<'a>(input: &'a str, rest: &str) -> &'a str {
+    &input[..input.len() - rest.len()]
+}
+
+/// Returns the name of the Greek letter written as the command `name`, such as `alpha` for `\alpha`,
+/// `Omega` for `\Omega` and `phi` for `\varphi`.
+fn greek_letter(name: &str) -> Option<&str> {
+    if let Some((_, letter)) = GREEK_VARIANTS.iter().find(|(variant, _)| *variant == name) {
+        return Some(letter);
+    }
+    greek_letter_index(name).map(|_| name)
+}
+
+/// Returns the expression a name without subscript or primes stands for: the imaginary unit for
+/// `i`, a named constant for `e`, `π`, `\pi`, `φ`, `\varphi`, `γ` and `\gamma`, and a variable otherwise.
+fn resolve_name(name: &str) -> Expr {
+    match name {
+        "i" => Expr::from(Constant::new(Number::i())),
+        "e" => Expr::from(NamedConstant::E),
+        "pi" | "π" => Expr::from(NamedConstant::Pi),
+        "phi" | "φ" => Expr::from(NamedConstant::Phi),
+        "gamma" | "γ" => Expr::from(NamedConstant::EulerGamma),
+        name => Expr::from(Variable::new(name)),
+    }
+}
+
+/// Parses the text argument of a command such as `\mathrm{max}` or `\operatorname{erf}`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `{`.
+///
+/// # Returns
+///
+/// * `ParseResult<&str>` - On success, the function returns the remaining input and the letters and digits in braces.
+fn parse_text_argument(input: &str) -> ParseResult<'_, &str> {
+    delimited(
+        tuple((multispace0, char('{'), multispace0)),
+        take_while1(char::is_alphanumeric),
+        tuple((multispace0, char('}'))),
+    )(input)
+}
+
+/// Parses a single part of a subscript: letters and digits, a Greek letter command, or text set
+/// with `\mathrm`, `\mathit` or `\text`. Outside of braces, `single` limits letters and digits to one character.
+fn parse_subscript_part(input: &str, single: bool) -> ParseResult<'_, String> {
+    if let Ok((rest, name)) = parse_command(input) {
+        return match name {
+            "mathrm" | "mathit" | "text" => map(parse_text_argument, str::to_string)(rest),
+            name => match greek_letter(name) {
+                Some(letter) => Ok((rest, letter.to_string())),
+                None => invalid(input),
+            },
+        };
+    }
+    let text: ParseResult<'_, &str> = if single {
+        recognize(satisfy(char::is_alphanumeric))(input)
+    } else {
+        take_while1(char::is_alphanumeric)(input)
+    };
+    match text {
+        Ok((rest, text)) => Ok((rest, text.to_string())),
+        Err(_) => invalid(input),
+    }
+}
+
+/// Parses a subscript from the input string, either a single token as in `x_1` or a group as in
+/// `x_{12}` or `v_{\mathrm{max}}`. Only names can be subscripts, so `x_{i+1}` is rejected.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with the subscript, after the `_`.
+///
+/// # Returns
+///
+/// * `ParseResult<String>` - On success, the function returns the remaining input and the text of the subscript.
+fn parse_subscript(input: &str) -> ParseResult<'_, String> {
+    let input = skip_space(input);
+    let Some(mut rest) = input.strip_prefix('{') else {
+        return parse_subscript_part(input, true);
+    };
+    let mut text = String::new();
+    loop {
+        rest = skip_space(rest);
+        if let Some(after) = rest.strip_prefix('}') {
+            return if text.is_empty() {
+                invalid(rest)
+            } else {
+                Ok((after, text))
+            };
+        }
+        if rest.is_empty() {
+            return failure(rest, SyntaxErrorKind::UnclosedDelimiter(input, "{", "}"));
+        }
+        let (after, part) = parse_subscript_part(rest, false)?;
+        text.push_str(&part);
+        rest = after;
+    }
+}
+
+/// Parses the subscript and primes that may follow the base of a name, as in `x_{i}`, `f'` or
+/// `\theta_0`, and returns the named expression. A superscript may come before the subscript, so
+/// `x^{2}_{1}` is parsed like `x_1^2`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin right after the base of the name.
+/// * `base` - The base of the name, such as `x` or `theta`.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the named expression as a `Expr`.
+fn parse_name<'a>(input: &'a str, base: &str) -> ParseResult<'a, Expr> {
+    let (rest, primes) = recognize(many0_count(char('\'')))(input)?;
+    // The superscript is only taken here if a subscript follows, otherwise it is a power as usual
+    let (rest, exponent) = match skip_space(rest).strip_prefix('^').map(parse_argument) {
+        Some(Ok((after, exponent))) if after.starts_with('_') => (after, Some(exponent)),
+        _ => (rest, None),
+    };
+    let (rest, subscript) = match skip_space(rest).strip_prefix('_') {
+        Some(after) => {
+            let (after, subscript) = parse_subscript(after)?;
+            (after, Some(subscript))
+        }
+        None => (rest, None),
+    };
+    let (rest, more_primes) = recognize(many0_count(char('\'')))(rest)?;
+    let name = match subscript {
+        Some(subscript) => format!("{}_{}{}{}", base, subscript, primes, more_primes),
+        None if primes.is_empty() && more_primes.is_empty() => {
+            return Ok((rest, resolve_name(base)))
+        }
+        None => format!("{}{}{}", base, primes, more_primes),
+    };
+    let variable = Expr::from(Variable::new(&name));
+    match exponent {
+        Some(exponent) => Ok((rest, Expr::from(Power::new(variable, exponent)))),
+        None => Ok((rest, variable)),
+    }
+}
+
+/// Parses a number from the input string, such as `3` or `3.14`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a number.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed number as a `Expr`.
+fn parse_number(input: &str) -> ParseResult<'_, Expr> {
+    map_opt(
+        recognize(alt((
+            recognize(tuple((digit1, opt(tuple((char('.'), digit1)))))),
+            recognize(tuple((char('.'), digit1))),
+        ))),
+        |digits: &str| Number::parse_decimal(digits).map(|num| Expr::from(Constant::new(num))),
+    )(input)
+}
+
+/// Parses a single letter from the input string, together with its subscript and primes.
+///
+/// As in LaTeX, every letter is a name of its own, so `xy` is the product of `x` and `y`.
+/// Longer names are written as `\mathit{mass}` or as Greek letter commands such as `\alpha`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a letter.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the named expression as a `Expr`.
+fn parse_letter(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, letter) = recognize(satisfy(char::is_alphabetic))(input)?;
+    parse_name(rest, letter)
+}
+
+/// Strips a `\left` from the start of the input, returning whether there was one.
+fn strip_left(input: &str) -> (&str, bool) {
+    match command("left")(input) {
+        Ok((rest, _)) => (skip_space(rest), true),
+        Err(_) => (input, false),
+    }
+}
+
+/// Strips the delimiter `closing` from the start of the input, preceded by `\right` if `sized` is `true`.
+fn strip_closing<'a>(input: &'a str, closing: &str, sized: bool) -> Option<&'a str> {
+    let input = skip_space(input);
+    let input = if sized {
+        skip_space(command("right")(input).ok()?.0)
+    } else {
+        input
+    };
+    input.strip_prefix(closing)
+}
+
+/// Parses an expression in braces from the input string, as in `{x + 1}`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `{`.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_group(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, open) = tag("{")(input)?;
+    let (rest, expr) = expect_operand(parse_binary(rest, 0), rest, open)?;
+    match strip_closing(rest, "}", false) {
+        Some(rest) => Ok((rest, expr)),
+        None => failure(
+            skip_space(rest),
+            SyntaxErrorKind::UnclosedDelimiter(input, open, "}"),
+        ),
+    }
+}
+
+/// Parses an expression between delimiters from the input string, such as `(x + 1)`,
+/// `\left[x\right]` or `|x|`. Vertical bars denote the absolute value of the expression.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with an opening delimiter, optionally preceded by `\left`.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_delimited(input: &str) -> ParseResult<'_, Expr> {
+    let (open, sized) = strip_left(input);
+    let delimiter = DELIMITERS
+        .iter()
+        .find(|(opening, _, _)| open.starts_with(opening));
+    let (opening, closing, is_absolute) = match delimiter {
+        Some(delimiter) => *delimiter,
+        None if sized => return invalid(open),
+        None => return mismatch(input),
+    };
+    let rest = &open[opening.len()..];
+    let (rest, expr) = expect_operand(parse_binary(rest, 0), rest, &open[..opening.len()])?;
+    match strip_closing(rest, closing, sized) {
+        Some(rest) if is_absolute => Ok((rest, Expr::from(Function::new("abs", vec![expr])))),
+        Some(rest) => Ok((rest, expr)),
+        None => failure(
+            skip_space(rest),
+            SyntaxErrorKind::UnclosedDelimiter(input, &open[..opening.len()], closing),
+        ),
+    }
+}
+
+/// Parses a parenthesized, comma-separated list of function arguments from the input string.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `(` or `\left(`.
+///
+/// # Returns
+///
+/// * `ParseResult<Vec<Expr>>` - On success, the function returns the remaining input and the parsed arguments.
+fn parse_arguments(input: &str) -> ParseResult<'_, Vec<Expr>> {
+    let (open, sized) = strip_left(input);
+    let (mut rest, mut after) = match open.strip_prefix('(') {
+        Some(rest) => (rest, &open[..1]),
+        None => return invalid(open),
+    };
+    let mut args = Vec::new();
+    loop {
+        let (next, arg) = expect_operand(parse_binary(rest, 0), rest, after)?;
+        args.push(arg);
+        let next = skip_space(next);
+        if let Some(after_comma) = next.strip_prefix(',') {
+            (rest, after) = (after_comma, &next[..1]);
+        } else if let Some(next) = strip_closing(next, ")", sized) {
+            return Ok((next, args));
+        } else {
+            return failure(
+                next,
+                SyntaxErrorKind::UnclosedDelimiter(input, &open[..1], ")"),
+            );
+        }
+    }
+}
+
+/// Parses a single-token argument of a command or a superscript from the input string: an
+/// expression in braces, a single digit, a single letter, or a command such as `\pi`. So
+/// `\frac12` is `1/2` and `x^2y` is `x^2` times `y`, as in LaTeX.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with the argument.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the argument as a `Expr`.
+fn parse_argument(input: &str) -> ParseResult<'_, Expr> {
+    let input = skip_space(input);
+    let (rest, argument) = alt((
+        parse_group,
+        map_opt(recognize(satisfy(|c| c.is_ascii_digit())), |digit: &str| {
+            Number::parse_decimal(digit).map(|num| Expr::from(Constant::new(num)))
+        }),
+        map(recognize(satisfy(char::is_alphabetic)), resolve_name),
+        parse_command_operand,
+    ))(input)?;
+    Ok((skip_space(rest), argument))
+}
+
+/// Parses a subscript or superscript argument introduced by `marker`, such as `_2` or `^{2}`,
+/// if the input starts with it.
+fn parse_script(input: &str, marker: char) -> ParseResult<'_, Option<Expr>> {
+    match input.strip_prefix(marker) {
+        Some(after) => {
+            let (after, argument) = expect_operand(parse_argument(after), after, &input[..1])?;
+            Ok((skip_space(after), Some(argument)))
+        }
+        None => Ok((input, None)),
+    }
+}
+
+/// Parses the argument of a function written as a command, such as `\sin`, and returns the call.
+///
+/// As with [`parse_expression`](crate::algebra::parser::parse_expression), the argument is either
+/// given in parentheses, as in `\sin\left(x\right)`, or without them, as in `\sin x`. An argument
+/// without parentheses takes the implicit product that follows it, so `\sin 2x` is `sin(2x)`, up
+/// to the next function, so `\sin x \cos x` is `sin(x) cos(x)`. A power written right after the
+/// name, as in `\sin^2 x`, applies to the call. A logarithm may have a base written as a
+/// subscript before or after the power, as in `\log_2 x`, which is read as `ln(x)/ln(2)`.
+///
+/// # Arguments
+///
+/// * `start` - The input at the start of the function name.
+/// * `input` - The input right after the function name.
+/// * `name` - The name of the function to call.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed call as a `Expr`.
+fn parse_call<'a>(start: &'a str, input: &'a str, name: &'a str) -> ParseResult<'a, Expr> {
+    let token = consumed(start, input);
+    let rest = skip_space(input);
+    let is_log = name == "log";
+    let (rest, base) = if is_log {
+        parse_script(rest, '_')?
+    } else {
+        (rest, None)
+    };
+    let (rest, exponent) = parse_script(rest, '^')?;
+    let (rest, base) = match base {
+        None if is_log => parse_script(rest, '_')?,
+        base => (rest, base),
+    };
+    let (rest, args) = if strip_left(rest).0.starts_with('(') {
+        parse_arguments(rest)?
+    } else if rest.starts_with(['+', '-']) {
+        // A sign would make `\sin - x` a call rather than a subtraction
+        return failure(rest, SyntaxErrorKind::ExpectedOperand(Some(token)));
+    } else {
+        let (mut rest, mut arg) =
+            expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, token)?;
+        while starts_implicit_operand(rest) && !starts_function(rest) {
+            let (after, factor) = parse_binary(rest, UNARY_PRECEDENCE)?;
+            arg = Operator::Multiply.apply(arg, factor);
+            rest = after;
+        }
+        (rest, vec![arg])
+    };
+    if Function::is_builtin(name) && args.len() != 1 {
+        return failure(start, SyntaxErrorKind::WrongArity(name, args.len()));
+    }
+    let call = match base {
+        // A logarithm to another base is a quotient of natural logarithms
+        Some(base) => Expr::from(Divide::new(
+            Expr::from(Function::new("ln", args)),
+            Expr::from(Function::new("ln", vec![base])),
+        )),
+        None => Expr::from(Function::new(name, args)),
+    };
+    match exponent {
+        Some(exponent) => Ok((rest, Expr::from(Power::new(call, exponent)))),
+        None => Ok((rest, call)),
+    }
+}
+
+/// Parses an operand written as a command from the input string, such as `\frac{1}{2}`,
+/// `\sqrt[3]{x}`, `\sin x`, `\operatorname{erf}(x)`, `\overline{z}`, `\pi` or `\alpha_1`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a command.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed operand as a `Expr`.
+fn parse_command_operand(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, name) = parse_command(input)?;
+    let token = consumed(input, rest);
+    match name {
+        "frac" | "dfrac" | "tfrac" => {
+            let (rest, numerator) = expect_operand(parse_argument(rest), rest, token)?;
+            let (rest, denominator) = expect_operand(parse_argument(rest), rest, token)?;
+            Ok((rest, Expr::from(Divide::new(numerator, denominator))))
+        }
+        "sqrt" => {
+            let open = skip_space(rest);
+            let (rest, index) = match open.strip_prefix('[') {
+                Some(after) => {
+                    let (after, index) = expect_operand(parse_binary(after, 0), after, &open[..1])?;
+                    match strip_closing(after, "]", false) {
+                        Some(after) => (after, Some(index)),
+                        None => {
+                            return failure(
+                                skip_space(after),
+                                SyntaxErrorKind::UnclosedDelimiter(open, &open[..1], "]"),
+                            )
+                        }
+                    }
+                }
+                None => (rest, None),
+            };
+            let (rest, radicand) = expect_operand(parse_argument(rest), rest, token)?;
+            Ok(match index {
+                Some(index) => {
+                    let exponent = Divide::new(Expr::from(Constant::new(1)), index);
+                    (rest, Expr::from(Power::new(radicand, Expr::from(exponent))))
+                }
+                None => (rest, Expr::from(Function::new("sqrt", vec![radicand]))),
+            })
+        }
+        "overline" | "bar" => {
+            let (rest, arg) = expect_operand(parse_argument(rest), rest, token)?;
+            Ok((rest, Expr::from(Function::new("conj", vec![arg]))))
+        }
+        "operatorname" | "mathrm" | "mathit" | "text" => match parse_text_argument(rest) {
+            Ok((after, text)) if is_named_call(name, after) => {
+                let function = LATEX_FUNCTIONS
+                    .iter()
+                    .find(|(latex, _)| *latex == text)
+                    .map_or(text, |(_, function)| function);
+                parse_call(input, after, function)
+            }
+            Ok((after, text)) => parse_name(after, text),
+            Err(_) => invalid(skip_space(rest)),
+        },
+        name if NON_OPERAND_COMMANDS.contains(&name) => mismatch(input),
+        name => {
+            if let Some((_, function)) = LATEX_FUNCTIONS.iter().find(|(latex, _)| *latex == name) {
+                return parse_call(input, rest, function);
+            }
+            match greek_letter(name) {
+                Some(letter) => parse_name(rest, letter),
+                None => failure(input, SyntaxErrorKind::UnknownCommand(token)),
+            }
+        }
+    }
+}
+
+/// Parses a binary operator from the input string, skipping surrounding whitespace.
+/// Besides `+`, `-`, `*`, `/` and `^`, products may be written with `\cdot` or `\times` and
+/// quotients with `\div`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with an operator.
+///
+/// # Returns
+///
+/// * `ParseResult<(Operator, &str)>` - On success, the function returns the remaining input, the parsed
+///   operator and the text it was written with.
+fn parse_operator(input: &str) -> ParseResult<'_, (Operator, &str)> {
+    let input = skip_space(input);
+    let (rest, operator) = alt((
+        value(Operator::Add, tag("+")),
+        value(Operator::Subtract, tag("-")),
+        value(
+            Operator::Multiply,
+            alt((tag("*"), command("cdot"), command("times"))),
+        ),
+        value(Operator::Divide, alt((tag("/"), command("div")))),
+        value(Operator::Power, tag("^")),
+    ))(input)?;
+    Ok((skip_space(rest), (operator, consumed(input, rest))))
+}
+
+/// Parses a factor from the input string.
+///
+/// A factor is an expression in braces or between delimiters, a number, a letter, or a command
+/// such as `\frac`, `\sqrt`, `\sin` or `\alpha`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a factor.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed factor as a `Expr`.
+fn parse_factor(input: &str) -> ParseResult<'_, Expr> {
+    let input = skip_space(input);
+    let (rest, factor) = alt((
+        parse_group,
+        parse_delimited,
+        parse_number,
+        parse_letter,
+        parse_command_operand,
+    ))(input)?;
+    Ok((skip_space(rest), factor))
+}
+
+/// Parses a unary expression from the input string.
+///
+/// A unary expression is a factor optionally preceded by any number of prefix `-` or `+` signs.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a unary expression.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_unary(input: &str) -> ParseResult<'_, Expr> {
+    let input = skip_space(input);
+    if let Some(rest) = input.strip_prefix('-') {
+        let (rest, op) = expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, "-")?;
+        Ok((rest, Expr::from(Negate::new(op))))
+    } else if let Some(rest) = input.strip_prefix('+') {
+        expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, "+")
+    } else {
+        parse_factor(input)
+    }
+}
+
+/// Returns `true` if the input starts with an operand that may be multiplied by juxtaposition,
+/// as in `2x`, `xy`, `2\pi r` or `\frac{1}{2}\left(a+b\right)`.
+///
+/// As with [`parse_expression`](crate::algebra::parser::parse_expression), a number never may, and
+/// neither may a vertical bar, which closes an absolute value rather than opening another one.
+fn starts_implicit_operand(input: &str) -> bool {
+    let input = skip_space(input);
+    match input.chars().next() {
+        Some('(' | '[' | '{') => true,
+        Some('\\') => {
+            !matches!(parse_command(input), Ok((_, name)) if NON_OPERAND_COMMANDS.contains(&name))
+        }
+        Some(c) => c.is_alphabetic(),
+        None => false,
+    }
+}
+
+/// Returns `true` if a name set with the command `command`, such as `\operatorname{erf}` or
+/// `\mathrm{max}`, is the name of a function called with the arguments at the start of `input`.
+/// A name set with `\operatorname` always is, and an upright name set with `\mathrm` is when it is
+/// followed by parentheses, as in `\mathrm{max}(x)`.
+fn is_named_call(command: &str, input: &str) -> bool {
+    command == "operatorname"
+        || (command == "mathrm" && strip_left(skip_space(input)).0.starts_with('('))
+}
+
+/// Returns `true` if the input starts with a function written as a command, such as `\sin`,
+/// `\operatorname{erf}` or `\mathrm{max}(x)`.
+fn starts_function(input: &str) -> bool {
+    match parse_command(skip_space(input)) {
+        Ok((rest, name)) => {
+            LATEX_FUNCTIONS.iter().any(|(latex, _)| *latex == name)
+                || parse_text_argument(rest).is_ok_and(|(after, _)| is_named_call(name, after))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Parses a chain of binary operations using precedence climbing, like the parser of
+/// [`parse_expression`](crate::algebra::parser::parse_expression), except that the exponent of
+/// `^` is a single-token argument as in LaTeX.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with an expression.
+/// * `min_precedence` - The loosest operator precedence this call may consume.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_binary(input: &str, min_precedence: u8) -> ParseResult<'_, Expr> {
+    let (mut input, mut lhs) = parse_unary(input)?;
+    loop {
+        let (rest, (operator, symbol)) = match parse_operator(input) {
+            Ok(result) => result,
+            Err(nom::Err::Error(_)) if starts_implicit_operand(input) => {
+                (input, (Operator::Multiply, ""))
+            }
+            Err(nom::Err::Error(_)) => break,
+            Err(err) => return Err(err),
+        };
+        if operator.precedence() < min_precedence {
+            break;
+        }
+        let (rest, rhs) = if operator == Operator::Power {
+            expect_operand(parse_argument(rest), rest, symbol)?
+        } else if symbol.is_empty() {
+            // Implicit multiplication only starts when an operand follows, so no operator to report
+            parse_binary(rest, operator.precedence() + 1)?
+        } else {
+            expect_operand(parse_binary(rest, operator.precedence() + 1), rest, symbol)?
+        };
+        lhs = operator.apply(lhs, rhs);
+        input = rest;
+    }
+    Ok((input, lhs))
+}
+
+/// Parses an expression written in LaTeX math notation from the input string, such as
+/// `\frac{x^2+1}{\sqrt{x}}`, `\sin\left(\theta\right)`, `x_{i}` or `2\cdot y`, into the same
+/// expression tree as [`parse_expression`](crate::algebra::parser::parse_expression).
+///
+/// Fractions are written with `\frac`, roots with `\sqrt` and `\sqrt[n]`, products by
+/// juxtaposition or with `\cdot` and `\times`, and absolute values with vertical bars. Functions
+/// are written as commands such as `\sin`, `\ln` and `\log_2`, with `\operatorname`, or with
+/// `\mathrm` followed by parentheses, as in `\mathrm{max}(x)`. Every letter is a
+/// name of its own, so `xy` is a product, while `\alpha`, `\mathit{mass}` and subscripts such as
+/// `v_{\mathrm{max}}` give longer names. The input may be surrounded by `$`, `$$`, `\[` and `\]`
+/// or `\(` and `\)`, and spacing commands such as `\,` are ignored. The whole input must be consumed.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should contain an expression in LaTeX notation.
+///
+/// # Returns
+///
+/// * `Result<Expr, ParseError>` - On success, the function returns the parsed expression as a `Expr`.
+///
+/// # Errors
+///
+/// This function will return a [`ParseError`] locating the first problem in the input if it is not a valid expression.
+pub fn parse_latex(input: &str) -> Result<Expr, ParseError> {
+    let trimmed = skip_space(input);
+    let (body, closing) = MATH_DELIMITERS
+        .iter()
+        .find_map(|(opening, closing)| trimmed.strip_prefix(opening).map(|body| (body, *closing)))
+        .unwrap_or((input, ""));
+    match parse_binary(body, 0) {
+        Ok((rest, result)) => {
+            let rest = skip_space(rest);
+            match rest.strip_prefix(closing) {
+                Some(end) if skip_space(end).is_empty() => Ok(result),
+                _ => Err(SyntaxError {
+                    input: rest,
+                    kind: SyntaxErrorKind::UnexpectedInput,
+                }
+                .into_parse_error(input)),
+            }
+        }
+        Err(nom::Err::Error(_)) => Err(SyntaxError {
+            input: skip_space(body),
+            kind: SyntaxErrorKind::ExpectedOperand(None),
+        }
+        .into_parse_error(input)),
+        Err(nom::Err::Failure(err)) => Err(err.into_parse_error(input)),
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers never return Incomplete"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::expression::Expression;
+    use crate::algebra::parser::parse_expression;
+
+    use super::*;
+
+    fn assert_parses_as(latex: &str, text: &str) {
+        assert_eq!(
+            parse_latex(latex).unwrap(),
+            parse_expression(text).unwrap(),
+            "{}",
+            latex
+        );
+    }
+
+    #[test]
+    fn parse_latex_fractions_and_roots() {
+        assert_parses_as("\\frac{x^2+1}{\\sqrt{x}}", "(x^2 + 1)/sqrt(x)");
+        assert_parses_as("\\dfrac12 x", "(1/2) x");
+        assert_parses_as("\\sqrt[3]{x + 1}", "(x + 1)^(1/3)");
+        assert_parses_as("x^{-1} + x^2y", "x^(-1) + x^2 y");
+        assert_parses_as("e^{i\\pi}", "e^(i pi)");
+    }
+
+    #[test]
+    fn parse_latex_products() {
+        assert_parses_as("2\\cdot y", "2*y");
+        assert_parses_as("3 \\times 4 \\div 5", "3*4/5");
+        assert_parses_as("2xy", "2 x y");
+        assert_parses_as("2\\pi r", "2 pi r");
+        assert_parses_as("\\left(a+b\\right)\\left[a-b\\right]", "(a + b)(a - b)");
+    }
+
+    #[test]
+    fn parse_latex_names() {
+        assert_parses_as("x_{i}", "x_i");
+        assert_parses_as("x_1 + x_{12}", "x_1 + x_12");
+        assert_parses_as("v_{\\mathrm{max}}", "v_max");
+        assert_parses_as("\\alpha + \\Omega + \\varphi", "alpha + Omega + phi");
+        assert_parses_as("\\theta_0 f'", "theta_0 f'");
+        assert_parses_as("x^{2}_{1} + x_1^2", "x_1^2 + x_1^2");
+        assert_parses_as("v^2_{\\mathrm{max}} y^2", "v_max^2 y^2");
+        assert_parses_as("\\mathit{mass} \\, c^2", "mass c^2");
+        assert!(parse_latex("x_{i+1}").is_err());
+    }
+
+    #[test]
+    fn parse_latex_functions() {
+        assert_parses_as("\\sin\\left(\\theta\\right)", "sin(theta)");
+        assert_parses_as("\\sin^2 x + \\cos^{2}(x)", "sin(x)^2 + cos(x)^2");
+        assert_parses_as("\\ln x^2 \\arctan y", "ln(x^2) atan(y)");
+        assert_parses_as("\\sin 2x + \\cos x y", "sin(2x) + cos(x y)");
+        assert_parses_as("\\sin x \\cos x \\cdot 2", "sin(x) cos(x) * 2");
+        assert_parses_as(
+            "\\log_2 x + \\log_{b}^2(x)",
+            "ln(x)/ln(2) + (ln(x)/ln(b))^2",
+        );
+        assert_parses_as("\\log^2_b x", "(ln(x)/ln(b))^2");
+        assert_parses_as("\\mathrm{sin}(x) \\mathrm{max}", "sin(x) max");
+        assert_parses_as("\\left|x\\right| + |y|", "abs(x) + abs(y)");
+        assert_parses_as("\\overline{z} \\operatorname{sgn}(z)", "conj(z) sign(z)");
+        for (latex, name) in [
+            ("\\operatorname{erf}(x)", "erf"),
+            ("\\mathrm{max}(x)", "max"),
+        ] {
+            let call = parse_latex(latex).unwrap();
+            if let Expr::Function(function) = &call {
+                assert_eq!(function.name, name);
+            } else {
+                panic!("Expected Function, found {:?}", call);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_latex_math_delimiters() {
+        assert_parses_as("$x + 1$", "x + 1");
+        assert_parses_as("\\[ \\displaystyle \\frac{1}{2} \\]", "1/2");
+        assert!(parse_latex("$x + 1").is_err());
+    }
+
+    #[test]
+    fn parse_latex_round_trips_to_latex() {
+        let inputs = [
+            "(x^2 + 1)/sqrt(x)",
+            "x - 2y + 1/3",
+            "sin(theta)^2 + cos(theta)^2",
+            "e^(i pi) + (3 + 4i) z",
+            "x_1^(2/3) - abs(-v_max)",
+            "2 * 3 x / (y + 1)",
+        ];
+        for input in inputs {
+            let simplified = parse_expression(input).unwrap().simplify();
+            let latex = simplified.to_latex();
+            let parsed = parse_latex(&latex).unwrap();
+            assert_eq!(parsed.simplify(), simplified, "{}", latex);
+        }
+    }
+
+    #[test]
+    fn parse_latex_errors() {
+        let err = parse_latex("\\frac{1}").unwrap_err();
+        assert_eq!(err.message, "expected operand after '\\frac' at column 9");
+
+        let err = parse_latex("1 + \\infty").unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!(err.message, "unknown command '\\infty' at column 5");
+
+        let err = parse_latex("{x + 1").unwrap_err();
+        assert_eq!(
+            err.message,
+            "expected '}' at column 7 to close '{' at column 1"
+        );
+
+        let err = parse_latex("\\left(x").unwrap_err();
+        assert_eq!(
+            err.message,
+            "expected ')' at column 8 to close '(' at column 1"
+        );
+
+        let err = parse_latex("\\sin(x, y)").unwrap_err();
+        assert_eq!(
+            err.message,
+            "function 'sin' takes 1 argument but 2 were given at column 1"
+        );
+    }
+}
@@ -0,0 +1,577 @@
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+use crate::algebra::display::{needs_parentheses, negated, signed_factors, Layout, Position};
+use crate::algebra::expr::Expr;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::number::Number;
+use crate::algebra::power::root_index;
+use crate::algebra::variable::greek_letter_index;
+
+/// Namespace of the MathML elements.
+const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Invisible times, placed between juxtaposed factors so a product is read out as one.
+const INVISIBLE_TIMES: &str = "<mo>&#x2062;</mo>";
+
+/// Dot operator, placed between factors that would read as a single number when juxtaposed.
+const DOT_OPERATOR: &str = "<mo>&#x22C5;</mo>";
+
+/// Function application, placed between the name of a function and its arguments.
+const APPLY_FUNCTION: &str = "<mo>&#x2061;</mo>";
+
+impl Expr {
+    /// Returns the expression as presentation MathML, a `<math>` element for display in a
+    /// browser or by assistive technology.
+    ///
+    /// The layout follows the one of [`to_typist`](crate::algebra::expression::Expression::to_typist):
+    /// divisions are set as fractions, unit fraction powers as roots, negative terms as
+    /// subtractions, and operands are parenthesized only where precedence requires it.
+    /// Custom nodes are set as text in their Typst form.
+    pub fn to_presentation_mathml(&self) -> String {
+        format!(
+            "<math xmlns=\"{}\">{}</math>",
+            MATHML_NAMESPACE,
+            presentation(self)
+        )
+    }
+
+    /// Returns the expression as content MathML, a `<math>` element describing its meaning for
+    /// exchanging it with other computer algebra systems.
+    ///
+    /// Every node becomes an `<apply>` of the matching MathML operator, keeping the shape of the
+    /// tree, so a subtraction is a sum with a `<minus/>` term. Exact numbers stay exact, as
+    /// integers, rationals or complex numbers. Functions without a MathML operator are applied
+    /// as `<ci type="function">`, and custom nodes, which have no meaning known to the crate,
+    /// are written as a `<cerror>`.
+    pub fn to_content_mathml(&self) -> String {
+        format!(
+            "<math xmlns=\"{}\">{}</math>",
+            MATHML_NAMESPACE,
+            content(self)
+        )
+    }
+}
+
+/// Escapes the characters of `text` that have a meaning in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns the presentation of `expr`, in parentheses if it needs them at `position`.
+fn operand(expr: &Expr, position: Position) -> String {
+    if needs_parentheses(expr, position, Layout::Typeset) {
+        format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", presentation(expr))
+    } else {
+        presentation(expr)
+    }
+}
+
+/// Returns `true` if the presentation of `expr` starts with a number, which would run into a
+/// number written right before it.
+fn leads_with_number(expr: &Expr) -> bool {
+    match expr {
+        Expr::Constant(c) if c.value.is_real() => !c.value.is_negative(),
+        Expr::Constant(c) => !c.value.im().is_one(),
+        Expr::Power(power) if root_index(&power.exponent).is_none() => matches!(
+            &power.base,
+            Expr::Constant(c) if c.value.is_real() && c.value.is_integer() && !c.value.is_negative()
+        ),
+        Expr::Multiply(multiply) => multiply.ops.first().is_some_and(leads_with_number),
+        _ => false,
+    }
+}
+
+/// Returns `true` if `expr` is set as a fraction.
+fn is_fraction(expr: &Expr) -> bool {
+    match expr {
+        Expr::Divide(_) => true,
+        Expr::Constant(c) => c.value.is_real() && c.value.is_exact() && !c.value.is_integer(),
+        _ => false,
+    }
+}
+
+/// Returns the presentation of a real number, with an exact non-integer written as a fraction.
+fn presentation_real(value: &Number) -> String {
+    let magnitude = match value {
+        Number::Exact(fraction) if !fraction.is_integer() => format!(
+            "<mfrac><mn>{}</mn><mn>{}</mn></mfrac>",
+            fraction.numer().abs(),
+            fraction.denom()
+        ),
+        _ if value.is_negative() => format!("<mn>{}</mn>", -value),
+        _ => format!("<mn>{}</mn>", value),
+    };
+    if value.is_negative() {
+        format!("<mrow><mo>-</mo>{}</mrow>", magnitude)
+    } else {
+        magnitude
+    }
+}
+
+/// Returns the presentation of a number, with imaginary parts followed by `i`.
+fn presentation_number(value: &Number) -> String {
+    if value.is_real() {
+        return presentation_real(value);
+    }
+    let (re, im) = (value.re(), value.im());
+    let magnitude = if im.is_negative() { -&im } else { im.clone() };
+    let imaginary = if magnitude.is_one() {
+        "<mi>i</mi>".to_string()
+    } else {
+        format!(
+            "<mrow>{}{}<mi>i</mi></mrow>",
+            presentation_real(&magnitude),
+            INVISIBLE_TIMES
+        )
+    };
+    match (re.is_zero(), im.is_negative()) {
+        (true, false) => imaginary,
+        (true, true) => format!("<mrow><mo>-</mo>{}</mrow>", imaginary),
+        (false, false) => format!(
+            "<mrow>{}<mo>+</mo>{}</mrow>",
+            presentation_real(&re),
+            imaginary
+        ),
+        (false, true) => format!(
+            "<mrow>{}<mo>-</mo>{}</mrow>",
+            presentation_real(&re),
+            imaginary
+        ),
+    }
+}
+
+/// Returns the Greek letter named `name`, such as `α` for `alpha` or `Ω` for `Omega`.
+fn greek_letter(name: &str) -> Option<char> {
+    let (index, is_capital) = greek_letter_index(name)?;
+    // The letters after rho are one code point further, past the final sigma
+    let index = if index > 16 { index + 1 } else { index } as u32;
+    let first = if is_capital { 'Α' } else { 'α' };
+    char::from_u32(first as u32 + index)
+}
+
+/// Returns the presentation of a segment of a variable name. Greek letter names become the
+/// letter, numbers are set as numbers, and a longer name is set in italics as the base of the
+/// name or upright as a descriptive subscript such as `max`.
+fn name_segment(segment: &str, is_base: bool) -> String {
+    if let Some(letter) = greek_letter(segment) {
+        format!("<mi>{}</mi>", letter)
+    } else if segment.chars().all(|c| c.is_ascii_digit()) {
+        format!("<mn>{}</mn>", segment)
+    } else if is_base && segment.chars().count() > 1 {
+        format!("<mi mathvariant=\"italic\">{}</mi>", escape(segment))
+    } else {
+        format!("<mi>{}</mi>", escape(segment))
+    }
+}
+
+/// Returns the name of a function as it is set, such as `arcsin` for `asin`.
+fn function_name(name: &str) -> &str {
+    match name {
+        "asin" => "arcsin",
+        "acos" => "arccos",
+        "atan" => "arctan",
+        "sign" => "sgn",
+        "re" => "Re",
+        "im" => "Im",
+        name => name,
+    }
+}
+
+/// Returns the presentation MathML of `expr`, as a single element.
+fn presentation(expr: &Expr) -> String {
+    match expr {
+        Expr::Constant(constant) => presentation_number(&constant.value),
+        Expr::NamedConstant(named) => {
+            let letter = match named {
+                NamedConstant::Pi => "π",
+                NamedConstant::E => "e",
+                NamedConstant::Phi => "φ",
+                NamedConstant::EulerGamma => "γ",
+            };
+            format!("<mi>{}</mi>", letter)
+        }
+        Expr::Variable(variable) => {
+            let (base, subscript, primes) = variable.parts();
+            let base = name_segment(base, true);
+            let primes =
+                (!primes.is_empty()).then(|| format!("<mo>{}</mo>", "′".repeat(primes.len())));
+            match (subscript, primes) {
+                (Some(subscript), Some(primes)) => format!(
+                    "<msubsup>{}{}{}</msubsup>",
+                    base,
+                    name_segment(subscript, false),
+                    primes
+                ),
+                (Some(subscript), None) => {
+                    format!("<msub>{}{}</msub>", base, name_segment(subscript, false))
+                }
+                (None, Some(primes)) => format!("<msup>{}{}</msup>", base, primes),
+                (None, None) => base,
+            }
+        }
+        Expr::Add(add) if add.ops.is_empty() => "<mn>0</mn>".to_string(),
+        Expr::Add(add) => {
+            let mut output = presentation(&add.ops[0]);
+            for op in &add.ops[1..] {
+                match negated(op) {
+                    Some(positive) => {
+                        output.push_str("<mo>-</mo>");
+                        output.push_str(&operand(&positive, Position::Subtrahend));
+                    }
+                    None => {
+                        output.push_str("<mo>+</mo>");
+                        output.push_str(&operand(op, Position::Addend));
+                    }
+                }
+            }
+            format!("<mrow>{}</mrow>", output)
+        }
+        Expr::Multiply(multiply) if multiply.ops.is_empty() => "<mn>1</mn>".to_string(),
+        Expr::Multiply(multiply) => {
+            let (signed, ops) = signed_factors(&multiply.ops);
+            let mut output = if signed {
+                "<mo>-</mo>".to_string()
+            } else {
+                String::new()
+            };
+            for (index, op) in ops.iter().enumerate() {
+                let position = if index == 0 && !signed {
+                    Position::FirstFactor
+                } else {
+                    Position::Factor
+                };
+                let parenthesize = needs_parentheses(op, position, Layout::Typeset);
+                if index > 0 {
+                    let previous = &ops[index - 1];
+                    let previous_is_number = matches!(
+                        previous,
+                        Expr::Constant(c) if c.value.is_real() && !c.value.is_negative()
+                    );
+                    if !parenthesize
+                        && (leads_with_number(op) || (previous_is_number && is_fraction(op)))
+                    {
+                        output.push_str(DOT_OPERATOR);
+                    } else {
+                        output.push_str(INVISIBLE_TIMES);
+                    }
+                }
+                output.push_str(&operand(op, position));
+            }
+            format!("<mrow>{}</mrow>", output)
+        }
+        Expr::Divide(divide) => match negated(&divide.numerator) {
+            // A leading minus of the numerator is set before the fraction
+            Some(numerator) => format!(
+                "<mrow><mo>-</mo><mfrac>{}{}</mfrac></mrow>",
+                presentation(&numerator),
+                presentation(&divide.denominator)
+            ),
+            None => format!(
+                "<mfrac>{}{}</mfrac>",
+                presentation(&divide.numerator),
+                presentation(&divide.denominator)
+            ),
+        },
+        Expr::Power(power) => {
+            if let Some(index) = root_index(&power.exponent) {
+                return if index == BigInt::from(2) {
+                    format!("<msqrt>{}</msqrt>", presentation(&power.base))
+                } else {
+                    format!(
+                        "<mroot>{}<mn>{}</mn></mroot>",
+                        presentation(&power.base),
+                        index
+                    )
+                };
+            }
+            format!(
+                "<msup>{}{}</msup>",
+                operand(&power.base, Position::Base),
+                presentation(&power.exponent)
+            )
+        }
+        Expr::Negate(negate) => format!(
+            "<mrow><mo>-</mo>{}</mrow>",
+            operand(&negate.op, Position::Negated)
+        ),
+        Expr::Function(function) => {
+            let args: Vec<String> = function.args.iter().map(presentation).collect();
+            let args = args.join("<mo>,</mo>");
+            match function.name.as_str() {
+                "sqrt" => format!("<msqrt>{}</msqrt>", args),
+                "abs" => format!("<mrow><mo>|</mo>{}<mo>|</mo></mrow>", args),
+                "conj" => format!(
+                    "<mover accent=\"true\"><mrow>{}</mrow><mo>&#x00AF;</mo></mover>",
+                    args
+                ),
+                name => format!(
+                    "<mrow><mi>{}</mi>{}<mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+                    escape(function_name(name)),
+                    APPLY_FUNCTION,
+                    args
+                ),
+            }
+        }
+        Expr::Custom(custom) => format!("<mtext>{}</mtext>", escape(&custom.to_typist())),
+    }
+}
+
+/// Returns the content MathML of a number.
+fn content_number(value: &Number) -> String {
+    match value {
+        Number::Exact(fraction) if fraction.is_integer() => {
+            format!("<cn type=\"integer\">{}</cn>", fraction)
+        }
+        Number::Exact(fraction) => format!(
+            "<cn type=\"rational\">{}<sep/>{}</cn>",
+            fraction.numer(),
+            fraction.denom()
+        ),
+        Number::Float(value) => format!("<cn type=\"real\">{}</cn>", value),
+        _ => {
+            let (re, im) = (value.re(), value.im());
+            if !value.is_exact() || (re.is_integer() && im.is_integer()) {
+                format!("<cn type=\"complex-cartesian\">{}<sep/>{}</cn>", re, im)
+            } else {
+                // The parts of a complex-cartesian number cannot be fractions
+                format!(
+                    "<apply><plus/>{}<apply><times/>{}<imaginaryi/></apply></apply>",
+                    content_number(&re),
+                    content_number(&im)
+                )
+            }
+        }
+    }
+}
+
+/// Returns the content MathML of `operator` applied to `operands`.
+fn apply<'a>(operator: &str, operands: impl IntoIterator<Item = &'a Expr>) -> String {
+    let operands: Vec<String> = operands.into_iter().map(content).collect();
+    format!("<apply>{}{}</apply>", operator, operands.concat())
+}
+
+/// Returns the content MathML operator of the function `name`, if MathML has one.
+fn content_function(name: &str) -> Option<&str> {
+    Some(match name {
+        "sin" => "<sin/>",
+        "cos" => "<cos/>",
+        "tan" => "<tan/>",
+        "asin" => "<arcsin/>",
+        "acos" => "<arccos/>",
+        "atan" => "<arctan/>",
+        "sinh" => "<sinh/>",
+        "cosh" => "<cosh/>",
+        "tanh" => "<tanh/>",
+        "exp" => "<exp/>",
+        "ln" => "<ln/>",
+        // The logarithm without a `<logbase>` is the base 10 logarithm, as `log` is
+        "log" => "<log/>",
+        "sqrt" => "<root/>",
+        "abs" => "<abs/>",
+        "conj" => "<conjugate/>",
+        "re" => "<real/>",
+        "im" => "<imaginary/>",
+        "arg" => "<arg/>",
+        _ => return None,
+    })
+}
+
+/// Returns the content MathML of `expr`, as a single element.
+fn content(expr: &Expr) -> String {
+    match expr {
+        Expr::Constant(constant) => content_number(&constant.value),
+        Expr::NamedConstant(named) => match named {
+            NamedConstant::Pi => "<pi/>".to_string(),
+            NamedConstant::E => "<exponentiale/>".to_string(),
+            NamedConstant::EulerGamma => "<eulergamma/>".to_string(),
+            // MathML has no golden ratio, so it is written as (1 + sqrt(5))/2
+            NamedConstant::Phi => "<apply><divide/><apply><plus/><cn type=\"integer\">1</cn>\
+                <apply><root/><cn type=\"integer\">5</cn></apply></apply>\
+                <cn type=\"integer\">2</cn></apply>"
+                .to_string(),
+        },
+        Expr::Variable(variable) => format!("<ci>{}</ci>", escape(&variable.name)),
+        Expr::Add(add) => apply("<plus/>", &add.ops),
+        Expr::Multiply(multiply) => apply("<times/>", &multiply.ops),
+        Expr::Divide(divide) => apply("<divide/>", [&divide.numerator, &divide.denominator]),
+        Expr::Power(power) => apply("<power/>", [&power.base, &power.exponent]),
+        Expr::Negate(negate) => apply("<minus/>", [&negate.op]),
+        Expr::Function(function) => match function.name.as_str() {
+            "sign" => apply("<csymbol>sign</csymbol>", &function.args),
+            name => match content_function(name) {
+                Some(operator) => apply(operator, &function.args),
+                None => apply(
+                    &format!("<ci type=\"function\">{}</ci>", escape(name)),
+                    &function.args,
+                ),
+            },
+        },
+        Expr::Custom(custom) => format!(
+            "<cerror><csymbol>unsupported</csymbol><cs>{}</cs></cerror>",
+            escape(&custom.to_typist())
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::constant::Constant;
+    use crate::algebra::expression::Expression;
+    use crate::algebra::function::Function;
+    use crate::algebra::parser::parse_expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    fn presentation_of(input: &str) -> String {
+        presentation(&parse_expression(input).unwrap())
+    }
+
+    fn content_of(input: &str) -> String {
+        content(&parse_expression(input).unwrap())
+    }
+
+    /// Asserts that every element of `xml` is closed in the order it was opened.
+    fn assert_well_formed(xml: &str) {
+        let mut open = Vec::new();
+        for tag in xml.split('<').skip(1) {
+            let tag = &tag[..tag.find('>').unwrap()];
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name), "{}", xml);
+            } else if !tag.ends_with('/') {
+                open.push(tag.split(' ').next().unwrap());
+            }
+        }
+        assert!(open.is_empty(), "{}", xml);
+    }
+
+    #[test]
+    fn mathml_presentation_of_operators() {
+        assert_eq!(
+            presentation_of("x^2 + 1/2"),
+            "<mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo>\
+             <mfrac><mn>1</mn><mn>2</mn></mfrac></mrow>"
+        );
+        assert_eq!(
+            presentation_of("a - (b + c)"),
+            "<mrow><mi>a</mi><mo>-</mo><mrow><mo>(</mo>\
+             <mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow><mo>)</mo></mrow></mrow>"
+        );
+        assert_eq!(
+            presentation_of("2*3x"),
+            "<mrow><mrow><mn>2</mn><mo>&#x22C5;</mo><mn>3</mn></mrow>\
+             <mo>&#x2062;</mo><mi>x</mi></mrow>"
+        );
+        assert_eq!(
+            presentation_of("(-x)^2"),
+            "<msup><mrow><mo>(</mo><mrow><mo>-</mo><mi>x</mi></mrow><mo>)</mo></mrow>\
+             <mn>2</mn></msup>"
+        );
+        assert_eq!(
+            presentation_of("sqrt(x) + x^(1/3)"),
+            "<mrow><msqrt><mi>x</mi></msqrt><mo>+</mo><mroot><mi>x</mi><mn>3</mn></mroot></mrow>"
+        );
+    }
+
+    #[test]
+    fn mathml_presentation_of_names() {
+        assert_eq!(presentation_of("theta"), "<mi>θ</mi>");
+        assert_eq!(presentation_of("Omega"), "<mi>Ω</mi>");
+        assert_eq!(presentation_of("omega"), "<mi>ω</mi>");
+        // A name that only looks like a Greek letter, here with a Kelvin sign, is set as it is
+        assert_eq!(
+            presentation_of("\u{212A}appa"),
+            "<mi mathvariant=\"italic\">\u{212A}appa</mi>"
+        );
+        assert_eq!(
+            presentation_of("mass"),
+            "<mi mathvariant=\"italic\">mass</mi>"
+        );
+        assert_eq!(
+            presentation_of("v_max"),
+            "<msub><mi>v</mi><mi>max</mi></msub>"
+        );
+        assert_eq!(
+            presentation_of("x_1'"),
+            "<msubsup><mi>x</mi><mn>1</mn><mo>′</mo></msubsup>"
+        );
+        assert_eq!(presentation_of("pi"), "<mi>π</mi>");
+        assert_eq!(
+            presentation_of("asin(x)"),
+            "<mrow><mi>arcsin</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>"
+        );
+        assert_eq!(
+            presentation_of("abs(x)"),
+            "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>"
+        );
+    }
+
+    #[test]
+    fn mathml_content_of_operators() {
+        assert_eq!(
+            content_of("2x - 1/3"),
+            "<apply><plus/><apply><times/><cn type=\"integer\">2</cn><ci>x</ci></apply>\
+             <apply><minus/><apply><divide/><cn type=\"integer\">1</cn>\
+             <cn type=\"integer\">3</cn></apply></apply></apply>"
+        );
+        assert_eq!(
+            content_of("sin(x)^2"),
+            "<apply><power/><apply><sin/><ci>x</ci></apply><cn type=\"integer\">2</cn></apply>"
+        );
+        assert_eq!(
+            content_of("e^(i pi)"),
+            "<apply><power/><exponentiale/><apply><times/>\
+             <cn type=\"complex-cartesian\">0<sep/>1</cn><pi/></apply></apply>"
+        );
+        let erf = Expr::from(Function::new("erf", vec![Expr::from(Variable::new("x"))]));
+        assert_eq!(
+            content(&erf),
+            "<apply><ci type=\"function\">erf</ci><ci>x</ci></apply>"
+        );
+    }
+
+    #[test]
+    fn mathml_content_keeps_numbers_exact() {
+        let simplified = |input: &str| content(&parse_expression(input).unwrap().simplify());
+        assert_eq!(simplified("2/6"), "<cn type=\"rational\">1<sep/>3</cn>");
+        assert_eq!(
+            simplified("3 - 4i"),
+            "<cn type=\"complex-cartesian\">3<sep/>-4</cn>"
+        );
+        assert_eq!(
+            simplified("1/2 + i/3"),
+            "<apply><plus/><cn type=\"rational\">1<sep/>2</cn><apply><times/>\
+             <cn type=\"rational\">1<sep/>3</cn><imaginaryi/></apply></apply>"
+        );
+        assert_eq!(
+            content(&Expr::from(Constant::new(2.5))),
+            "<cn type=\"real\">2.5</cn>"
+        );
+    }
+
+    #[test]
+    fn mathml_documents_are_well_formed() {
+        let inputs = [
+            "(x^2 + 1)/sqrt(x)",
+            "-(a + b)(a - b) - -3/4 x^-2",
+            "sin(theta_0)^2 + cos(theta_0)^2",
+            "e^(i pi) + (3 + 4i) z'",
+            "conj(z) sign(z) re(z) im(z) arg(z)",
+            "log(abs(-2x)) + exp(ln(phi))",
+        ];
+        for input in inputs {
+            let expr = parse_expression(input).unwrap();
+            for expr in [expr.clone(), expr.simplify()] {
+                let presentation = expr.to_presentation_mathml();
+                assert!(
+                    presentation.starts_with("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">")
+                );
+                assert_well_formed(&presentation);
+                assert_well_formed(&expr.to_content_mathml());
+            }
+        }
+    }
+}
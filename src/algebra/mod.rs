@@ -1,6 +1,25 @@
 pub mod add;
+pub mod bigfloat;
 pub mod constant;
+pub mod display;
+pub mod divide;
+pub mod eval;
+pub mod expr;
 pub mod expression;
+pub mod function;
+pub mod interner;
+pub mod interval;
+pub mod latex_parser;
+pub mod mathml;
 pub mod multiply;
+pub mod named_constant;
+pub mod negate;
+pub mod number;
+pub mod operators;
+pub mod parse_error;
 pub mod parser;
+pub mod power;
+#[cfg(feature = "serde")]
+pub mod schema;
 pub mod variable;
+pub mod visit;
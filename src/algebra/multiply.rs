@@ -1,28 +1,75 @@
 use crate::algebra::add::Add;
 use std::any::Any;
+use std::collections::BTreeMap;
 
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
 use crate::algebra::constant::Constant;
+use crate::algebra::display::{latex_operand, signed_factors, typst_operand, Position};
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
 use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::number::Number;
+use crate::algebra::power::Power;
+
+/// A base paired with the exponent it is raised to.
+type Factor = (Expr, Expr);
 
 /// `Multiply` struct represents a multiplication operation in an expression tree.
-/// It contains a vector of `Expression` trait objects, which can be any type that implements the `Expression` trait.
+/// It contains a vector of `Expr` operands, which can be any node of an expression tree.
 pub struct Multiply {
-    pub ops: Vec<Box<dyn Expression>>,
+    pub ops: Vec<Expr>,
 }
 
 impl Multiply {
     /// Constructs a new `Multiply` instance.
-    pub fn new(ops: Vec<Box<dyn Expression>>) -> Self {
+    pub fn new(ops: Vec<Expr>) -> Self {
         Self { ops }
     }
 
+    /// Writes the factors of the product with `operand`, juxtaposed except that `dot` is
+    /// written before a number and between a number and a fraction starting with `fraction`.
+    /// This is shared by the Typst and the LaTeX output, which only differ in their notation.
+    fn typeset(&self, operand: fn(&Expr, Position) -> String, fraction: &str, dot: &str) -> String {
+        let (signed, ops) = signed_factors(&self.ops);
+        let mut output = if signed {
+            "-".to_string()
+        } else {
+            String::new()
+        };
+        let starts_with_number = |part: &str| part.starts_with(|c: char| c.is_ascii_digit());
+        let mut previous: Option<String> = None;
+        for (index, op) in ops.iter().enumerate() {
+            let position = if index == 0 && !signed {
+                Position::FirstFactor
+            } else {
+                Position::Factor
+            };
+            let part = operand(op, position);
+            if let Some(previous) = &previous {
+                if starts_with_number(&part)
+                    || (starts_with_number(previous) && part.starts_with(fraction))
+                {
+                    output.push_str(dot);
+                } else {
+                    output.push(' ');
+                }
+            }
+            output.push_str(&part);
+            previous = Some(part);
+        }
+        output
+    }
+
     /// Flattens nested `Multiply` expressions into a single-level `Multiply` expression.
     /// This is a helper method used in the `simplify` method.
-    fn flatten(&self) -> Vec<Box<dyn Expression>> {
+    fn flatten(&self) -> Vec<Expr> {
         let mut flattened_ops = Vec::new();
         for op in &self.ops {
-            if let Some(mul) = op.as_any().downcast_ref::<Multiply>() {
-                let more_flattened_ops = mul.flatten();
+            if let Expr::Multiply(multiply) = op {
+                let more_flattened_ops = multiply.flatten();
                 flattened_ops.extend(more_flattened_ops);
             } else {
                 flattened_ops.push(op.clone());
@@ -30,75 +77,129 @@ impl Multiply {
         }
         flattened_ops
     }
+
+    /// Splits a simplified factor into its base and exponent, so `x^2` becomes `(x, 2)`.
+    /// A factor that is not a `Power` has an exponent of 1.
+    fn split_exponent(factor: Expr) -> Factor {
+        match factor {
+            Expr::Power(power) => (power.base.clone(), power.exponent.clone()),
+            factor => (factor, Expr::from(Constant::new(1))),
+        }
+    }
+
+    /// Adds two exponents of the same base, exactly when both are numeric and symbolically otherwise.
+    fn add_exponents(lhs: Expr, rhs: Expr) -> Expr {
+        match (Constant::value_of(&lhs), Constant::value_of(&rhs)) {
+            (Some(lhs), Some(rhs)) => Expr::from(Constant::new(lhs + rhs)),
+            _ => Add::new(vec![lhs, rhs]).simplify(),
+        }
+    }
 }
 
 impl Expression for Multiply {
-    fn eval(&self) -> Box<dyn Expression> {
-        // Simplify
-        // Eval all children
-        // Multiply all children
-        todo!("Implement eval for Multiply")
+    /// Evaluates the expression to a number.
+    /// For a `Multiply`, it returns the product of its evaluated operands.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        self.ops.iter().map(|op| op.eval(env)).product()
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// For a `Multiply`, it returns the product of its evaluated operands.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        self.ops.iter().map(|op| op.eval_complex(env)).product()
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// For a `Multiply`, it returns the product of its evaluated operands.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        self.ops.iter().try_fold(BigFloat::one(), |product, op| {
+            Ok(product.mul(&op.eval_big(env, precision)?, precision))
+        })
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// For a `Multiply`, it returns the product of its evaluated operands.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        self.ops
+            .iter()
+            .try_fold(Interval::point(1.0), |product, op| {
+                Ok(product.mul(&op.eval_interval(env)?))
+            })
     }
 
     /// Simplifies the expression and returns a new simplified expression.
-    /// This method implements several algebraic simplification rules, such as eliminating multiplication by 1,
-    /// evaluating constant multiplication, and others.
-    fn simplify(&self) -> Box<dyn Expression> {
-        // flatten nested multiply expressions
-        let flattened_ops = self.flatten();
-
-        // Handle 0
-        if flattened_ops.iter().any(|op| {
-            if let Some(op) = op.as_any().downcast_ref::<Constant>() {
-                op.value == 0.0
+    /// This method flattens nested multiplications, merges the numeric factors into a single
+    /// leading coefficient and collects repeated bases into powers by adding their exponents,
+    /// so `2 * x * 3 * x` becomes `6 x^2` and `x * x^-1` becomes `1`. Powers of a constant base
+    /// whose exponents add up to a number are evaluated into the coefficient, so
+    /// `3 * 2^(1/2) * 2^(1/2)` becomes `6`. The factors are sorted in the
+    /// canonical order of their bases, so `y * x` and `x * y` give the same tree. A product
+    /// containing zero is zero, and a single remaining factor is returned on its own.
+    fn simplify(&self) -> Expr {
+        // Simplify all operands, flattening any Multiply produced by simplification
+        let mut factors: Vec<Expr> = Vec::new();
+        for op in self.flatten() {
+            let op = op.simplify();
+            match op {
+                Expr::Multiply(multiply) => factors.extend(multiply.ops.iter().cloned()),
+                op => factors.push(op),
+            }
+        }
+
+        // Multiply the numeric factors and group the others by their base
+        let mut coefficient = Number::from(1);
+        let mut groups: BTreeMap<Expr, Expr> = BTreeMap::new();
+        for factor in factors {
+            if let Some(value) = Constant::value_of(&factor) {
+                coefficient = &coefficient * value;
+                continue;
+            }
+            let (base, exponent) = Multiply::split_exponent(factor);
+            if let Some(sum) = groups.get_mut(&base) {
+                *sum = Multiply::add_exponents(sum.clone(), exponent);
             } else {
-                false
+                groups.insert(base, exponent);
+            }
+        }
+        // A constant base whose exponents add up to a constant, as in `2^(1/2) 2^(1/2)`, is
+        // folded into the coefficient when the power has an exact or floating point value
+        groups.retain(|base, exponent| {
+            match (Constant::value_of(base), Constant::value_of(exponent)) {
+                (Some(base), Some(exponent)) => match base.pow(exponent) {
+                    Some(power) => {
+                        coefficient = &coefficient * &power;
+                        false
+                    }
+                    None => true,
+                },
+                _ => true,
             }
-        }) {
-            return Box::new(Constant::new(0.0));
+        });
+        if coefficient.is_zero() {
+            return Expr::from(Constant::new(coefficient));
         }
 
-        // Filter out multiplying by 1 and simplify all operands
-        let ops: Vec<Box<dyn Expression>> = flattened_ops
-            .iter()
-            .map(|op| op.simplify())
-            .filter(|op| {
-                if let Some(op) = op.as_any().downcast_ref::<Constant>() {
-                    op.value != 1.0
+        // Rebuild the factors in canonical order, dropping bases whose exponents cancelled out
+        let mut ops: Vec<Expr> = groups
+            .into_iter()
+            .filter(|(_, exponent)| !Constant::value_of(exponent).is_some_and(Number::is_zero))
+            .map(|(base, exponent)| -> Expr {
+                if Constant::value_of(&exponent).is_some_and(Number::is_one) {
+                    base
                 } else {
-                    true
+                    Expr::from(Power::new(base, exponent))
                 }
             })
             .collect();
-
-        // TODO: create identity trait and implement to combine like terms and calculate product of constants
-        // Combine Like Terms
-        // Evaluate Constant Multiplication
-        if ops
-            .iter()
-            .all(|op| op.as_any().downcast_ref::<Constant>().is_some())
-        {
-            let mut product = 1.0;
-            for op in ops.iter() {
-                if let Some(op) = op.as_any().downcast_ref::<Constant>() {
-                    product *= op.value;
-                }
-            }
-            return Box::new(Constant::new(product));
+        if !coefficient.is_one() {
+            ops.insert(0, Expr::from(Constant::new(coefficient)));
         }
 
-        // Multiplication of Inverses
-        // Sort and Group Operands
-        // Distribute Multiplication over Addition
-        // Simplify Multiplication with Variables and Coefficients
-        // Eliminate Unit Coefficients
-        // Use Algebraic Identities
-        // Simplify Products Involving Exponents
-        // Consider Special Cases and Simplifications
-        // Simplify and Reduce Expression
-        // Return Simplified Expression
-
-        Box::new(Self { ops })
+        match ops.len() {
+            0 => Expr::from(Constant::new(1)),
+            1 => ops.pop().unwrap(),
+            _ => Expr::from(Self { ops }),
+        }
     }
 
     /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
@@ -117,20 +218,19 @@ impl Expression for Multiply {
     }
 
     /// Returns a Typist string for the expression.
+    /// Factors are juxtaposed, as in `2 x y`, except that `dot` is written before a number and
+    /// between a number and a fraction, so `2 dot 3` is not read as `23` nor `2 frac(1, 3)` as a
+    /// mixed number. A coefficient of -1 is written as a minus sign, and sums and factors with a
+    /// leading minus are wrapped in parentheses.
     fn to_typist(&self) -> String {
-        let mut parts: Vec<String> = Vec::new();
-        for op in &self.ops {
-            // Use parentheses for nested expressions for clarity
-            let part = op.to_typist();
-            if op.as_any().downcast_ref::<Multiply>().is_some()
-                || op.as_any().downcast_ref::<Add>().is_some()
-            {
-                parts.push(format!("({})", part));
-            } else {
-                parts.push(part);
-            }
-        }
-        parts.join(" ") // Join with multiplication symbol; adjust based on Typist conventions if necessary
+        self.typeset(typst_operand, "frac", " dot ")
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Multiply`, factors are juxtaposed, with `\cdot` between two numbers and
+    /// parentheses around sums and negative factors.
+    fn to_latex(&self) -> String {
+        self.typeset(latex_operand, "\\frac", " \\cdot ")
     }
 }
 
@@ -146,18 +246,20 @@ impl Clone for Multiply {
 mod tests {
     use crate::algebra::add::Add;
     use crate::algebra::constant::Constant;
+    use crate::algebra::function::Function;
+    use crate::algebra::variable::Variable;
 
     use super::*;
 
     #[test]
     fn multiply_simplify_with_zero_and_one() {
         let multiply = Multiply::new(vec![
-            Box::new(Constant::new(1.0)),
-            Box::new(Constant::new(0.0)),
-            Box::new(Constant::new(2.0)),
+            Expr::from(Constant::new(1.0)),
+            Expr::from(Constant::new(0.0)),
+            Expr::from(Constant::new(2.0)),
         ]);
         let simplified = multiply.simplify();
-        if let Some(constant) = simplified.as_any().downcast_ref::<Constant>() {
+        if let Expr::Constant(constant) = &simplified {
             assert_eq!(constant.value, 0.0);
         } else {
             panic!("Expected Constant");
@@ -167,12 +269,12 @@ mod tests {
     #[test]
     fn multiply_simplify_with_no_zero_or_one() {
         let multiply = Multiply::new(vec![
-            Box::new(Constant::new(2.0)),
-            Box::new(Constant::new(3.0)),
-            Box::new(Constant::new(4.0)),
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Constant::new(3.0)),
+            Expr::from(Constant::new(4.0)),
         ]);
         let simplified = multiply.simplify();
-        if let Some(constant) = simplified.as_any().downcast_ref::<Constant>() {
+        if let Expr::Constant(constant) = &simplified {
             assert_eq!(constant.value, 24.0);
         } else {
             panic!("Expected Constant");
@@ -182,15 +284,15 @@ mod tests {
     #[test]
     fn multiply_simplify_with_nested_multiply() {
         let nested_multiply = Multiply::new(vec![
-            Box::new(Constant::new(2.0)),
-            Box::new(Constant::new(3.0)),
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Constant::new(3.0)),
         ]);
         let multiply = Multiply::new(vec![
-            Box::new(Constant::new(4.0)),
-            Box::new(nested_multiply),
+            Expr::from(Constant::new(4.0)),
+            Expr::from(nested_multiply),
         ]);
         let simplified = multiply.simplify();
-        if let Some(constant) = simplified.as_any().downcast_ref::<Constant>() {
+        if let Expr::Constant(constant) = &simplified {
             assert_eq!(constant.value, 24.0);
         } else {
             panic!("Expected Constant");
@@ -200,25 +302,150 @@ mod tests {
     #[test]
     fn multiply_simplify_with_nested_add() {
         let nested_add = Add::new(vec![
-            Box::new(Constant::new(2.0)),
-            Box::new(Constant::new(3.0)),
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Constant::new(3.0)),
         ]);
-        let multiply = Multiply::new(vec![Box::new(Constant::new(4.0)), Box::new(nested_add)]);
+        let multiply = Multiply::new(vec![Expr::from(Constant::new(4.0)), Expr::from(nested_add)]);
         let simplified = multiply.simplify();
-        if let Some(simplified_multiply) = simplified.as_any().downcast_ref::<Multiply>() {
-            assert_eq!(
-                simplified_multiply
-                    .ops
-                    .first() // TODO: This is picking the multiply constant, need to fix to combine
-                    .unwrap()
-                    .as_any()
-                    .downcast_ref::<Constant>()
-                    .unwrap()
-                    .value,
-                4.0
-            )
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 20.0);
         } else {
             panic!("Expected Constant, found {:?}", simplified);
         }
     }
+
+    #[test]
+    fn multiply_eval_with_environment() {
+        let multiply = Multiply::new(vec![
+            Expr::from(Constant::new(3.0)),
+            Expr::from(Add::new(vec![
+                Expr::from(Variable::new("x")),
+                Expr::from(Constant::new(1.0)),
+            ])),
+        ]);
+        let env = Environment::new().with("x", 4.0);
+        assert_eq!(multiply.eval(&env), Ok(15.0));
+    }
+
+    #[test]
+    fn multiply_simplify_merges_coefficients() {
+        // 2 * x * 3 * x
+        let multiply = Multiply::new(vec![
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(3.0)),
+            Expr::from(Variable::new("x")),
+        ]);
+        let simplified = multiply.simplify();
+        if let Expr::Multiply(multiply) = &simplified {
+            assert_eq!(multiply.ops.len(), 2);
+            assert_eq!(multiply.to_typist(), "6 x^2");
+        } else {
+            panic!("Expected Multiply, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn multiply_simplify_collects_exponents() {
+        let power = |exponent: f64| -> Expr {
+            Expr::from(Power::new(
+                Expr::from(Variable::new("x")),
+                Expr::from(Constant::new(exponent)),
+            ))
+        };
+        let multiply = Multiply::new(vec![Expr::from(Variable::new("x")), power(2.0)]);
+        let simplified = multiply.simplify();
+        if let Expr::Power(power) = &simplified {
+            assert_eq!(power.to_typist(), "x^3");
+        } else {
+            panic!("Expected Power, found {:?}", simplified);
+        }
+
+        let multiply = Multiply::new(vec![Expr::from(Variable::new("x")), power(-1.0)]);
+        let simplified = multiply.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 1.0);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn multiply_simplify_adds_symbolic_exponents() {
+        let multiply = Multiply::new(vec![
+            Expr::from(Power::new(
+                Expr::from(Variable::new("x")),
+                Expr::from(Variable::new("n")),
+            )),
+            Expr::from(Variable::new("x")),
+        ]);
+        assert_eq!(multiply.simplify().to_typist(), "x^(n + 1)");
+    }
+
+    #[test]
+    fn multiply_simplify_folds_constant_powers() {
+        let root = || {
+            Expr::from(Power::new(
+                Expr::from(Constant::new(2)),
+                Expr::from(Constant::new(Number::rational(1, 2))),
+            ))
+        };
+        let multiply = Multiply::new(vec![Expr::from(Constant::new(3)), root(), root()]);
+        let simplified = multiply.simplify();
+        assert_eq!(simplified, Expr::from(Constant::new(6)));
+        assert_eq!(simplified.simplify(), simplified);
+
+        let multiply = Multiply::new(vec![root(), Expr::from(Variable::new("x")), root(), root()]);
+        // 2^(3/2) has no exact value, so it stays a power
+        let simplified = multiply.simplify();
+        assert_eq!(simplified.to_typist(), "2^(frac(3, 2)) x");
+        assert_eq!(simplified.simplify(), simplified);
+    }
+
+    #[test]
+    fn multiply_simplify_is_canonical() {
+        let x = || Expr::from(Variable::new("x"));
+        let y = || Expr::from(Variable::new("y"));
+        let lhs = Multiply::new(vec![y(), x(), Constant::new(2).into(), y()]).simplify();
+        let rhs = Multiply::new(vec![y(), y(), x(), Constant::new(2).into()]).simplify();
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.to_typist(), "2 x y^2");
+    }
+
+    #[test]
+    fn multiply_simplify_shared_factors() {
+        // Each level multiplies the sine of the previous level by itself, sharing both factors
+        let sine = |arg: Expr| Expr::from(Function::new("sin", vec![arg]));
+        let mut shared = Expr::from(Variable::new("x"));
+        let mut expected = Expr::from(Variable::new("x"));
+        for _ in 0..12 {
+            shared = Expr::from(Multiply::new(vec![sine(shared.clone()), sine(shared)]));
+            expected = Expr::from(Power::new(sine(expected), Constant::new(2).into()));
+        }
+        assert_eq!(shared.simplify(), expected);
+    }
+
+    #[test]
+    fn multiply_to_typist_separates_numbers() {
+        let x = || Expr::from(Variable::new("x"));
+        let multiply = Multiply::new(vec![
+            Expr::from(Constant::new(2)),
+            Expr::from(Constant::new(3)),
+            x(),
+            Expr::from(Constant::new(4)),
+        ]);
+        assert_eq!(multiply.to_typist(), "2 dot 3 x dot 4");
+        let multiply = Multiply::new(vec![
+            Expr::from(Constant::new(2)),
+            Expr::from(Constant::new(Number::rational(1, 3))),
+        ]);
+        assert_eq!(multiply.to_typist(), "2 dot frac(1, 3)");
+        let multiply = Multiply::new(vec![
+            Expr::from(Constant::new(-1)),
+            x(),
+            Expr::from(Constant::new(-2)),
+            Expr::from(Add::new(vec![x(), Expr::from(Constant::new(1))])),
+        ]);
+        assert_eq!(multiply.to_typist(), "-x (-2) (x + 1)");
+    }
 }
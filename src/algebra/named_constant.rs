@@ -0,0 +1,231 @@
+use std::any::Any;
+
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::algebra::add::Add;
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::number::Number;
+
+/// `NamedConstant` represents a mathematical constant that stays symbolic in an expression tree.
+/// It is evaluated numerically by `eval`, or to any number of digits by [`NamedConstant::to_digits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NamedConstant {
+    /// The ratio of a circle's circumference to its diameter, `π`.
+    Pi,
+    /// The base of the natural logarithm, `e`.
+    E,
+    /// The golden ratio, `φ = (1 + sqrt(5)) / 2`.
+    Phi,
+    /// The Euler–Mascheroni constant, `γ`.
+    EulerGamma,
+}
+
+impl NamedConstant {
+    /// Returns the name of the constant, e.g. `pi`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedConstant::Pi => "pi",
+            NamedConstant::E => "e",
+            NamedConstant::Phi => "phi",
+            NamedConstant::EulerGamma => "gamma",
+        }
+    }
+
+    /// Returns the value of the constant rounded to the nearest `f64`.
+    pub fn value(&self) -> f64 {
+        match self {
+            NamedConstant::Pi => std::f64::consts::PI,
+            NamedConstant::E => std::f64::consts::E,
+            NamedConstant::Phi => 1.618_033_988_749_895,
+            NamedConstant::EulerGamma => 0.577_215_664_901_532_9,
+        }
+    }
+
+    /// Returns the decimal expansion of the constant rounded to `digits` significant digits,
+    /// e.g. `3.1416` for `NamedConstant::Pi.to_digits(5)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `digits` - The number of significant digits, at least 1.
+    pub fn to_digits(&self, digits: usize) -> String {
+        BigFloat::evaluate_to_digits(digits, |precision| Ok(self.value_big(precision)))
+            .expect("irrational constants are never on a rounding boundary")
+    }
+
+    /// Returns the constant rounded to `precision` bits, with a guaranteed error bound.
+    pub fn value_big(&self, precision: u64) -> BigFloat {
+        match self {
+            NamedConstant::Pi => BigFloat::pi(precision),
+            NamedConstant::E => BigFloat::e(precision),
+            NamedConstant::Phi => BigFloat::phi(precision),
+            NamedConstant::EulerGamma => BigFloat::euler_gamma(precision),
+        }
+    }
+
+    /// Returns the rational `q` if `op` is the simplified form of `q * pi`, such as `pi`, `3 pi`, `pi * 2 * 3`
+    /// or `pi / 6`.
+    pub fn pi_multiple(op: &Expr) -> Option<BigRational> {
+        match op {
+            Expr::Divide(divide) => {
+                let multiple = NamedConstant::pi_multiple(&divide.numerator)?;
+                match Constant::value_of(&divide.denominator)? {
+                    Number::Exact(denominator) if !denominator.is_zero() => {
+                        Some(multiple / denominator)
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Multiply(_) => match Add::split_coefficient(op.clone()) {
+                // The remaining factors are a multiple of pi only if a single one is left
+                (Number::Exact(coefficient), rest) if !matches!(rest, Expr::Multiply(_)) => {
+                    Some(coefficient * NamedConstant::pi_multiple(&rest)?)
+                }
+                _ => None,
+            },
+            Expr::NamedConstant(NamedConstant::Pi) => Some(BigRational::one()),
+            _ => None,
+        }
+    }
+}
+
+impl Expression for NamedConstant {
+    /// Evaluates the expression to a number.
+    /// For a `NamedConstant`, it returns its value rounded to the nearest `f64`.
+    fn eval(&self, _env: &Environment) -> Result<f64, EvalError> {
+        Ok(self.value())
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// For a `NamedConstant`, it returns its value rounded to the nearest `f64`.
+    fn eval_complex(&self, _env: &Environment) -> Result<Complex64, EvalError> {
+        Ok(Complex64::new(self.value(), 0.0))
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    fn eval_big(&self, _env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        Ok(self.value_big(precision))
+    }
+
+    /// Evaluates the expression to an interval enclosing its value.
+    fn eval_interval(&self, _env: &Environment) -> Result<Interval, EvalError> {
+        Ok(Interval::enclosing(&self.value_big(64)))
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// A `NamedConstant` stays symbolic, so it returns a clone of itself.
+    fn simplify(&self) -> Expr {
+        Expr::from(*self)
+    }
+
+    /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        format!(
+            "{}NamedConstant {{ name: {} }}\n",
+            " ".repeat(indent),
+            self.name()
+        )
+    }
+
+    /// Returns a Typist string for the expression.
+    /// The constants are written with Typst's symbols `pi`, `e`, `phi` and `gamma`.
+    fn to_typist(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// The constants are written as `\pi`, `e`, `\varphi` and `\gamma`.
+    fn to_latex(&self) -> String {
+        match self {
+            NamedConstant::Pi => "\\pi",
+            NamedConstant::E => "e",
+            NamedConstant::Phi => "\\varphi",
+            NamedConstant::EulerGamma => "\\gamma",
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::divide::Divide;
+    use crate::algebra::multiply::Multiply;
+
+    use super::*;
+
+    #[test]
+    fn named_constant_to_digits() {
+        for (constant, expected) in [
+            (NamedConstant::Pi, "3.14159265358979323846264338328"),
+            (NamedConstant::E, "2.71828182845904523536028747135"),
+            (NamedConstant::Phi, "1.61803398874989484820458683437"),
+            (
+                NamedConstant::EulerGamma,
+                "0.577215664901532860606512090082",
+            ),
+        ] {
+            assert_eq!(constant.to_digits(30), expected, "{}", constant.name());
+        }
+        assert_eq!(NamedConstant::Pi.to_digits(1), "3");
+        assert_eq!(NamedConstant::Pi.to_digits(5), "3.1416");
+    }
+
+    #[test]
+    fn named_constant_many_digits() {
+        let pi = NamedConstant::Pi.to_digits(100);
+        assert_eq!(
+            pi,
+            "3.141592653589793238462643383279502884197169399375105820974944592307816406286208998628034825342117068"
+        );
+    }
+
+    #[test]
+    fn named_constant_pi_multiple() {
+        let third_pi = Divide::new(
+            Expr::from(Multiply::new(vec![
+                Expr::from(Constant::new(2)),
+                Expr::from(NamedConstant::Pi),
+            ])),
+            Expr::from(Constant::new(3)),
+        );
+        assert_eq!(
+            NamedConstant::pi_multiple(&Expr::from(third_pi)),
+            Some(BigRational::new(2.into(), 3.into()))
+        );
+        let sixth_pi = Multiply::new(vec![
+            Expr::from(NamedConstant::Pi),
+            Expr::from(Constant::new(BigRational::new(1.into(), 2.into()))),
+            Expr::from(Constant::new(BigRational::new(1.into(), 3.into()))),
+        ]);
+        assert_eq!(
+            NamedConstant::pi_multiple(&Expr::from(sixth_pi)),
+            Some(BigRational::new(1.into(), 6.into()))
+        );
+        assert_eq!(
+            NamedConstant::pi_multiple(&Expr::from(NamedConstant::E)),
+            None
+        );
+        let pi_e = Multiply::new(vec![
+            Expr::from(Constant::new(2)),
+            Expr::from(NamedConstant::Pi),
+            Expr::from(NamedConstant::E),
+        ]);
+        assert_eq!(NamedConstant::pi_multiple(&Expr::from(pi_e)), None);
+    }
+}
@@ -0,0 +1,132 @@
+use std::any::Any;
+
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::display::{latex_operand, typst_operand, Position};
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::multiply::Multiply;
+
+/// `Negate` struct represents an arithmetic negation (unary minus) in an expression tree.
+/// It contains a single `Expr` as its operand.
+pub struct Negate {
+    pub op: Expr,
+}
+
+impl Negate {
+    /// Constructs a new `Negate` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The expression being negated.
+    pub fn new(op: Expr) -> Self {
+        Self { op }
+    }
+}
+
+impl Expression for Negate {
+    /// Evaluates the expression to a number.
+    /// For a `Negate`, it returns the negated value of its operand.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        Ok(-self.op.eval(env)?)
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// For a `Negate`, it returns the negated value of its operand.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        Ok(-self.op.eval_complex(env)?)
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// For a `Negate`, it returns the negation of its evaluated operand.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        Ok(self.op.eval_big(env, precision)?.neg())
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// For a `Negate`, it returns the negation of its evaluated operand.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        Ok(self.op.eval_interval(env)?.neg())
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// Negated constants are folded, double negation is removed, and any other operand is
+    /// rewritten as a multiplication by -1 so it can take part in `Multiply` simplification.
+    fn simplify(&self) -> Expr {
+        if let Expr::Negate(negate) = &self.op {
+            return negate.op.simplify();
+        }
+        let op = self.op.simplify();
+        if let Expr::Constant(constant) = &op {
+            return Expr::from(Constant::new(-&constant.value));
+        }
+        Multiply::new(vec![Expr::from(Constant::new(-1)), op]).simplify()
+    }
+
+    /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        let mut output = format!("{}Negate {{\n", " ".repeat(indent));
+        output.push_str(&self.op.debug(indent + 2));
+        output.push_str(&format!("{}}}\n", " ".repeat(indent)));
+        output
+    }
+
+    /// Returns a Typist string for the expression.
+    /// The operand is wrapped in parentheses if it is a sum or starts with a minus itself.
+    fn to_typist(&self) -> String {
+        format!("-{}", typst_operand(&self.op, Position::Negated))
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Negate`, it returns the operand prefixed with `-`, in parentheses if it is a sum or
+    /// starts with a minus itself.
+    fn to_latex(&self) -> String {
+        format!("-{}", latex_operand(&self.op, Position::Negated))
+    }
+}
+
+impl Clone for Negate {
+    fn clone(&self) -> Self {
+        Negate {
+            op: self.op.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    #[test]
+    fn negate_simplify_constant() {
+        let negate = Negate::new(Expr::from(Constant::new(4.0)));
+        let simplified = negate.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, -4.0);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn negate_simplify_double_negation() {
+        let negate = Negate::new(Expr::from(Negate::new(Expr::from(Variable::new("x")))));
+        let simplified = negate.simplify();
+        if let Expr::Variable(variable) = &simplified {
+            assert_eq!(variable.name, "x");
+        } else {
+            panic!("Expected Variable, found {:?}", simplified);
+        }
+    }
+}
@@ -0,0 +1,736 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Largest number of bits an exact power may have before it is left unevaluated.
+const MAX_EXACT_POWER_BITS: u64 = 1 << 20;
+
+/// Largest decimal exponent a number literal may have and still be read exactly.
+const MAX_EXACT_LITERAL_EXPONENT: u64 = 4096;
+
+/// Largest root an exact power with a fractional exponent is evaluated for.
+const MAX_EXACT_ROOT: u32 = 64;
+
+/// `Number` is the numeric value held by a `Constant`.
+///
+/// Numbers are exact arbitrary-precision rationals by default, so `1/3 + 1/3 + 1/3` is exactly 1
+/// and `0.1 + 0.2` is exactly `3/10`. A floating point number is only created when one is explicitly
+/// requested, e.g. through `From<f64>`, and any arithmetic involving one gives a floating point result.
+///
+/// Complex numbers have an exact and a floating point form as well. Their imaginary part is never
+/// zero, as arithmetic that cancels it out gives a real number, so `i * i` is exactly `-1`.
+#[derive(Debug, Clone)]
+pub enum Number {
+    /// An exact rational number, kept in lowest terms with a positive denominator.
+    Exact(BigRational),
+    /// An inexact floating point number.
+    Float(f64),
+    /// An exact complex number with rational real and non-zero imaginary parts, in that order.
+    ExactComplex(BigRational, BigRational),
+    /// An inexact complex number with a non-zero imaginary part.
+    FloatComplex(Complex64),
+}
+
+impl Number {
+    /// Constructs the exact fraction `numerator / denominator` in lowest terms.
+    ///
+    /// # Arguments
+    ///
+    /// * `numerator` - The integer above the fraction bar.
+    /// * `denominator` - The integer below the fraction bar.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `denominator` is zero.
+    pub fn rational(numerator: i64, denominator: i64) -> Self {
+        Number::Exact(BigRational::new(numerator.into(), denominator.into()))
+    }
+
+    /// Returns the imaginary unit `i`.
+    pub fn i() -> Self {
+        Number::ExactComplex(BigRational::zero(), BigRational::one())
+    }
+
+    /// Constructs the complex number `re + im i`.
+    ///
+    /// # Arguments
+    ///
+    /// * `re` - The real part.
+    /// * `im` - The imaginary part. The result is real if it is zero.
+    pub fn complex(re: impl Into<Number>, im: impl Into<Number>) -> Self {
+        re.into() + im.into() * Number::i()
+    }
+
+    /// Reads a decimal number literal such as `3`, `3.14`, `.5` or `6.02E23` exactly.
+    ///
+    /// Literals with an exponent too large to represent exactly are read as a floating point number.
+    ///
+    /// # Arguments
+    ///
+    /// * `literal` - Digits with an optional fractional part and an optional `e`/`E` exponent.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Number)` - The value of the literal.
+    /// * `None` - If `literal` is not a valid number literal.
+    pub fn parse_decimal(literal: &str) -> Option<Self> {
+        let (mantissa, exponent) = match literal.find(['e', 'E']) {
+            Some(index) => {
+                let exponent = &literal[index + 1..];
+                let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                // An exponent that does not fit an `i64` is far too large to be read exactly
+                (&literal[..index], exponent.parse::<i64>().ok())
+            }
+            None => (literal, Some(0)),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+        let scale = match exponent.and_then(|exponent| exponent.checked_sub(fraction.len() as i64))
+        {
+            Some(scale) if scale.unsigned_abs() <= MAX_EXACT_LITERAL_EXPONENT => scale,
+            _ => return literal.parse::<f64>().ok().map(Number::Float),
+        };
+        let power = num_traits::pow(BigInt::from(10), scale.unsigned_abs() as usize);
+        Some(Number::Exact(if scale < 0 {
+            BigRational::new(digits, power)
+        } else {
+            BigRational::from_integer(digits * power)
+        }))
+    }
+
+    /// Returns the value as a floating point number, rounding exact values to the nearest `f64`.
+    /// Complex numbers have no real value and return `NaN`.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Float(value) => *value,
+            Number::ExactComplex(..) | Number::FloatComplex(_) => f64::NAN,
+        }
+    }
+
+    /// Returns the value as a floating point complex number.
+    pub fn to_complex64(&self) -> Complex64 {
+        match self {
+            Number::ExactComplex(re, im) => Complex64::new(
+                re.to_f64().unwrap_or(f64::NAN),
+                im.to_f64().unwrap_or(f64::NAN),
+            ),
+            Number::FloatComplex(value) => *value,
+            _ => Complex64::new(self.to_f64(), 0.0),
+        }
+    }
+
+    /// Returns `true` if the number is exact rather than a floating point number.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Number::Exact(_) | Number::ExactComplex(..))
+    }
+
+    /// Returns `true` if the number is real.
+    pub fn is_real(&self) -> bool {
+        matches!(self, Number::Exact(_) | Number::Float(_))
+    }
+
+    /// Returns `true` if the number is zero.
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_zero(),
+            Number::Float(value) => *value == 0.0,
+            Number::ExactComplex(..) | Number::FloatComplex(_) => false,
+        }
+    }
+
+    /// Returns `true` if the number is one.
+    pub fn is_one(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_one(),
+            Number::Float(value) => *value == 1.0,
+            Number::ExactComplex(..) | Number::FloatComplex(_) => false,
+        }
+    }
+
+    /// Returns `true` if the number is a whole number.
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_integer(),
+            Number::Float(value) => value.fract() == 0.0,
+            Number::ExactComplex(..) | Number::FloatComplex(_) => false,
+        }
+    }
+
+    /// Returns `true` if the number is real and less than zero.
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_negative(),
+            Number::Float(value) => *value < 0.0,
+            Number::ExactComplex(..) | Number::FloatComplex(_) => false,
+        }
+    }
+
+    /// Returns the real part of the number.
+    pub fn re(&self) -> Number {
+        match self {
+            Number::ExactComplex(re, _) => Number::Exact(re.clone()),
+            Number::FloatComplex(value) => Number::Float(value.re),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the imaginary part of the number.
+    pub fn im(&self) -> Number {
+        match self {
+            Number::Exact(_) => Number::from(0),
+            Number::Float(_) => Number::Float(0.0),
+            Number::ExactComplex(_, im) => Number::Exact(im.clone()),
+            Number::FloatComplex(value) => Number::Float(value.im),
+        }
+    }
+
+    /// Returns the complex conjugate of the number.
+    pub fn conj(&self) -> Number {
+        match self {
+            Number::ExactComplex(re, im) => Number::ExactComplex(re.clone(), -im),
+            Number::FloatComplex(value) => Number::FloatComplex(value.conj()),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the absolute value of the number, which is the modulus for a complex number.
+    /// Returns `None` for an exact complex number whose modulus is irrational, such as `1 + i`.
+    pub fn abs(&self) -> Option<Number> {
+        match self {
+            Number::Exact(value) => Some(Number::Exact(value.abs())),
+            Number::Float(value) => Some(Number::Float(value.abs())),
+            Number::ExactComplex(re, im) => Number::Exact(re * re + im * im).sqrt(),
+            Number::FloatComplex(value) => Some(Number::Float(value.norm())),
+        }
+    }
+
+    /// Returns -1, 0 or 1 depending on the sign of a real number, or `z / |z|` for a complex number.
+    /// Returns `None` for an exact complex number whose modulus is irrational.
+    pub fn signum(&self) -> Option<Number> {
+        match self {
+            Number::Exact(value) => Some(Number::Exact(value.signum())),
+            Number::Float(value) if *value == 0.0 => Some(Number::Float(0.0)),
+            Number::Float(value) => Some(Number::Float(value.signum())),
+            Number::ExactComplex(..) | Number::FloatComplex(_) => self.checked_div(&self.abs()?),
+        }
+    }
+
+    /// Divides the number by `rhs`.
+    /// Returns `None` if `rhs` is zero.
+    pub fn checked_div(&self, rhs: &Number) -> Option<Number> {
+        if rhs.is_zero() {
+            return None;
+        }
+        Some(match (self, rhs) {
+            (Number::Exact(lhs), Number::Exact(rhs)) => Number::Exact(lhs / rhs),
+            _ if self.is_real() && rhs.is_real() => Number::Float(self.to_f64() / rhs.to_f64()),
+            _ => match (self.exact_parts(), rhs.exact_parts()) {
+                (Some((a, b)), Some((c, d))) => {
+                    let denominator = &c * &c + &d * &d;
+                    Number::from_exact_parts(
+                        (&a * &c + &b * &d) / &denominator,
+                        (&b * &c - &a * &d) / &denominator,
+                    )
+                }
+                _ => Number::from(self.to_complex64() / rhs.to_complex64()),
+            },
+        })
+    }
+
+    /// Raises the number to the power `exponent`.
+    ///
+    /// An exact base raised to an exact exponent gives an exact result when one exists, such as
+    /// `(2/3)^2 = 4/9`, `8^(1/3) = 2` or `(1 + i)^2 = 2i`. If either number is a floating point number
+    /// the result is computed in floating point. A real base and exponent never give a complex result,
+    /// so a negative base has no real power with a fractional exponent, such as `(-8)^(1/3)`, whose
+    /// principal value is complex, as in every evaluator.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Number)` - The power, if it has an exact value or a floating point operand and is finite.
+    /// * `None` - If the power is irrational, not real for a real base, a division by zero, or too large to compute exactly.
+    pub fn pow(&self, exponent: &Number) -> Option<Number> {
+        match (self, exponent) {
+            (Number::Exact(base), Number::Exact(exponent)) => {
+                let root = exponent
+                    .denom()
+                    .to_u32()
+                    .filter(|root| *root <= MAX_EXACT_ROOT)?;
+                let power = exponent.numer().to_i64()?;
+                if base.is_zero() && power < 0 {
+                    return None;
+                }
+                let bits = base.numer().bits().max(base.denom().bits());
+                if bits.saturating_mul(power.unsigned_abs()) / u64::from(root)
+                    > MAX_EXACT_POWER_BITS
+                {
+                    return None;
+                }
+                let base = exact_root(base, root)?;
+                let magnitude = u32::try_from(power.unsigned_abs()).ok()?;
+                let result =
+                    BigRational::new(base.numer().pow(magnitude), base.denom().pow(magnitude));
+                Some(Number::Exact(if power < 0 {
+                    result.recip()
+                } else {
+                    result
+                }))
+            }
+            (Number::ExactComplex(re, im), Number::Exact(exponent)) if exponent.is_integer() => {
+                let power = exponent.numer().to_i64()?;
+                let bits = [re.numer(), re.denom(), im.numer(), im.denom()]
+                    .iter()
+                    .map(|part| part.bits())
+                    .max()
+                    .unwrap_or(0);
+                if bits.saturating_mul(power.unsigned_abs()) > MAX_EXACT_POWER_BITS {
+                    return None;
+                }
+                let mut result = Number::from(1);
+                let mut square = self.clone();
+                let mut remaining = power.unsigned_abs();
+                while remaining > 0 {
+                    if remaining & 1 == 1 {
+                        result = &result * &square;
+                    }
+                    square = &square * &square;
+                    remaining >>= 1;
+                }
+                if power < 0 {
+                    Number::from(1).checked_div(&result)
+                } else {
+                    Some(result)
+                }
+            }
+            _ if self.is_exact() && exponent.is_exact() => None,
+            _ if self.is_real() && exponent.is_real() => {
+                let result = self.to_f64().powf(exponent.to_f64());
+                result.is_finite().then_some(Number::Float(result))
+            }
+            _ => {
+                if self.is_zero() {
+                    return None;
+                }
+                let result = self.to_complex64().powc(exponent.to_complex64());
+                result.is_finite().then(|| Number::from(result))
+            }
+        }
+    }
+
+    /// Returns the square root of the number.
+    /// Exact numbers only have a square root here if it is exact, such as `sqrt(9/4) = 3/2`.
+    pub fn sqrt(&self) -> Option<Number> {
+        self.pow(&Number::rational(1, 2))
+    }
+
+    /// Compares two numbers structurally, giving a total order that distinguishes the exact
+    /// number 1 from the floating point number 1.0, unlike `==` which compares by value.
+    /// Exact numbers come before floating point numbers, and numbers of the same kind are ordered
+    /// by their real and then their imaginary parts, with `f64::total_cmp` for floating point parts.
+    pub fn total_cmp(&self, other: &Number) -> Ordering {
+        match (self, other) {
+            (Number::Exact(lhs), Number::Exact(rhs)) => lhs.cmp(rhs),
+            (Number::Float(lhs), Number::Float(rhs)) => lhs.total_cmp(rhs),
+            (Number::ExactComplex(a, b), Number::ExactComplex(c, d)) => a.cmp(c).then(b.cmp(d)),
+            (Number::FloatComplex(lhs), Number::FloatComplex(rhs)) => {
+                lhs.re.total_cmp(&rhs.re).then(lhs.im.total_cmp(&rhs.im))
+            }
+            _ => self.kind().cmp(&other.kind()),
+        }
+    }
+
+    /// Feeds the number into `state` consistently with [`Number::total_cmp`], so numbers that
+    /// compare equal structurally have the same hash.
+    pub fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.kind().hash(state);
+        match self {
+            Number::Exact(value) => value.hash(state),
+            Number::Float(value) => value.to_bits().hash(state),
+            Number::ExactComplex(re, im) => {
+                re.hash(state);
+                im.hash(state);
+            }
+            Number::FloatComplex(value) => {
+                value.re.to_bits().hash(state);
+                value.im.to_bits().hash(state);
+            }
+        }
+    }
+
+    /// Returns the position of the variant in the order used by [`Number::total_cmp`].
+    fn kind(&self) -> u8 {
+        match self {
+            Number::Exact(_) => 0,
+            Number::Float(_) => 1,
+            Number::ExactComplex(..) => 2,
+            Number::FloatComplex(_) => 3,
+        }
+    }
+
+    /// Returns the real and imaginary parts of an exact number.
+    fn exact_parts(&self) -> Option<(BigRational, BigRational)> {
+        match self {
+            Number::Exact(value) => Some((value.clone(), BigRational::zero())),
+            Number::ExactComplex(re, im) => Some((re.clone(), im.clone())),
+            _ => None,
+        }
+    }
+
+    /// Constructs an exact number from its parts, which is real if the imaginary part is zero.
+    fn from_exact_parts(re: BigRational, im: BigRational) -> Number {
+        if im.is_zero() {
+            Number::Exact(re)
+        } else {
+            Number::ExactComplex(re, im)
+        }
+    }
+}
+
+/// Returns the exact `root`-th root of `value`, if it is rational.
+/// A negative value has none, as the principal root of a negative number is not real.
+fn exact_root(value: &BigRational, root: u32) -> Option<BigRational> {
+    if root == 1 {
+        return Some(value.clone());
+    }
+    if value.is_negative() {
+        return None;
+    }
+    let numerator = value.numer().nth_root(root);
+    let denominator = value.denom().nth_root(root);
+    let candidate = BigRational::new(numerator.clone(), denominator.clone());
+    if numerator.pow(root) == *value.numer() && denominator.pow(root) == *value.denom() {
+        Some(candidate)
+    } else {
+        None
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::Exact(BigRational::from_integer(value.into()))
+    }
+}
+
+impl From<i32> for Number {
+    fn from(value: i32) -> Self {
+        Number::from(i64::from(value))
+    }
+}
+
+impl From<BigInt> for Number {
+    fn from(value: BigInt) -> Self {
+        Number::Exact(BigRational::from_integer(value))
+    }
+}
+
+impl From<BigRational> for Number {
+    fn from(value: BigRational) -> Self {
+        Number::Exact(value)
+    }
+}
+
+/// Converting from an `f64` explicitly requests a floating point number.
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float(value)
+    }
+}
+
+/// Converting from a `Complex64` explicitly requests a floating point number,
+/// which is real if the imaginary part is zero.
+impl From<Complex64> for Number {
+    fn from(value: Complex64) -> Self {
+        if value.im == 0.0 {
+            Number::Float(value.re)
+        } else {
+            Number::FloatComplex(value)
+        }
+    }
+}
+
+impl Add for &Number {
+    type Output = Number;
+
+    fn add(self, rhs: &Number) -> Number {
+        match (self, rhs) {
+            (Number::Exact(lhs), Number::Exact(rhs)) => Number::Exact(lhs + rhs),
+            _ if self.is_real() && rhs.is_real() => Number::Float(self.to_f64() + rhs.to_f64()),
+            _ => match (self.exact_parts(), rhs.exact_parts()) {
+                (Some((a, b)), Some((c, d))) => Number::from_exact_parts(a + c, b + d),
+                _ => Number::from(self.to_complex64() + rhs.to_complex64()),
+            },
+        }
+    }
+}
+
+impl Sub for &Number {
+    type Output = Number;
+
+    fn sub(self, rhs: &Number) -> Number {
+        self + &-rhs
+    }
+}
+
+impl Mul for &Number {
+    type Output = Number;
+
+    fn mul(self, rhs: &Number) -> Number {
+        match (self, rhs) {
+            (Number::Exact(lhs), Number::Exact(rhs)) => Number::Exact(lhs * rhs),
+            _ if self.is_real() && rhs.is_real() => Number::Float(self.to_f64() * rhs.to_f64()),
+            _ => match (self.exact_parts(), rhs.exact_parts()) {
+                (Some((a, b)), Some((c, d))) => {
+                    Number::from_exact_parts(&a * &c - &b * &d, &a * &d + &b * &c)
+                }
+                _ => Number::from(self.to_complex64() * rhs.to_complex64()),
+            },
+        }
+    }
+}
+
+impl Neg for &Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Exact(value) => Number::Exact(-value),
+            Number::Float(value) => Number::Float(-value),
+            Number::ExactComplex(re, im) => Number::ExactComplex(-re, -im),
+            Number::FloatComplex(value) => Number::FloatComplex(-value),
+        }
+    }
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, rhs: Number) -> Number {
+        &self + &rhs
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, rhs: Number) -> Number {
+        &self - &rhs
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, rhs: Number) -> Number {
+        &self * &rhs
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        -&self
+    }
+}
+
+/// Numbers compare by value, so an exact number equals a floating point number it rounds to.
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (self, other) {
+            (Number::Exact(lhs), Number::Exact(rhs)) => lhs == rhs,
+            (Number::ExactComplex(a, b), Number::ExactComplex(c, d)) => a == c && b == d,
+            _ if self.is_real() && other.is_real() => self.to_f64() == other.to_f64(),
+            _ => self.to_complex64() == other.to_complex64(),
+        }
+    }
+}
+
+impl PartialEq<f64> for Number {
+    fn eq(&self, other: &f64) -> bool {
+        self.to_f64() == *other
+    }
+}
+
+/// Exact numbers are written as an integer or a fraction such as `-1/3`,
+/// floating point numbers in Rust's shortest round-trip form,
+/// and complex numbers as a sum such as `3 - 4i`.
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Exact(value) if value.is_integer() => write!(f, "{}", value.numer()),
+            Number::Exact(value) => write!(f, "{}/{}", value.numer(), value.denom()),
+            // Very large and very small magnitudes are written with an exponent, as in `1e300`
+            Number::Float(value)
+                if value.is_finite() && *value != 0.0 && !(1e-5..1e16).contains(&value.abs()) =>
+            {
+                write!(f, "{:e}", value)
+            }
+            Number::Float(value) => write!(f, "{}", value),
+            Number::ExactComplex(..) | Number::FloatComplex(_) => {
+                let (re, im) = (self.re(), self.im());
+                let magnitude = if im.is_negative() { -&im } else { im.clone() };
+                let imaginary = if magnitude.is_one() {
+                    "i".to_string()
+                } else if magnitude.is_integer() || !magnitude.is_exact() {
+                    format!("{}i", magnitude)
+                } else {
+                    format!("{} i", magnitude)
+                };
+                match (re.is_zero(), im.is_negative()) {
+                    (true, false) => write!(f, "{}", imaginary),
+                    (true, true) => write!(f, "-{}", imaginary),
+                    (false, false) => write!(f, "{} + {}", re, imaginary),
+                    (false, true) => write!(f, "{} - {}", re, imaginary),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_rational_reduces_to_lowest_terms() {
+        assert_eq!(Number::rational(6, -4).to_string(), "-3/2");
+        assert_eq!(Number::rational(4, 2).to_string(), "2");
+    }
+
+    #[test]
+    fn number_exact_arithmetic() {
+        let third = Number::rational(1, 3);
+        assert_eq!(&(&third + &third) + &third, Number::from(1));
+        let sum = Number::parse_decimal("0.1").unwrap() + Number::parse_decimal("0.2").unwrap();
+        assert_eq!(sum.to_string(), "3/10");
+        assert_eq!(
+            Number::from(1).checked_div(&Number::from(3)),
+            Some(third.clone())
+        );
+        assert_eq!(Number::from(1).checked_div(&Number::from(0)), None);
+    }
+
+    #[test]
+    fn number_float_is_contagious() {
+        let sum = Number::rational(1, 2) + Number::from(0.25);
+        assert!(!sum.is_exact());
+        assert_eq!(sum, 0.75);
+    }
+
+    #[test]
+    fn number_parse_decimal() {
+        for (literal, expected) in [
+            ("42", "42"),
+            ("3.14", "157/50"),
+            (".5", "1/2"),
+            ("5.", "5"),
+            ("1e-9", "1/1000000000"),
+            ("6.02E23", "602000000000000000000000"),
+        ] {
+            assert_eq!(
+                Number::parse_decimal(literal).unwrap().to_string(),
+                expected,
+                "parsing '{}'",
+                literal
+            );
+        }
+        assert!(Number::parse_decimal(".").is_none());
+        assert!(Number::parse_decimal("1e").is_none());
+        assert_eq!(
+            Number::parse_decimal("1e99999999999999999999"),
+            Some(Number::Float(f64::INFINITY))
+        );
+        assert_eq!(
+            Number::parse_decimal("1e-99999999999999999999"),
+            Some(Number::Float(0.0))
+        );
+    }
+
+    #[test]
+    fn number_exact_powers() {
+        let pow = |base: Number, exponent: Number| base.pow(&exponent).map(|n| n.to_string());
+        assert_eq!(
+            pow(Number::rational(2, 3), Number::from(2)),
+            Some("4/9".to_string())
+        );
+        assert_eq!(
+            pow(Number::from(2), Number::from(-2)),
+            Some("1/4".to_string())
+        );
+        assert_eq!(
+            pow(Number::from(8), Number::rational(1, 3)),
+            Some("2".to_string())
+        );
+        assert_eq!(pow(Number::from(-8), Number::rational(1, 3)), None);
+        assert_eq!(pow(Number::from(2), Number::rational(1, 2)), None);
+        assert_eq!(pow(Number::from(-4), Number::rational(1, 2)), None);
+        assert_eq!(pow(Number::from(0), Number::from(-1)), None);
+    }
+
+    #[test]
+    fn number_complex_arithmetic() {
+        let i = Number::i();
+        assert_eq!(&i * &i, Number::from(-1));
+        assert!((&i * &i).is_real());
+        let z = Number::complex(3, 4);
+        assert_eq!(z.to_string(), "3 + 4i");
+        assert_eq!(z.conj().to_string(), "3 - 4i");
+        assert_eq!(z.abs(), Some(Number::from(5)));
+        assert_eq!(
+            Number::from(1).checked_div(&z).unwrap().to_string(),
+            "3/25 - 4/25 i"
+        );
+        assert_eq!(&z + &z.conj(), Number::from(6));
+        assert_eq!(Number::complex(1, 1).abs(), None);
+    }
+
+    #[test]
+    fn number_complex_powers() {
+        let pow = |base: Number, exponent: Number| base.pow(&exponent).map(|n| n.to_string());
+        assert_eq!(
+            pow(Number::complex(1, 1), Number::from(2)),
+            Some("2i".to_string())
+        );
+        assert_eq!(pow(Number::i(), Number::from(-1)), Some("-i".to_string()));
+        assert_eq!(pow(Number::i(), Number::rational(1, 2)), None);
+        let root = Number::from(-1.0).pow(&Number::complex(0.5, 0.0));
+        assert_eq!(root, None);
+        let square = Number::complex(0.0, 2.0).pow(&Number::from(2.0)).unwrap();
+        assert!((square.re().to_f64() + 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn number_total_cmp() {
+        assert_eq!(Number::from(1), Number::from(1.0));
+        assert_eq!(
+            Number::from(1).total_cmp(&Number::from(1.0)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Number::rational(1, 3).total_cmp(&Number::rational(1, 2)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Number::from(-0.0).total_cmp(&Number::from(0.0)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Number::i().total_cmp(&Number::complex(1, 1)),
+            Ordering::Less
+        );
+        assert_eq!(Number::i().total_cmp(&Number::i()), Ordering::Equal);
+    }
+}
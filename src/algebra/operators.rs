@@ -0,0 +1,397 @@
+use std::ops;
+use std::rc::Rc;
+
+use crate::algebra::add::Add;
+use crate::algebra::constant::Constant;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::negate::Negate;
+use crate::algebra::number::Number;
+use crate::algebra::power::Power;
+
+/// Builds an expression tree from Rust tokens at compile time.
+///
+/// Identifiers become variables, except `pi`, `e`, `phi` and `gamma`, which become named constants,
+/// and `i`, which becomes the imaginary unit. A call such as `sin(x)` becomes a function with that name
+/// applied to its argument, and number literals become exact constants as in the parser, so
+/// `expr!(1/3)` is a fraction and `0.1` is exactly `1/10`. Powers are written with
+/// [`Expr::pow`], as `^` does not have the precedence of a power in Rust.
+/// A Rust expression in braces, such as `{ y.clone() }`, is inserted as it is.
+/// Other literals, such as strings or numbers with a suffix like `1u8`, are rejected at compile time.
+///
+/// The operators are the overloads of this module, so the tree has the shape given by the usual
+/// precedence and associativity of Rust, which matches the one of the parser.
+///
+/// ```
+/// use atium::expr;
+/// use atium::algebra::expression::Expression;
+///
+/// let square = expr!(x + 1);
+/// let expr = expr!(2 * { square }.pow(2) + sin(pi / 6));
+/// assert_eq!(expr.simplify().to_string(), "2*(x + 1)^2 + 1/2");
+/// ```
+///
+/// ```compile_fail
+/// use atium::expr;
+///
+/// let expr = expr!(x + 1u8);
+/// ```
+#[macro_export]
+macro_rules! expr {
+    ($($tokens:tt)+) => {
+        $crate::__expr_munch!([] $($tokens)+)
+    };
+}
+
+/// Rewrites the tokens of [`expr!`] one at a time into `[$($out)*]`, and returns the rewritten
+/// tokens as an `Expr` once all of them have been consumed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __expr_munch {
+    ([$($out:tt)*]) => {
+        $crate::algebra::expr::Expr::from($($out)*)
+    };
+    // A method call, whose name is kept and whose argument is an expression
+    ([$($out:tt)*] . $method:ident ( $($arg:tt)+ ) $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* . $method ($crate::expr!($($arg)+))] $($rest)*)
+    };
+    // A function call
+    ([$($out:tt)*] $name:ident ( $($arg:tt)+ ) $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::function::Function::new(stringify!($name), vec![$crate::expr!($($arg)+)])
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] pi $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::named_constant::NamedConstant::Pi
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] e $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::named_constant::NamedConstant::E
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] phi $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::named_constant::NamedConstant::Phi
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] gamma $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::named_constant::NamedConstant::EulerGamma
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] i $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::constant::Constant::new($crate::algebra::number::Number::i())
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] $name:ident $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::from(
+            $crate::algebra::variable::Variable::new(stringify!($name))
+        )] $($rest)*)
+    };
+    ([$($out:tt)*] ( $($inner:tt)+ ) $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* ($crate::expr!($($inner)+))] $($rest)*)
+    };
+    // Operators are matched before literals, as a `literal` fragment would take the sign of `- 1`
+    ([$($out:tt)*] + $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* +] $($rest)*)
+    };
+    ([$($out:tt)*] - $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* -] $($rest)*)
+    };
+    ([$($out:tt)*] * $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* *] $($rest)*)
+    };
+    ([$($out:tt)*] / $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* /] $($rest)*)
+    };
+    // A number literal, checked when the expression is compiled
+    ([$($out:tt)*] $literal:literal $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $crate::algebra::expr::Expr::literal({
+            const LITERAL: &str = stringify!($literal);
+            const _: () = assert!(
+                $crate::algebra::expr::Expr::is_number_literal(LITERAL),
+                concat!("`", stringify!($literal), "` is not an unsuffixed decimal number literal")
+            );
+            LITERAL
+        })] $($rest)*)
+    };
+    ([$($out:tt)*] $other:tt $($rest:tt)*) => {
+        $crate::__expr_munch!([$($out)* $other] $($rest)*)
+    };
+}
+
+impl Expr {
+    /// Returns the expression raised to the power `exponent`.
+    ///
+    /// # Arguments
+    ///
+    /// * `exponent` - The exponent, an expression or a number.
+    pub fn pow(self, exponent: impl Into<Expr>) -> Expr {
+        Expr::from(Power::new(self, exponent.into()))
+    }
+
+    /// Returns whether `literal` is a decimal number literal without a suffix, such as `3`, `1_000`,
+    /// `0.5` or `6.02e23`, which [`expr!`] reads as an exact constant.
+    #[doc(hidden)]
+    pub const fn is_number_literal(literal: &str) -> bool {
+        let bytes = literal.as_bytes();
+        // Skips the digits and underscores starting at `start`, and returns the index after them
+        // together with the number of digits
+        const fn digits(bytes: &[u8], start: usize) -> (usize, usize) {
+            let (mut index, mut count) = (start, 0);
+            while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'_') {
+                if bytes[index] != b'_' {
+                    count += 1;
+                }
+                index += 1;
+            }
+            (index, count)
+        }
+        let (mut index, count) = digits(bytes, 0);
+        if count == 0 {
+            return false;
+        }
+        if index < bytes.len() && bytes[index] == b'.' {
+            index = digits(bytes, index + 1).0;
+        }
+        if index < bytes.len() && (bytes[index] == b'e' || bytes[index] == b'E') {
+            index += 1;
+            if index < bytes.len() && (bytes[index] == b'+' || bytes[index] == b'-') {
+                index += 1;
+            }
+            let (end, count) = digits(bytes, index);
+            if count == 0 {
+                return false;
+            }
+            index = end;
+        }
+        index == bytes.len()
+    }
+
+    /// Returns the exact constant written as the number literal `literal`, used by [`expr!`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `literal` is not a number literal.
+    #[doc(hidden)]
+    pub fn literal(literal: &str) -> Expr {
+        let digits = literal.replace('_', "");
+        match Number::parse_decimal(&digits) {
+            Some(value) => Expr::from(Constant::new(value)),
+            None => panic!("{} is not a number literal", literal),
+        }
+    }
+}
+
+impl From<&Expr> for Expr {
+    fn from(expr: &Expr) -> Self {
+        expr.clone()
+    }
+}
+
+impl From<i32> for Expr {
+    fn from(value: i32) -> Self {
+        Expr::from(Constant::new(value))
+    }
+}
+
+impl From<i64> for Expr {
+    fn from(value: i64) -> Self {
+        Expr::from(Constant::new(value))
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(value: f64) -> Self {
+        Expr::from(Constant::new(value))
+    }
+}
+
+/// Returns the sum `lhs + rhs`, appending to `lhs` if it is already a sum, so a chain of
+/// additions builds a single `Add` node. The node is only copied if it is shared.
+fn sum(lhs: Expr, rhs: Expr) -> Expr {
+    match lhs {
+        Expr::Add(mut add) => {
+            Rc::make_mut(&mut add).ops.push(rhs);
+            Expr::Add(add)
+        }
+        lhs => Expr::from(Add::new(vec![lhs, rhs])),
+    }
+}
+
+/// Returns the product `lhs * rhs`, appending to `lhs` if it is already a product.
+fn product(lhs: Expr, rhs: Expr) -> Expr {
+    match lhs {
+        Expr::Multiply(mut multiply) => {
+            Rc::make_mut(&mut multiply).ops.push(rhs);
+            Expr::Multiply(multiply)
+        }
+        lhs => Expr::from(Multiply::new(vec![lhs, rhs])),
+    }
+}
+
+/// Returns the difference `lhs - rhs`.
+fn difference(lhs: Expr, rhs: Expr) -> Expr {
+    sum(lhs, Expr::from(Negate::new(rhs)))
+}
+
+/// Returns the quotient `lhs / rhs`.
+fn quotient(lhs: Expr, rhs: Expr) -> Expr {
+    Expr::from(Divide::new(lhs, rhs))
+}
+
+/// Implements a binary operator for `Expr` and `&Expr` with anything that converts into an `Expr`
+/// on the right, and for numbers with an `Expr` or an `&Expr` on the right.
+macro_rules! impl_binary_operator {
+    ($trait:ident, $method:ident, $build:ident) => {
+        impl<T: Into<Expr>> ops::$trait<T> for Expr {
+            type Output = Expr;
+
+            fn $method(self, rhs: T) -> Expr {
+                $build(self, rhs.into())
+            }
+        }
+
+        impl<T: Into<Expr>> ops::$trait<T> for &Expr {
+            type Output = Expr;
+
+            fn $method(self, rhs: T) -> Expr {
+                $build(self.clone(), rhs.into())
+            }
+        }
+
+        impl_binary_operator!(@numbers $trait, $method, $build, i64 f64);
+    };
+    (@numbers $trait:ident, $method:ident, $build:ident, $($number:ty)*) => {
+        $(
+            impl ops::$trait<Expr> for $number {
+                type Output = Expr;
+
+                fn $method(self, rhs: Expr) -> Expr {
+                    $build(Expr::from(self), rhs)
+                }
+            }
+
+            impl ops::$trait<&Expr> for $number {
+                type Output = Expr;
+
+                fn $method(self, rhs: &Expr) -> Expr {
+                    $build(Expr::from(self), rhs.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_operator!(Add, add, sum);
+impl_binary_operator!(Sub, sub, difference);
+impl_binary_operator!(Mul, mul, product);
+impl_binary_operator!(Div, div, quotient);
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::from(Negate::new(self))
+    }
+}
+
+impl ops::Neg for &Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        -self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::eval::Environment;
+    use crate::algebra::expression::Expression;
+    use crate::algebra::parser::parse_expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    fn simplified(input: &str) -> String {
+        parse_expression(input).unwrap().simplify().to_typist()
+    }
+
+    #[test]
+    fn operators_build_expressions() {
+        let x = Expr::from(Variable::new("x"));
+        let y = Expr::from(Variable::new("y"));
+        let expr = &x * 2 + &y / 4 - 1;
+        assert_eq!(expr.simplify().to_typist(), simplified("2x + y/4 - 1"));
+        let expr = 3 * -&x + 0.5 * y.clone().pow(2);
+        let env = Environment::new().with("x", 1.0).with("y", 2.0);
+        assert_eq!(expr.eval(&env), Ok(-1.0));
+        assert_eq!((1 - x).simplify().to_typist(), simplified("1 - x"));
+    }
+
+    #[test]
+    fn operators_flatten_chains() {
+        let x = Expr::from(Variable::new("x"));
+        let sum = &x + 1 + 2 + 3;
+        if let Expr::Add(add) = &sum {
+            assert_eq!(add.ops.len(), 4);
+        } else {
+            panic!("Expected Add, found {:?}", sum);
+        }
+        // Appending to a shared sum leaves it unchanged
+        let longer = sum.clone() + 4;
+        assert_eq!(sum.operands().len(), 4);
+        assert_eq!(longer.operands().len(), 5);
+    }
+
+    #[test]
+    fn expr_macro_matches_parser() {
+        assert_eq!(
+            expr!(2 * x + y / 4 - 1).simplify().to_typist(),
+            simplified("2x + y/4 - 1")
+        );
+        assert_eq!(
+            expr!(sin(x).pow(2) + cos(x).pow(2)).simplify().to_typist(),
+            simplified("sin(x)^2 + cos(x)^2")
+        );
+        assert_eq!(
+            expr!(-(a + b) * (a - b)).simplify().to_typist(),
+            simplified("-(a + b)(a - b)")
+        );
+        assert_eq!(
+            expr!(e.pow(i * pi) + 1).simplify().to_typist(),
+            simplified("e^(i pi) + 1")
+        );
+        assert_eq!(expr!(phi * gamma), parse_expression("phi gamma").unwrap());
+    }
+
+    #[test]
+    fn expr_macro_literals_are_exact() {
+        assert_eq!(expr!(1 / 3).simplify().to_typist(), simplified("1/3"));
+        assert_eq!(expr!(0.1 + 0.2).simplify().to_typist(), simplified("3/10"));
+        assert_eq!(expr!(1_000).simplify().to_typist(), "1000");
+        assert_eq!(expr!(1.5e3).simplify().to_typist(), "1500");
+    }
+
+    #[test]
+    fn expr_macro_number_literals() {
+        for literal in ["3", "1_000", "0.5", "5.", "6.02e23", "1E-9"] {
+            assert!(Expr::is_number_literal(literal), "{}", literal);
+        }
+        for literal in ["1u8", "2.5f64", "0x1F", "1e", "\"1\"", "'1'", "true"] {
+            assert!(!Expr::is_number_literal(literal), "{}", literal);
+        }
+    }
+
+    #[test]
+    fn expr_macro_inserts_rust_expressions() {
+        let inner = expr!(x + 1);
+        let n = 3;
+        let expr = expr!({ inner.clone() }.pow({ n }) / 2);
+        assert_eq!(expr.simplify().to_typist(), simplified("(x + 1)^3 / 2"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// `ParseError` describes why an input string could not be parsed into an expression.
+///
+/// It records where the problem is as a byte `offset` into the input, what the parser would
+/// have accepted at that point, and a human readable `message`. Use [`ParseError::render`] to
+/// show the message together with the offending input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the input at which the problem was found.
+    pub offset: usize,
+    /// Descriptions of the tokens that would have been accepted at `offset`.
+    pub expected: Vec<String>,
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+impl ParseError {
+    /// Constructs a new `ParseError` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The byte offset into the input at which the problem was found.
+    /// * `expected` - Descriptions of the tokens that would have been accepted at `offset`.
+    /// * `message` - A human readable description of the problem.
+    pub fn new(offset: usize, expected: Vec<String>, message: String) -> Self {
+        Self {
+            offset,
+            expected,
+            message,
+        }
+    }
+
+    /// Returns the 1-based column of the error in `input`, counted in characters.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string that was parsed.
+    pub fn column(&self, input: &str) -> usize {
+        column_at(input, self.offset)
+    }
+
+    /// Renders the error for display to an end user.
+    ///
+    /// The output shows the input with a caret under the problem, followed by the message and
+    /// the list of expected tokens:
+    ///
+    /// ```text
+    /// 3+*4
+    ///   ^ expected operand after '+' at column 3
+    ///   = expected a number, a variable, a function call or '('
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The string that was parsed.
+    pub fn render(&self, input: &str) -> String {
+        let offset = self.offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let padding = " ".repeat(column_at(input, offset) - 1);
+
+        let mut output = format!(
+            "{}\n{}^ {}",
+            &input[line_start..line_end],
+            padding,
+            self.message
+        );
+        if !self.expected.is_empty() {
+            output.push_str(&format!("\n{}= expected {}", padding, self.expected_list()));
+        }
+        output
+    }
+
+    /// Joins the expected tokens into a readable list, e.g. `a, b or c`.
+    fn expected_list(&self) -> String {
+        match self.expected.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => String::new(),
+        }
+    }
+}
+
+/// Returns the 1-based column of the byte `offset` within its line of `input`, counted in characters.
+fn column_at(input: &str, offset: usize) -> usize {
+    let offset = offset.min(input.len());
+    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    input[line_start..offset].chars().count() + 1
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_render_places_caret() {
+        let error = ParseError::new(
+            2,
+            vec!["a number".to_string(), "'('".to_string()],
+            "expected operand after '+' at column 3".to_string(),
+        );
+        assert_eq!(
+            error.render("3+*4"),
+            "3+*4\n  ^ expected operand after '+' at column 3\n  = expected a number or '('"
+        );
+    }
+
+    #[test]
+    fn parse_error_column_counts_characters() {
+        let error = ParseError::new("θ+".len(), vec![], "message".to_string());
+        assert_eq!(error.column("θ+"), 3);
+        assert_eq!(error.render("θ+"), "θ+\n  ^ message");
+    }
+
+    #[test]
+    fn parse_error_render_multiline_input() {
+        let error = ParseError::new(6, vec![], "message".to_string());
+        assert_eq!(error.column("1 +\n2 *"), 3);
+        assert_eq!(error.render("1 +\n2 *"), "2 *\n  ^ message");
+    }
+}
@@ -1,58 +1,464 @@
-use nom::combinator::map;
-use nom::multi::many0;
-use nom::sequence::preceded;
+use nom::bytes::complete::{take_while, take_while1};
+use nom::character::complete::{char, digit0, one_of, satisfy};
+use nom::combinator::{map, opt, recognize, value, verify};
+use nom::error::{ErrorKind, FromExternalError};
+use nom::multi::many0_count;
+use nom::sequence::{preceded, tuple};
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::digit1,
-    character::complete::multispace0, combinator::map_res, sequence::delimited, IResult,
+    character::complete::multispace0, combinator::map_opt, sequence::delimited, IResult,
 };
 
 use crate::algebra::add::Add;
 use crate::algebra::constant::Constant;
-use crate::algebra::expression::Expression;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::function::Function;
 use crate::algebra::multiply::Multiply;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::negate::Negate;
+use crate::algebra::number::Number;
+use crate::algebra::parse_error::ParseError;
+use crate::algebra::power::Power;
 use crate::algebra::variable::Variable;
 
+/// Name reserved for the imaginary unit.
+const IMAGINARY_UNIT: &str = "i";
+
+/// Names reserved for the named constants that can be written in an expression.
+const NAMED_CONSTANTS: [(&str, NamedConstant); 7] = [
+    ("pi", NamedConstant::Pi),
+    ("π", NamedConstant::Pi),
+    ("e", NamedConstant::E),
+    ("phi", NamedConstant::Phi),
+    ("φ", NamedConstant::Phi),
+    ("gamma", NamedConstant::EulerGamma),
+    ("γ", NamedConstant::EulerGamma),
+];
+
+/// The tokens that can start an operand, reported when an operand is missing.
+const OPERAND_TOKENS: [&str; 4] = ["a number", "a variable", "a function call", "'('"];
+
+/// What the parser found wrong with its input.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SyntaxErrorKind<'a> {
+    /// An operand was required, optionally right after the given token.
+    ExpectedOperand(Option<&'a str>),
+    /// A `)` was required to close the `(` at the start of the given input.
+    UnclosedParenthesis(&'a str),
+    /// A closing delimiter was required to close an opening one: the input at the opening
+    /// delimiter, followed by the opening and the closing delimiter, such as `{` and `}`.
+    UnclosedDelimiter(&'a str, &'a str, &'a str),
+    /// A LaTeX command the parser does not know was found.
+    UnknownCommand(&'a str),
+    /// A complete expression was followed by more input.
+    UnexpectedInput,
+    /// A built-in function was called with the given number of arguments instead of one.
+    WrongArity(&'a str, usize),
+    /// A low level parser rejected the input.
+    Invalid,
+}
+
+/// The error type used by the individual parsers. It records the remaining input at the point of the
+/// problem, and is turned into a [`ParseError`] with an offset into the original input by `parse_expression`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SyntaxError<'a> {
+    pub(crate) input: &'a str,
+    pub(crate) kind: SyntaxErrorKind<'a>,
+}
+
+impl<'a> SyntaxError<'a> {
+    /// Converts the error into a `ParseError` for the original `source` string.
+    pub(crate) fn into_parse_error(self, source: &str) -> ParseError {
+        let offset = source.len() - self.input.len();
+        let mut error = ParseError::new(offset, Vec::new(), String::new());
+        let column = error.column(source);
+        let (expected, message): (&[&str], String) = match self.kind {
+            SyntaxErrorKind::ExpectedOperand(Some(after)) => (
+                &OPERAND_TOKENS,
+                format!("expected operand after '{}' at column {}", after, column),
+            ),
+            SyntaxErrorKind::ExpectedOperand(None) => (
+                &OPERAND_TOKENS,
+                format!("expected operand at column {}", column),
+            ),
+            SyntaxErrorKind::UnclosedParenthesis(open) => {
+                let open = ParseError::new(source.len() - open.len(), Vec::new(), String::new());
+                (
+                    &["an operator", "')'"],
+                    format!(
+                        "expected ')' at column {} to close '(' at column {}",
+                        column,
+                        open.column(source)
+                    ),
+                )
+            }
+            SyntaxErrorKind::UnclosedDelimiter(open, opening, closing) => {
+                let open = ParseError::new(source.len() - open.len(), Vec::new(), String::new());
+                (
+                    &["an operator"],
+                    format!(
+                        "expected '{}' at column {} to close '{}' at column {}",
+                        closing,
+                        column,
+                        opening,
+                        open.column(source)
+                    ),
+                )
+            }
+            SyntaxErrorKind::UnknownCommand(command) => (
+                &[],
+                format!("unknown command '{}' at column {}", command, column),
+            ),
+            SyntaxErrorKind::UnexpectedInput => (
+                &["an operator", "end of input"],
+                match self.input.chars().next() {
+                    Some(c) => format!("unexpected '{}' at column {}", c, column),
+                    None => format!("unexpected end of input at column {}", column),
+                },
+            ),
+            SyntaxErrorKind::WrongArity(name, found) => (
+                &[],
+                format!(
+                    "function '{}' takes 1 argument but {} were given at column {}",
+                    name, found, column
+                ),
+            ),
+            SyntaxErrorKind::Invalid => (&[], format!("invalid input at column {}", column)),
+        };
+        error.expected = expected.iter().map(|token| token.to_string()).collect();
+        error.message = message;
+        error
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for SyntaxError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind: SyntaxErrorKind::Invalid,
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for SyntaxError<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _e: E) -> Self {
+        <Self as nom::error::ParseError<&'a str>>::from_error_kind(input, kind)
+    }
+}
+
+/// The result type of the individual parsers.
+pub(crate) type ParseResult<'a, T> = IResult<&'a str, T, SyntaxError<'a>>;
+
+/// Returns an unrecoverable parse error of the given kind at the start of `input`.
+pub(crate) fn failure<'a, T>(input: &'a str, kind: SyntaxErrorKind<'a>) -> ParseResult<'a, T> {
+    Err(nom::Err::Failure(SyntaxError { input, kind }))
+}
+
+/// Turns a recoverable error from parsing a required operand into an unrecoverable
+/// "expected operand" error, so the caller reports it instead of backtracking.
+///
+/// # Arguments
+///
+/// * `result` - The result of parsing the operand.
+/// * `input` - The input the operand was parsed from.
+/// * `after` - The token the operand was required to follow.
+pub(crate) fn expect_operand<'a>(
+    result: ParseResult<'a, Expr>,
+    input: &'a str,
+    after: &'a str,
+) -> ParseResult<'a, Expr> {
+    match result {
+        Err(nom::Err::Error(_)) => failure(
+            input.trim_start(),
+            SyntaxErrorKind::ExpectedOperand(Some(after)),
+        ),
+        result => result,
+    }
+}
+
+/// Parses an identifier from the input string.
+///
+/// An identifier starts with a letter (any Unicode alphabetic character, so `θ` is accepted) followed by
+/// letters and digits, e.g. `x`, `alpha` or `x2`. It may carry a subscript introduced by an underscore,
+/// as in `x_1` or `v_max`, and may end with any number of primes, as in `f'` or `y''`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with an identifier.
+///
+/// # Returns
+///
+/// * `ParseResult<&str>` - On success, the function returns the remaining input and the text of the identifier.
+fn parse_identifier(input: &str) -> ParseResult<'_, &str> {
+    recognize(tuple((
+        satisfy(char::is_alphabetic),
+        take_while(char::is_alphanumeric),
+        opt(preceded(char('_'), take_while1(char::is_alphanumeric))),
+        many0_count(char('\'')),
+    )))(input)
+}
+
+/// Parses the imaginary unit `i` from the input string.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `i`.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the imaginary unit as a `Expr`.
+fn parse_imaginary_unit(input: &str) -> ParseResult<'_, Expr> {
+    map(
+        delimited(
+            multispace0,
+            verify(parse_identifier, |name: &str| name == IMAGINARY_UNIT),
+            multispace0,
+        ),
+        |_| Expr::from(Constant::new(Number::i())),
+    )(input)
+}
+
+/// Parses a named constant, `pi` (or `π`), `e`, `phi` (or `φ`) or `gamma` (or `γ`), from the input string.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with the name of a constant.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the constant as a `Expr`.
+fn parse_named_constant(input: &str) -> ParseResult<'_, Expr> {
+    map_opt(
+        delimited(multispace0, parse_identifier, multispace0),
+        |name: &str| {
+            NAMED_CONSTANTS
+                .iter()
+                .find(|(constant_name, _)| *constant_name == name)
+                .map(|(_, constant)| Expr::from(*constant))
+        },
+    )(input)
+}
+
 /// Parses a variable from the input string.
 ///
+/// The names of built-in functions, the imaginary unit `i` and the constants `pi`, `π`, `e`, `phi`,
+/// `φ`, `gamma` and `γ` are reserved and are not accepted as variables, though longer names such as
+/// `i_1`, `e_0` or `phi_1` are.
+///
 /// # Arguments
 ///
 /// * `input` - A string slice that should begin with a variable name.
 ///
 /// # Returns
 ///
-/// * `IResult<&str, Box<dyn Expression>>` - On success, the function returns the remaining input and the parsed variable as a `Box<dyn Expression>`.
-fn parse_variable(input: &str) -> IResult<&str, Box<dyn Expression>> {
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed variable as a `Expr`.
+fn parse_variable(input: &str) -> ParseResult<'_, Expr> {
     map(
         delimited(
             multispace0,
-            // TODO: use alpha1 instead?
-            alt((tag("x"), tag("y"), tag("z"))),
+            verify(parse_identifier, |name: &str| {
+                name != IMAGINARY_UNIT
+                    && !Function::is_builtin(name)
+                    && !NAMED_CONSTANTS
+                        .iter()
+                        .any(|(constant, _)| *constant == name)
+            }),
             multispace0,
         ),
-        |var_str: &str| Box::new(Variable::new(var_str)) as Box<dyn Expression>,
+        |var_str: &str| Expr::from(Variable::new(var_str)),
     )(input)
 }
 
 /// Parses a number from the input string.
 ///
+/// Numbers may have a fractional part and an exponent, as in `3`, `3.14`, `.5`, `5.`, `1e-9` or `6.02E23`.
+/// Numbers are read exactly, so `0.1` is the fraction `1/10` rather than the nearest floating point number.
+/// An exact fraction such as `1/3` is written as a division of integers, which simplifies to a `Constant`.
+///
 /// # Arguments
 ///
 /// * `input` - A string slice that should begin with a number.
 ///
 /// # Returns
 ///
-/// * `IResult<&str, Box<dyn Expression>>` - On success, the function returns the remaining input and the parsed number as a `Box<dyn Expression>`.
-fn parse_number(input: &str) -> IResult<&str, Box<dyn Expression>> {
-    map_res(digit1, |digit_str: &str| {
-        digit_str
-            .parse::<f64>()
-            .map(|num| Box::new(Constant::new(num)) as Box<dyn Expression>)
-    })(input)
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed number as a `Expr`.
+fn parse_number(input: &str) -> ParseResult<'_, Expr> {
+    map_opt(
+        recognize(tuple((
+            alt((
+                recognize(tuple((digit1, opt(tuple((char('.'), digit0)))))),
+                recognize(tuple((char('.'), digit1))),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        |digit_str: &str| {
+            Number::parse_decimal(digit_str).map(|num| Expr::from(Constant::new(num)))
+        },
+    )(input)
+}
+
+/// Parses a call of a built-in function from the input string.
+///
+/// The argument is either given in parentheses, as in `sin(x)`, or as a single operand without
+/// parentheses, as in `sin x`. An unparenthesized argument extends over a power but not over a product,
+/// so `sin x^2` is `sin(x^2)` while `sin x cos x` is `sin(x) * cos(x)`, and it may not start with a sign.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with a function name.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed call as a `Expr`.
+fn parse_function_call(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, name) = verify(parse_identifier, |name: &str| Function::is_builtin(name))(input)?;
+    let rest = rest.trim_start();
+    let (rest, args) = if rest.starts_with('(') {
+        parse_arguments(rest)?
+    } else if rest.starts_with(['+', '-']) {
+        // A sign would make `sin - x` a call rather than a subtraction
+        return failure(rest, SyntaxErrorKind::ExpectedOperand(Some(name)));
+    } else {
+        let (rest, arg) = expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, name)?;
+        (rest, vec![arg])
+    };
+    if args.len() != 1 {
+        return failure(input, SyntaxErrorKind::WrongArity(name, args.len()));
+    }
+    Ok((rest, Expr::from(Function::new(name, args))))
+}
+
+/// Parses a parenthesized, comma-separated list of function arguments from the input string.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `(`.
+///
+/// # Returns
+///
+/// * `ParseResult<Vec<Expr>>` - On success, the function returns the remaining input and the parsed arguments.
+fn parse_arguments(input: &str) -> ParseResult<'_, Vec<Expr>> {
+    let (mut rest, mut after) = (&input[1..], "(");
+    let mut args = Vec::new();
+    loop {
+        let (next, arg) = expect_operand(parse_binary(rest, 0), rest, after)?;
+        args.push(arg);
+        let next = next.trim_start();
+        if let Some(next) = next.strip_prefix(')') {
+            return Ok((next, args));
+        } else if let Some(next) = next.strip_prefix(',') {
+            (rest, after) = (next, ",");
+        } else {
+            return failure(next, SyntaxErrorKind::UnclosedParenthesis(input));
+        }
+    }
+}
+
+/// Parses an expression in parentheses from the input string.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with `(`.
+///
+/// # Returns
+///
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_parenthesized(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, open) = tag("(")(input)?;
+    let (rest, expr) = expect_operand(parse_binary(rest, 0), rest, open)?;
+    match rest.trim_start().strip_prefix(')') {
+        Some(rest) => Ok((rest, expr)),
+        None => failure(
+            rest.trim_start(),
+            SyntaxErrorKind::UnclosedParenthesis(input),
+        ),
+    }
+}
+
+/// Binary operators understood by the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+/// Precedence of the prefix `-` and `+` operators. It binds tighter than multiplication but
+/// looser than exponentiation, so `-x^2` parses as `-(x^2)` and `2^-x` as `2^(-x)`.
+pub(crate) const UNARY_PRECEDENCE: u8 = 3;
+
+impl Operator {
+    /// Returns the binding power of the operator. Higher values bind tighter.
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            Operator::Add | Operator::Subtract => 1,
+            Operator::Multiply | Operator::Divide => 2,
+            Operator::Power => 4,
+        }
+    }
+
+    /// Returns the symbol the operator is written with.
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Power => "^",
+        }
+    }
+
+    /// Returns `true` if a chain of this operator groups from the right, as `^` does.
+    fn is_right_associative(self) -> bool {
+        self == Operator::Power
+    }
+
+    /// Builds the expression node for `lhs <operator> rhs`.
+    pub(crate) fn apply(self, lhs: Expr, rhs: Expr) -> Expr {
+        match self {
+            Operator::Add => Expr::from(Add::new(vec![lhs, rhs])),
+            // Subtraction is represented as adding the negated right-hand side
+            Operator::Subtract => Expr::from(Add::new(vec![lhs, Expr::from(Negate::new(rhs))])),
+            Operator::Multiply => Expr::from(Multiply::new(vec![lhs, rhs])),
+            Operator::Divide => Expr::from(Divide::new(lhs, rhs)),
+            Operator::Power => Expr::from(Power::new(lhs, rhs)),
+        }
+    }
+}
+
+/// Parses a binary operator from the input string, skipping surrounding whitespace.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should begin with an operator.
+///
+/// # Returns
+///
+/// * `ParseResult<Operator>` - On success, the function returns the remaining input and the parsed operator.
+fn parse_operator(input: &str) -> ParseResult<'_, Operator> {
+    delimited(
+        multispace0,
+        alt((
+            value(Operator::Add, tag("+")),
+            value(Operator::Subtract, tag("-")),
+            value(Operator::Multiply, tag("*")),
+            value(Operator::Divide, tag("/")),
+            value(Operator::Power, tag("^")),
+        )),
+        multispace0,
+    )(input)
 }
 
 /// Parses a factor from the input string.
 ///
-/// A factor is either a number, a variable, or an expression in parentheses.
+/// A factor is either a number, a variable, a function call, or an expression in parentheses.
 ///
 /// # Arguments
 ///
@@ -60,12 +466,15 @@ fn parse_number(input: &str) -> IResult<&str, Box<dyn Expression>> {
 ///
 /// # Returns
 ///
-/// * `IResult<&str, Box<dyn Expression>>` - On success, the function returns the remaining input and the parsed factor as a `Box<dyn Expression>`.
-fn parse_factor(input: &str) -> IResult<&str, Box<dyn Expression>> {
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed factor as a `Expr`.
+fn parse_factor(input: &str) -> ParseResult<'_, Expr> {
     delimited(
         multispace0,
         alt((
-            delimited(tag("("), parse_expression, tag(")")),
+            parse_parenthesized,
+            parse_function_call,
+            parse_imaginary_unit,
+            parse_named_constant,
             parse_variable,
             parse_number,
         )),
@@ -73,79 +482,135 @@ fn parse_factor(input: &str) -> IResult<&str, Box<dyn Expression>> {
     )(input)
 }
 
-/// Parses a term from the input string.
+/// Parses a unary expression from the input string.
 ///
-/// A term is a sequence of factors separated by multiplication operators.
+/// A unary expression is a factor optionally preceded by any number of prefix `-` or `+` signs.
 ///
 /// # Arguments
 ///
-/// * `input` - A string slice that should begin with a term.
+/// * `input` - A string slice that should begin with a unary expression.
 ///
 /// # Returns
 ///
-/// * `IResult<&str, Box<dyn Expression>>` - On success, the function returns the remaining input and the parsed term as a `Box<dyn Expression>`.
-fn parse_term(input: &str) -> IResult<&str, Box<dyn Expression>> {
-    let (input, init) = parse_factor(input)?;
-    let (input, ops) = many0(preceded(tag("*"), parse_factor))(input)?;
-    Ok((
-        input,
-        ops.into_iter()
-            .fold(init, |acc, val| Box::new(Multiply::new(vec![acc, val]))),
-    ))
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_unary(input: &str) -> ParseResult<'_, Expr> {
+    let input = input.trim_start();
+    if let Some(rest) = input.strip_prefix('-') {
+        let (rest, op) = expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, "-")?;
+        Ok((rest, Expr::from(Negate::new(op))))
+    } else if let Some(rest) = input.strip_prefix('+') {
+        expect_operand(parse_binary(rest, UNARY_PRECEDENCE), rest, "+")
+    } else {
+        parse_factor(input)
+    }
 }
 
-/// Parses an expression from the input string.
+/// Returns `true` if the input starts with an operand that may be multiplied by juxtaposition.
 ///
-/// An expression is a sequence of terms separated by addition or subtraction operators.
+/// Only a parenthesized expression or an identifier may follow another operand without an explicit `*`,
+/// as in `2x`, `3(x+1)`, `(a+b)(a-b)` or `x y`. A number never may, so `2 3` and `x 2` are rejected
+/// rather than silently read as products.
+fn starts_implicit_operand(input: &str) -> bool {
+    matches!(input.trim_start().chars().next(), Some(c) if c == '(' || c.is_alphabetic())
+}
+
+/// Parses a chain of binary operations using precedence climbing.
+///
+/// Only operators whose precedence is at least `min_precedence` are consumed; anything binding
+/// more loosely is left for the caller. Left-associative operators parse their right-hand side one
+/// level higher so that `1 - 2 - 3` groups as `(1 - 2) - 3`, while `^` parses it at the same level
+/// so that `2^3^2` groups as `2^(3^2)`.
+///
+/// Juxtaposed operands are multiplied with the same precedence as `*`, so `2x^2` is `2 * x^2`
+/// and `1/2x` is `(1/2) * x`.
 ///
 /// # Arguments
 ///
 /// * `input` - A string slice that should begin with an expression.
+/// * `min_precedence` - The loosest operator precedence this call may consume.
 ///
 /// # Returns
 ///
-/// * `IResult<&str, Box<dyn Expression>>` - On success, the function returns the remaining input and the parsed expression as a `Box<dyn Expression>`.
-pub fn parse_expression(input: &str) -> IResult<&str, Box<dyn Expression>> {
-    let (input, init) = parse_term(input)?;
-    let (input, ops) = many0(alt((
-        preceded(tag("+"), parse_term),
-        // Handle subtraction by negating the term following the '-'
-        map(
-            preceded(tag("-"), parse_term),
-            |term: Box<dyn Expression>| {
-                Box::new(Add::new(vec![
-                    Box::new(Constant::new(0.0)),
-                    Box::new(Multiply::new(vec![Box::new(Constant::new(-1.0)), term])),
-                ])) as Box<dyn Expression>
-            },
-        ),
-    )))(input)?;
-
-    let result = ops
-        .into_iter()
-        .fold(init, |acc, val| Box::new(Add::new(vec![acc, val])));
+/// * `ParseResult<Expr>` - On success, the function returns the remaining input and the parsed expression as a `Expr`.
+fn parse_binary(input: &str, min_precedence: u8) -> ParseResult<'_, Expr> {
+    let (mut input, mut lhs) = parse_unary(input)?;
+    loop {
+        let (rest, operator) = match parse_operator(input) {
+            Ok(result) => result,
+            Err(nom::Err::Error(_)) if starts_implicit_operand(input) => {
+                (input, Operator::Multiply)
+            }
+            Err(nom::Err::Error(_)) => break,
+            Err(err) => return Err(err),
+        };
+        if operator.precedence() < min_precedence {
+            break;
+        }
+        let next_precedence = if operator.is_right_associative() {
+            operator.precedence()
+        } else {
+            operator.precedence() + 1
+        };
+        let (rest, rhs) = if rest.len() == input.len() {
+            // Implicit multiplication only starts when an operand follows, so no operator to report
+            parse_binary(rest, next_precedence)?
+        } else {
+            expect_operand(parse_binary(rest, next_precedence), rest, operator.symbol())?
+        };
+        lhs = operator.apply(lhs, rhs);
+        input = rest;
+    }
+    Ok((input, lhs))
+}
 
-    if input.is_empty() {
-        Ok((input, result))
-    } else {
-        let err = nom::error::Error::new(input, nom::error::ErrorKind::Eof);
-        Err(nom::Err::Failure(err))
+/// Parses an expression from the input string.
+///
+/// An expression is a sequence of operands joined by `+`, `-`, `*`, `/` and `^`, with the usual
+/// precedence: `^` binds tightest and is right-associative, followed by prefix `-`/`+`, then `*`, `/`
+/// and implicit multiplication, then `+` and `-`. Parentheses may be used to override the precedence.
+/// The whole input must be consumed.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that should contain an expression.
+///
+/// # Returns
+///
+/// * `Result<Expr, ParseError>` - On success, the function returns the parsed expression as a `Expr`.
+///
+/// # Errors
+///
+/// This function will return a [`ParseError`] locating the first problem in the input if it is not a valid expression.
+pub fn parse_expression(input: &str) -> Result<Expr, ParseError> {
+    match parse_binary(input, 0) {
+        Ok(("", result)) => Ok(result),
+        Ok((rest, _)) => Err(SyntaxError {
+            input: rest,
+            kind: SyntaxErrorKind::UnexpectedInput,
+        }
+        .into_parse_error(input)),
+        Err(nom::Err::Error(_)) => Err(SyntaxError {
+            input: input.trim_start(),
+            kind: SyntaxErrorKind::ExpectedOperand(None),
+        }
+        .into_parse_error(input)),
+        Err(nom::Err::Failure(err)) => Err(err.into_parse_error(input)),
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers never return Incomplete"),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::algebra::add::Add;
     use crate::algebra::constant::Constant;
-    use crate::algebra::multiply::Multiply;
+    use crate::algebra::expression::Expression;
 
     use super::*;
 
     #[test]
     fn parse_single_number() {
         let input = "3";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(constant) = parsed.as_any().downcast_ref::<Constant>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Constant(constant) = &parsed {
             assert_eq!(constant.value, 3.0);
         } else {
             panic!("Expected Constant");
@@ -155,8 +620,8 @@ mod tests {
     #[test]
     fn parse_addition_expression() {
         let input = "3+2";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(add) = parsed.as_any().downcast_ref::<Add>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Add(add) = &parsed {
             assert_eq!(add.ops.len(), 2);
         } else {
             panic!("Expected Add");
@@ -166,8 +631,8 @@ mod tests {
     #[test]
     fn parse_multiplication_expression() {
         let input = "3*2";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(multiply) = parsed.as_any().downcast_ref::<Multiply>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Multiply(multiply) = &parsed {
             assert_eq!(multiply.ops.len(), 2);
         } else {
             panic!("Expected Multiply");
@@ -177,10 +642,10 @@ mod tests {
     #[test]
     fn parse_complex_expression() {
         let input = "3+2*4";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(add) = parsed.as_any().downcast_ref::<Add>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Add(add) = &parsed {
             assert_eq!(add.ops.len(), 2);
-            if let Some(multiply) = add.ops[1].as_any().downcast_ref::<Multiply>() {
+            if let Expr::Multiply(multiply) = &add.ops[1] {
                 assert_eq!(multiply.ops.len(), 2);
             } else {
                 panic!("Expected Multiply in Add");
@@ -200,8 +665,8 @@ mod tests {
     #[test]
     fn parse_variable_expression() {
         let input = "x";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(variable) = parsed.as_any().downcast_ref::<Variable>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Variable(variable) = &parsed {
             assert_eq!(variable.name, "x");
         } else {
             panic!("Expected Variable");
@@ -211,11 +676,11 @@ mod tests {
     #[test]
     fn parse_subtraction_expression() {
         let input = "3-2";
-        let (_, parsed) = parse_expression(input)
+        let parsed = parse_expression(input)
             .unwrap_or_else(|_| panic!("Failed to parse expression '{}'", input));
 
         // Expect the top-level operation to be an `Add`.
-        if let Some(add) = parsed.as_any().downcast_ref::<Add>() {
+        if let Expr::Add(add) = &parsed {
             // The `Add` operation should have exactly two operands: `3` and the negation structure.
             assert_eq!(
                 add.ops.len(),
@@ -225,7 +690,7 @@ mod tests {
             );
 
             // The first operand should be the constant `3`.
-            if let Some(constant) = add.ops[0].as_any().downcast_ref::<Constant>() {
+            if let Expr::Constant(constant) = &add.ops[0] {
                 assert_eq!(
                     constant.value, 3.0,
                     "Expected first operand to be 3, found {}",
@@ -238,72 +703,25 @@ mod tests {
                 );
             }
 
-            // The second operand should be an `Add` operation representing the negated term.
-            if let Some(inner_add) = add.ops[1].as_any().downcast_ref::<Add>() {
-                // This `Add` operation should have exactly two operands: `0` and the multiplication by `-1`.
-                assert_eq!(
-                    inner_add.ops.len(),
-                    2,
-                    "Expected inner Add operation to have 2 operands for negation, found {}",
-                    inner_add.ops.len()
-                );
-
-                // The first operand of this inner `Add` should be the constant `0`.
-                if let Some(constant) = inner_add.ops[0].as_any().downcast_ref::<Constant>() {
+            // The second operand should be the negation of the constant `2`.
+            if let Expr::Negate(negate) = &add.ops[1] {
+                if let Expr::Constant(constant) = &negate.op {
                     assert_eq!(
-                        constant.value, 0.0,
-                        "Expected first operand of inner Add to be 0, found {}",
+                        constant.value, 2.0,
+                        "Expected negated operand to be 2, found {}",
                         constant.value
                     );
                 } else {
                     panic!(
-                        "Expected first operand of inner Add to be Constant(0), found {:?}",
-                        inner_add.ops[0]
-                    );
-                }
-
-                // The second operand should be a `Multiply` operation with `-1` and `2`.
-                if let Some(multiply) = inner_add.ops[1].as_any().downcast_ref::<Multiply>() {
-                    assert_eq!(
-                        multiply.ops.len(),
-                        2,
-                        "Expected Multiply operation to have 2 operands for negation, found {}",
-                        multiply.ops.len()
-                    );
-
-                    if let Some(constant) = multiply.ops[0].as_any().downcast_ref::<Constant>() {
-                        assert_eq!(
-                            constant.value, -1.0,
-                            "Expected first operand of Multiply to be -1 for negation, found {}",
-                            constant.value
-                        );
-                    } else {
-                        panic!(
-                            "Expected first operand of Multiply to be Constant(-1), found {:?}",
-                            multiply.ops[0]
-                        );
-                    }
-
-                    if let Some(constant) = multiply.ops[1].as_any().downcast_ref::<Constant>() {
-                        assert_eq!(
-                            constant.value, 2.0,
-                            "Expected second operand of Multiply to be 2, found {}",
-                            constant.value
-                        );
-                    } else {
-                        panic!(
-                            "Expected second operand of Multiply to be Constant(2), found {:?}",
-                            multiply.ops[1]
-                        );
-                    }
-                } else {
-                    panic!(
-                        "Expected second operand of inner Add to be Multiply(-1, 2), found {:?}",
-                        inner_add.ops[1]
+                        "Expected negated operand to be Constant(2), found {:?}",
+                        negate.op
                     );
                 }
             } else {
-                panic!("Expected second operand of top-level Add to be an inner Add operation representing negation, found {:?}", add.ops[1]);
+                panic!(
+                    "Expected second operand of Add to be Negate(2), found {:?}",
+                    add.ops[1]
+                );
             }
         } else {
             panic!(
@@ -316,10 +734,10 @@ mod tests {
     #[test]
     fn parse_expression_with_whitespace() {
         let input = " 3 + 2 * 4 ";
-        let (_, parsed) = parse_expression(input).unwrap();
-        if let Some(add) = parsed.as_any().downcast_ref::<Add>() {
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Add(add) = &parsed {
             assert_eq!(add.ops.len(), 2);
-            if let Some(multiply) = add.ops[1].as_any().downcast_ref::<Multiply>() {
+            if let Expr::Multiply(multiply) = &add.ops[1] {
                 assert_eq!(multiply.ops.len(), 2);
             } else {
                 panic!("Expected Multiply");
@@ -328,4 +746,371 @@ mod tests {
             panic!("Expected Add");
         }
     }
+
+    #[test]
+    fn parse_parenthesized_expression() {
+        let input = "(3+2)*4";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Multiply(multiply) = &parsed {
+            assert!(matches!(multiply.ops[0], Expr::Add(_)));
+        } else {
+            panic!("Expected Multiply, found {:?}", parsed);
+        }
+    }
+
+    #[test]
+    fn parse_division_is_left_associative() {
+        let input = "8/4/2";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Divide(divide) = &parsed {
+            assert!(matches!(divide.numerator, Expr::Divide(_)));
+            assert!(matches!(divide.denominator, Expr::Constant(_)));
+        } else {
+            panic!("Expected Divide, found {:?}", parsed);
+        }
+        assert_eq!(*Constant::value_of(&parsed.simplify()).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn parse_power_is_right_associative() {
+        let input = "2^3^2";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Power(power) = &parsed {
+            assert!(matches!(power.base, Expr::Constant(_)));
+            assert!(matches!(power.exponent, Expr::Power(_)));
+        } else {
+            panic!("Expected Power, found {:?}", parsed);
+        }
+        assert_eq!(*Constant::value_of(&parsed.simplify()).unwrap(), 512.0);
+    }
+
+    #[test]
+    fn parse_power_binds_tighter_than_multiplication() {
+        let input = "2*x^2";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Multiply(multiply) = &parsed {
+            assert!(matches!(multiply.ops[1], Expr::Power(_)));
+        } else {
+            panic!("Expected Multiply, found {:?}", parsed);
+        }
+    }
+
+    #[test]
+    fn parse_unary_minus_binds_looser_than_power() {
+        let input = "-x^2";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Negate(negate) = &parsed {
+            assert!(matches!(negate.op, Expr::Power(_)));
+        } else {
+            panic!("Expected Negate, found {:?}", parsed);
+        }
+    }
+
+    #[test]
+    fn parse_unary_minus_in_exponent() {
+        let input = "2^-1";
+        let parsed = parse_expression(input).unwrap();
+        assert_eq!(*Constant::value_of(&parsed.simplify()).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn parse_unary_plus_and_repeated_signs() {
+        let input = "+3 * --2";
+        let parsed = parse_expression(input).unwrap();
+        assert_eq!(*Constant::value_of(&parsed.simplify()).unwrap(), 6.0);
+    }
+
+    #[test]
+    fn parse_unbalanced_parentheses() {
+        assert!(parse_expression("(3+2").is_err());
+        assert!(parse_expression("3+2)").is_err());
+    }
+
+    #[test]
+    fn parse_multi_character_variable() {
+        let input = "alpha + x2";
+        let parsed = parse_expression(input).unwrap();
+        if let Expr::Add(add) = &parsed {
+            let names: Vec<&str> = add
+                .ops
+                .iter()
+                .map(|op| match op {
+                    Expr::Variable(variable) => variable.name.as_str(),
+                    op => panic!("Expected Variable, found {:?}", op),
+                })
+                .collect();
+            assert_eq!(names, vec!["alpha", "x2"]);
+        } else {
+            panic!("Expected Add, found {:?}", parsed);
+        }
+    }
+
+    #[test]
+    fn parse_subscripted_primed_and_unicode_variables() {
+        for input in ["x_1", "v_max", "f'", "y''", "θ", "θ_0"] {
+            let parsed = parse_expression(input).unwrap();
+            if let Expr::Variable(variable) = &parsed {
+                assert_eq!(variable.name, input);
+            } else {
+                panic!("Expected Variable for '{}', found {:?}", input, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_invalid_identifiers() {
+        assert!(parse_expression("x_").is_err());
+        assert!(parse_expression("_x").is_err());
+        assert!(parse_expression("'x").is_err());
+    }
+
+    #[test]
+    fn parse_decimal_and_scientific_numbers() {
+        for (input, expected) in [
+            ("2.75", 2.75),
+            (".5", 0.5),
+            ("5.", 5.0),
+            ("1e-9", 1e-9),
+            ("6.02E23", 6.02e23),
+            ("2.5e+3", 2500.0),
+        ] {
+            let parsed = parse_expression(input).unwrap();
+            if let Expr::Constant(constant) = &parsed {
+                assert_eq!(constant.value, expected, "parsing '{}'", input);
+            } else {
+                panic!("Expected Constant for '{}', found {:?}", input, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_invalid_numbers() {
+        assert!(parse_expression("1.2.3").is_err());
+        assert!(parse_expression(".").is_err());
+        assert!(parse_expression("1e+").is_err());
+    }
+
+    #[test]
+    fn parse_rational_stays_exact() {
+        let input = "2/6";
+        let parsed = parse_expression(input).unwrap();
+        let simplified = parsed.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, Number::rational(1, 3));
+            assert!(constant.value.is_exact());
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn parse_decimal_arithmetic_stays_exact() {
+        for (input, expected) in [
+            ("1/3 + 1/3 + 1/3", "1"),
+            ("0.1 + 0.2", "frac(3, 10)"),
+            ("2^-2 * 3", "frac(3, 4)"),
+            ("(2/3)^2", "frac(4, 9)"),
+        ] {
+            let simplified = parse_expression(input).unwrap().simplify();
+            assert_eq!(simplified.to_typist(), expected, "simplifying '{}'", input);
+        }
+    }
+
+    #[test]
+    fn parse_imaginary_unit_expressions() {
+        for (input, expected) in [
+            ("i", "i"),
+            ("3 + 4i", "3 + 4i"),
+            ("i*i", "-1"),
+            ("i^2", "-1"),
+            ("(1+i)(1-i)", "2"),
+            ("1/(1+i)", "frac(1, 2) - frac(1, 2) i"),
+            ("conj(2 - i)", "2 + i"),
+        ] {
+            let simplified = parse_expression(input).unwrap().simplify();
+            assert_eq!(simplified.to_typist(), expected, "simplifying '{}'", input);
+        }
+        let parsed = parse_expression("i_1").unwrap();
+        assert!(matches!(parsed, Expr::Variable(_)));
+    }
+
+    #[test]
+    fn parse_named_constants() {
+        for (input, expected) in [
+            ("pi", "pi"),
+            ("π", "pi"),
+            ("2pi + pi", "3 pi"),
+            ("sin(pi)", "0"),
+            ("cos(2pi/3)", "-frac(1, 2)"),
+            ("ln(e)", "1"),
+            ("ln(e^x)", "x"),
+            ("e^2 e", "e^3"),
+            ("phi + φ", "2 phi"),
+            ("gamma γ", "gamma^2"),
+        ] {
+            let simplified = parse_expression(input).unwrap().simplify();
+            assert_eq!(simplified.to_typist(), expected, "simplifying '{}'", input);
+        }
+        for input in ["e_0", "phi_1", "gamma'"] {
+            let parsed = parse_expression(input).unwrap();
+            assert!(matches!(parsed, Expr::Variable(_)), "{}", input);
+        }
+        assert_eq!(
+            parse_expression("φ").unwrap(),
+            Expr::from(NamedConstant::Phi)
+        );
+        assert_eq!(
+            parse_expression("gamma").unwrap(),
+            Expr::from(NamedConstant::EulerGamma)
+        );
+    }
+
+    #[test]
+    fn parse_implicit_multiplication() {
+        for input in ["2x", "3(x+1)", "(a+b)(a-b)", "x y", "2 x", "x(y)"] {
+            let parsed = parse_expression(input).unwrap();
+            if let Expr::Multiply(multiply) = &parsed {
+                assert_eq!(multiply.ops.len(), 2, "parsing '{}'", input);
+            } else {
+                panic!("Expected Multiply for '{}', found {:?}", input, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_implicit_multiplication_precedence() {
+        let parsed = parse_expression("2x^2 + 1").unwrap();
+        if let Expr::Add(add) = &parsed {
+            let Expr::Multiply(multiply) = &add.ops[0] else {
+                panic!("Expected Multiply, found {:?}", add.ops[0]);
+            };
+            assert!(matches!(multiply.ops[1], Expr::Power(_)));
+        } else {
+            panic!("Expected Add, found {:?}", parsed);
+        }
+
+        let parsed = parse_expression("1/2x").unwrap();
+        if let Expr::Multiply(multiply) = &parsed {
+            assert!(matches!(multiply.ops[0], Expr::Divide(_)));
+        } else {
+            panic!("Expected Multiply, found {:?}", parsed);
+        }
+    }
+
+    #[test]
+    fn parse_juxtaposed_numbers_are_rejected() {
+        assert!(parse_expression("2 3").is_err());
+        assert!(parse_expression("x 2").is_err());
+        assert!(parse_expression("(x)2").is_err());
+    }
+
+    #[test]
+    fn parse_function_calls() {
+        for input in ["sin(x)", "sin x", "ln(2x + 1)", "sqrt (x)", "exp(-x)"] {
+            let parsed = parse_expression(input).unwrap();
+            if let Expr::Function(function) = &parsed {
+                assert_eq!(function.args.len(), 1, "parsing '{}'", input);
+            } else {
+                panic!("Expected Function for '{}', found {:?}", input, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_function_call_without_parentheses() {
+        let parsed = parse_expression("sin x^2").unwrap();
+        let Expr::Function(function) = &parsed else {
+            panic!("Expected Function, found {:?}", parsed);
+        };
+        assert!(matches!(function.args[0], Expr::Power(_)));
+
+        let parsed = parse_expression("2sin x cos x").unwrap();
+        let Expr::Multiply(multiply) = &parsed else {
+            panic!("Expected Multiply, found {:?}", parsed);
+        };
+        assert!(matches!(multiply.ops[1], Expr::Function(_)));
+    }
+
+    #[test]
+    fn parse_function_call_in_expression() {
+        let parsed = parse_expression("sin(x)^2 + cos(x)^2").unwrap();
+        let Expr::Add(add) = &parsed else {
+            panic!("Expected Add, found {:?}", parsed);
+        };
+        let Expr::Power(power) = &add.ops[0] else {
+            panic!("Expected Power, found {:?}", add.ops[0]);
+        };
+        assert!(matches!(power.base, Expr::Function(_)));
+    }
+
+    #[test]
+    fn parse_unknown_function_is_multiplication() {
+        let parsed = parse_expression("f(x)").unwrap();
+        assert!(matches!(parsed, Expr::Multiply(_)));
+    }
+
+    #[test]
+    fn parse_invalid_function_calls() {
+        assert!(parse_expression("sin").is_err());
+        assert!(parse_expression("sin + 1").is_err());
+        assert!(parse_expression("sin(x, y)").is_err());
+        assert!(parse_expression("sin()").is_err());
+    }
+
+    #[test]
+    fn parse_error_reports_missing_operand() {
+        let err = parse_expression("3+*4").unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.message, "expected operand after '+' at column 3");
+        assert!(err.expected.contains(&"a number".to_string()));
+        assert_eq!(
+            err.render("3+*4"),
+            "3+*4\n  ^ expected operand after '+' at column 3\n  \
+             = expected a number, a variable, a function call or '('"
+        );
+
+        let err = parse_expression("2^").unwrap_err();
+        assert_eq!(err.message, "expected operand after '^' at column 3");
+
+        let err = parse_expression("  ").unwrap_err();
+        assert_eq!(err.message, "expected operand at column 3");
+    }
+
+    #[test]
+    fn parse_error_reports_unclosed_parenthesis() {
+        let err = parse_expression("(1 + 2").unwrap_err();
+        assert_eq!(err.offset, 6);
+        assert_eq!(
+            err.message,
+            "expected ')' at column 7 to close '(' at column 1"
+        );
+
+        let err = parse_expression("sqrt(x (1)").unwrap_err();
+        assert_eq!(
+            err.message,
+            "expected ')' at column 11 to close '(' at column 5"
+        );
+    }
+
+    #[test]
+    fn parse_error_reports_unexpected_input() {
+        let err = parse_expression("2 3").unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.message, "unexpected '3' at column 3");
+
+        let err = parse_expression("x)").unwrap_err();
+        assert_eq!(err.message, "unexpected ')' at column 2");
+    }
+
+    #[test]
+    fn parse_error_reports_function_misuse() {
+        let err = parse_expression("1 + sin(x, y)").unwrap_err();
+        assert_eq!(
+            err.message,
+            "function 'sin' takes 1 argument but 2 were given at column 5"
+        );
+
+        let err = parse_expression("sin - x").unwrap_err();
+        assert_eq!(err.message, "expected operand after 'sin' at column 5");
+    }
 }
@@ -0,0 +1,372 @@
+use std::any::Any;
+
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::One;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::constant::Constant;
+use crate::algebra::display::{latex_operand, typst_operand, Position};
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
+use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+use crate::algebra::number::Number;
+
+/// `Power` struct represents an exponentiation operation in an expression tree.
+/// It contains a `base` and an `exponent`, both of which are `Expr` nodes.
+pub struct Power {
+    pub base: Expr,
+    pub exponent: Expr,
+}
+
+impl Power {
+    /// Constructs a new `Power` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The expression being raised to a power.
+    /// * `exponent` - The expression the base is raised to.
+    pub fn new(base: Expr, exponent: Expr) -> Self {
+        Self { base, exponent }
+    }
+}
+
+/// Returns `n` if `exponent` is the exact fraction `1/n` for an integer `n` of at least 2,
+/// either as a constant or as a division of constants.
+pub(crate) fn root_index(exponent: &Expr) -> Option<BigInt> {
+    let value = match exponent {
+        Expr::Constant(constant) => constant.value.clone(),
+        Expr::Divide(divide) => Constant::value_of(&divide.numerator)?
+            .checked_div(Constant::value_of(&divide.denominator)?)?,
+        _ => return None,
+    };
+    match value {
+        Number::Exact(fraction) if fraction.numer().is_one() && !fraction.is_integer() => {
+            Some(fraction.denom().clone())
+        }
+        _ => None,
+    }
+}
+
+impl Expression for Power {
+    /// Evaluates the expression to a number.
+    /// Raising zero to a negative power is a division by zero, and a negative base
+    /// with a non-integer exponent has no real value.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        let base = self.base.eval(env)?;
+        let exponent = self.exponent.eval(env)?;
+        if base == 0.0 && exponent < 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+        if base < 0.0 && exponent.fract() != 0.0 {
+            return Err(EvalError::Domain(format!(
+                "{}^{} is not a real number",
+                base, exponent
+            )));
+        }
+        let result = base.powf(exponent);
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(EvalError::Domain(format!(
+                "{}^{} overflows",
+                base, exponent
+            )))
+        }
+    }
+
+    /// Evaluates the expression to a complex number, using the principal value of the power.
+    /// Raising zero to a power with a non-positive real part is a division by zero.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        let base = self.base.eval_complex(env)?;
+        let exponent = self.exponent.eval_complex(env)?;
+        let result = if exponent == Complex64::new(0.0, 0.0) {
+            Complex64::new(1.0, 0.0)
+        } else if base == Complex64::new(0.0, 0.0) {
+            if exponent.re <= 0.0 {
+                return Err(EvalError::DivisionByZero);
+            }
+            base
+        } else if exponent.im == 0.0
+            && exponent.re.fract() == 0.0
+            && exponent.re.abs() <= i32::MAX as f64
+        {
+            base.powi(exponent.re as i32)
+        } else {
+            base.powc(exponent)
+        };
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(EvalError::Domain(format!(
+                "({})^({}) overflows",
+                base, exponent
+            )))
+        }
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// Integer powers are computed exactly up to rounding, other powers need a positive base.
+    fn eval_big(&self, env: &Environment, precision: u64) -> Result<BigFloat, EvalError> {
+        let base = self.base.eval_big(env, precision)?;
+        let exponent = self.exponent.eval_big(env, precision)?;
+        base.pow(&exponent, precision)
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// Integer powers accept any base, other powers use the part of the base that is not negative.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        let base = self.base.eval_interval(env)?;
+        let exponent = self.exponent.eval_interval(env)?;
+        base.pow(&exponent)
+    }
+
+    /// Simplifies the expression and returns a new simplified expression.
+    /// This method handles the identities `x^0 = 1`, `x^1 = x`, `1^x = 1` and `0^x = 0` for positive `x`,
+    /// and evaluates powers of constants when the result is exact, such as `(2/3)^2 = 4/9`,
+    /// or when a floating point constant is involved and the result is a real number.
+    fn simplify(&self) -> Expr {
+        let base = self.base.simplify();
+        let exponent = self.exponent.simplify();
+
+        match (Constant::value_of(&base), Constant::value_of(&exponent)) {
+            (_, Some(e)) if e.is_zero() => Expr::from(Constant::new(1)),
+            (_, Some(e)) if e.is_one() => base,
+            (Some(b), _) if b.is_one() => Expr::from(Constant::new(1)),
+            (Some(b), Some(e)) if b.is_zero() && e.is_real() && !e.is_negative() => {
+                Expr::from(Constant::new(0))
+            }
+            (Some(b), Some(e)) => match b.pow(e) {
+                Some(result) => Expr::from(Constant::new(result)),
+                None => Expr::from(Self::new(base, exponent)),
+            },
+            _ => Expr::from(Self::new(base, exponent)),
+        }
+    }
+
+    /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns a debug string for the expression. The `indent` parameter specifies the indentation level.
+    fn debug(&self, indent: usize) -> String {
+        let mut output = format!("{}Power {{\n", " ".repeat(indent));
+        output.push_str(&self.base.debug(indent + 2));
+        output.push_str(&self.exponent.debug(indent + 2));
+        output.push_str(&format!("{}}}\n", " ".repeat(indent)));
+        output
+    }
+
+    /// Returns a Typist string for the expression.
+    /// A power with exponent `1/2` is written as `sqrt` and one with exponent `1/n` as `root`.
+    /// The base is wrapped in parentheses unless it is a single symbol, number or function call,
+    /// and the exponent unless it is a single number or symbol.
+    fn to_typist(&self) -> String {
+        if let Some(index) = root_index(&self.exponent) {
+            return if index == BigInt::from(2) {
+                format!("sqrt({})", self.base.to_typist())
+            } else {
+                format!("root({}, {})", index, self.base.to_typist())
+            };
+        }
+        let base = typst_operand(&self.base, Position::Base);
+        let exponent = self.exponent.to_typist();
+        if exponent.chars().all(char::is_alphanumeric) {
+            format!("{}^{}", base, exponent)
+        } else {
+            // Typst drops the parentheses around a superscript
+            format!("{}^({})", base, exponent)
+        }
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Power`, unit fraction exponents are written as roots such as `\sqrt{x}` and
+    /// `\sqrt[3]{x}`, and other exponents as a superscript, with the base in parentheses
+    /// unless it is an atom.
+    fn to_latex(&self) -> String {
+        if let Some(index) = root_index(&self.exponent) {
+            return if index == BigInt::from(2) {
+                format!("\\sqrt{{{}}}", self.base.to_latex())
+            } else {
+                format!("\\sqrt[{}]{{{}}}", index, self.base.to_latex())
+            };
+        }
+        let base = latex_operand(&self.base, Position::Base);
+        format!("{}^{{{}}}", base, self.exponent.to_latex())
+    }
+}
+
+impl Clone for Power {
+    fn clone(&self) -> Self {
+        Power {
+            base: self.base.clone(),
+            exponent: self.exponent.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::add::Add;
+    use crate::algebra::divide::Divide;
+    use crate::algebra::parser::parse_expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    #[test]
+    fn power_simplify_with_zero_exponent() {
+        let power = Power::new(
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(0.0)),
+        );
+        let simplified = power.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 1.0);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn power_simplify_with_unit_exponent() {
+        let power = Power::new(
+            Expr::from(Variable::new("x")),
+            Expr::from(Constant::new(1.0)),
+        );
+        let simplified = power.simplify();
+        if let Expr::Variable(variable) = &simplified {
+            assert_eq!(variable.name, "x");
+        } else {
+            panic!("Expected Variable, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn power_simplify_with_constants() {
+        let power = Power::new(
+            Expr::from(Constant::new(2.0)),
+            Expr::from(Constant::new(10.0)),
+        );
+        let simplified = power.simplify();
+        if let Expr::Constant(constant) = &simplified {
+            assert_eq!(constant.value, 1024.0);
+        } else {
+            panic!("Expected Constant, found {:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn power_simplify_keeps_non_real_result() {
+        let power = Power::new(
+            Expr::from(Constant::new(-8.0)),
+            Expr::from(Constant::new(0.5)),
+        );
+        let simplified = power.simplify();
+        assert!(matches!(simplified, Expr::Power(_)));
+    }
+
+    #[test]
+    fn power_simplify_zero_base_needs_positive_exponent() {
+        let power = Power::new(
+            Expr::from(Constant::new(0)),
+            Expr::from(Constant::new(Number::i())),
+        );
+        let simplified = power.simplify();
+        assert!(matches!(simplified, Expr::Power(_)), "{:?}", simplified);
+    }
+
+    #[test]
+    fn power_to_typist_wraps_compound_base() {
+        let power = Power::new(
+            Expr::from(Add::new(vec![
+                Expr::from(Variable::new("x")),
+                Expr::from(Constant::new(1.0)),
+            ])),
+            Expr::from(Constant::new(2.0)),
+        );
+        assert_eq!(power.to_typist(), "(x + 1)^2");
+    }
+
+    #[test]
+    fn power_to_typist_writes_roots() {
+        let x = || Expr::from(Variable::new("x"));
+        let power = Power::new(x(), Expr::from(Constant::new(Number::rational(1, 2))));
+        assert_eq!(power.to_typist(), "sqrt(x)");
+        let power = Power::new(
+            x(),
+            Expr::from(Divide::new(
+                Expr::from(Constant::new(1)),
+                Expr::from(Constant::new(3)),
+            )),
+        );
+        assert_eq!(power.to_typist(), "root(3, x)");
+        let power = Power::new(x(), Expr::from(Constant::new(Number::rational(2, 3))));
+        assert_eq!(power.to_typist(), "x^(frac(2, 3))");
+        let power = Power::new(x(), Expr::from(Constant::new(-1)));
+        assert_eq!(power.to_typist(), "x^(-1)");
+    }
+
+    #[test]
+    fn power_to_latex() {
+        let x = || Expr::from(Variable::new("x"));
+        let power = Power::new(x(), Expr::from(Constant::new(Number::rational(1, 2))));
+        assert_eq!(power.to_latex(), "\\sqrt{x}");
+        let power = Power::new(x(), Expr::from(Constant::new(Number::rational(1, 3))));
+        assert_eq!(power.to_latex(), "\\sqrt[3]{x}");
+        let power = Power::new(x(), Expr::from(Constant::new(Number::rational(2, 3))));
+        assert_eq!(power.to_latex(), "x^{\\frac{2}{3}}");
+        let power = Power::new(
+            Expr::from(Add::new(vec![x(), Expr::from(Constant::new(1))])),
+            Expr::from(Constant::new(-1)),
+        );
+        assert_eq!(power.to_latex(), "\\left(x + 1\\right)^{-1}");
+    }
+
+    #[test]
+    fn power_eval_domain_errors() {
+        let env = Environment::new();
+        let power = Power::new(
+            Expr::from(Constant::new(-8.0)),
+            Expr::from(Constant::new(0.5)),
+        );
+        assert!(matches!(power.eval(&env), Err(EvalError::Domain(_))));
+        let power = Power::new(
+            Expr::from(Constant::new(0.0)),
+            Expr::from(Constant::new(-1.0)),
+        );
+        assert_eq!(power.eval(&env), Err(EvalError::DivisionByZero));
+        let power = Power::new(
+            Expr::from(Constant::new(-2.0)),
+            Expr::from(Constant::new(3.0)),
+        );
+        assert_eq!(power.eval(&env), Ok(-8.0));
+    }
+
+    #[test]
+    fn power_simplify_agrees_with_eval() {
+        let env = Environment::new();
+        for input in [
+            "(-8)^(1/3)",
+            "(-8)^(2/3)",
+            "8^(1/3)",
+            "(-8)^3",
+            "(-1/8)^(-1)",
+        ] {
+            let power = parse_expression(input).unwrap();
+            let simplified = power.simplify();
+            match (power.eval(&env), simplified.eval(&env)) {
+                (Ok(value), Ok(simplified_value)) => {
+                    assert!((value - simplified_value).abs() < 1e-12, "{}", input)
+                }
+                (Err(err), Err(simplified_err)) => assert_eq!(err, simplified_err, "{}", input),
+                (value, simplified_value) => panic!(
+                    "{} evaluates to {:?} but simplifies to {} = {:?}",
+                    input, value, simplified, simplified_value
+                ),
+            }
+        }
+    }
+}
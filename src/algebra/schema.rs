@@ -0,0 +1,342 @@
+use std::str::FromStr;
+
+use num_complex::Complex64;
+use num_rational::BigRational;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::algebra::add::Add;
+use crate::algebra::constant::Constant;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::function::Function;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::named_constant::NamedConstant;
+use crate::algebra::negate::Negate;
+use crate::algebra::number::Number;
+use crate::algebra::power::Power;
+use crate::algebra::variable::Variable;
+
+/// Version of the schema written by [`Document`].
+/// It is increased whenever a change to the schema would stop older readers from reading a document.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `Document` is a serialized expression together with the version of the schema it was written
+/// with, for storing expressions or exchanging them between programs.
+///
+/// In a document, every node is an object with a single key naming its kind, e.g.
+/// `{"add": [{"variable": "x"}, {"constant": {"exact": "1/3"}}]}` for `x + 1/3`. Exact numbers are
+/// written as strings so no precision is lost, and subexpressions shared by several nodes are
+/// written once for each of them. Infinite and NaN floats are written as the strings `"inf"`,
+/// `"-inf"` and `"nan"` in JSON. Custom nodes cannot be serialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// The schema version the document was written with, [`SCHEMA_VERSION`] for new documents.
+    #[serde(deserialize_with = "deserialize_version")]
+    pub version: u32,
+    /// The expression stored in the document.
+    pub expr: Expr,
+}
+
+impl Document {
+    /// Constructs a new `Document` holding `expr` with the current schema version.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to store.
+    pub fn new(expr: Expr) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            expr,
+        }
+    }
+}
+
+/// Reads the version of a document, rejecting versions newer than [`SCHEMA_VERSION`].
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(D::Error::custom(format!(
+            "unsupported schema version {}, expected at most {}",
+            version, SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// The serialized form of a node, borrowing its operands from the node.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum NodeRef<'a> {
+    Constant(&'a Number),
+    NamedConstant(NamedConstant),
+    Variable(&'a str),
+    Add(&'a [Expr]),
+    Multiply(&'a [Expr]),
+    Divide {
+        numerator: &'a Expr,
+        denominator: &'a Expr,
+    },
+    Power {
+        base: &'a Expr,
+        exponent: &'a Expr,
+    },
+    Negate(&'a Expr),
+    Function {
+        name: &'a str,
+        args: &'a [Expr],
+    },
+}
+
+/// The deserialized form of a node, read with the same schema as `NodeRef`.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Node {
+    Constant(Number),
+    NamedConstant(NamedConstant),
+    Variable(String),
+    Add(Vec<Expr>),
+    Multiply(Vec<Expr>),
+    Divide { numerator: Expr, denominator: Expr },
+    Power { base: Expr, exponent: Expr },
+    Negate(Expr),
+    Function { name: String, args: Vec<Expr> },
+}
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let node = match self {
+            Expr::Constant(constant) => NodeRef::Constant(&constant.value),
+            Expr::NamedConstant(named) => NodeRef::NamedConstant(*named),
+            Expr::Variable(variable) => NodeRef::Variable(&variable.name),
+            Expr::Add(add) => NodeRef::Add(&add.ops),
+            Expr::Multiply(multiply) => NodeRef::Multiply(&multiply.ops),
+            Expr::Divide(divide) => NodeRef::Divide {
+                numerator: &divide.numerator,
+                denominator: &divide.denominator,
+            },
+            Expr::Power(power) => NodeRef::Power {
+                base: &power.base,
+                exponent: &power.exponent,
+            },
+            Expr::Negate(negate) => NodeRef::Negate(&negate.op),
+            Expr::Function(function) => NodeRef::Function {
+                name: &function.name,
+                args: &function.args,
+            },
+            Expr::Custom(custom) => {
+                return Err(S::Error::custom(format!(
+                    "custom node {:?} cannot be serialized",
+                    custom
+                )))
+            }
+        };
+        node.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Node::deserialize(deserializer)? {
+            Node::Constant(value) => Expr::from(Constant::new(value)),
+            Node::NamedConstant(named) => Expr::from(named),
+            Node::Variable(name) => Expr::from(Variable::new(&name)),
+            Node::Add(ops) => Expr::from(Add::new(ops)),
+            Node::Multiply(ops) => Expr::from(Multiply::new(ops)),
+            Node::Divide {
+                numerator,
+                denominator,
+            } => Expr::from(Divide::new(numerator, denominator)),
+            Node::Power { base, exponent } => Expr::from(Power::new(base, exponent)),
+            Node::Negate(op) => Expr::from(Negate::new(op)),
+            Node::Function { name, args } => Expr::from(Function::new(&name, args)),
+        })
+    }
+}
+
+/// The serialized form of a `Number`. Exact parts are written as fractions such as `-1/3`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NumberRepr {
+    Exact(String),
+    Float(FloatRepr),
+    ExactComplex(String, String),
+    FloatComplex(FloatRepr, FloatRepr),
+}
+
+/// The serialized form of a float part of a number. Human readable formats such as JSON have no
+/// numbers for infinities and NaN, so these are written as the strings `"inf"`, `"-inf"` and `"nan"`.
+struct FloatRepr(f64);
+
+impl Serialize for FloatRepr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.0;
+        if value.is_finite() || !serializer.is_human_readable() {
+            serializer.serialize_f64(value)
+        } else if value.is_nan() {
+            serializer.serialize_str("nan")
+        } else if value > 0.0 {
+            serializer.serialize_str("inf")
+        } else {
+            serializer.serialize_str("-inf")
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FloatRepr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Float {
+            Finite(f64),
+            NonFinite(String),
+        }
+
+        if !deserializer.is_human_readable() {
+            return f64::deserialize(deserializer).map(FloatRepr);
+        }
+        let value = match Float::deserialize(deserializer)? {
+            Float::Finite(value) => value,
+            Float::NonFinite(name) => match name.as_str() {
+                "inf" => f64::INFINITY,
+                "-inf" => f64::NEG_INFINITY,
+                "nan" => f64::NAN,
+                _ => return Err(D::Error::custom(format!("invalid float {:?}", name))),
+            },
+        };
+        Ok(FloatRepr(value))
+    }
+}
+
+/// Reads an exact part of a number written as a fraction.
+fn parse_exact<E: serde::de::Error>(fraction: &str) -> Result<BigRational, E> {
+    BigRational::from_str(fraction)
+        .map_err(|err| E::custom(format!("invalid exact number {:?}: {}", fraction, err)))
+}
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Number::Exact(value) => NumberRepr::Exact(value.to_string()),
+            Number::Float(value) => NumberRepr::Float(FloatRepr(*value)),
+            Number::ExactComplex(re, im) => {
+                NumberRepr::ExactComplex(re.to_string(), im.to_string())
+            }
+            Number::FloatComplex(value) => {
+                NumberRepr::FloatComplex(FloatRepr(value.re), FloatRepr(value.im))
+            }
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match NumberRepr::deserialize(deserializer)? {
+            NumberRepr::Exact(value) => Number::Exact(parse_exact(&value)?),
+            NumberRepr::Float(FloatRepr(value)) => Number::Float(value),
+            // A zero imaginary part is dropped, as `Number` keeps complex numbers non-real
+            NumberRepr::ExactComplex(re, im) => Number::complex(
+                Number::Exact(parse_exact(&re)?),
+                Number::Exact(parse_exact(&im)?),
+            ),
+            NumberRepr::FloatComplex(FloatRepr(re), FloatRepr(im)) => {
+                Number::from(Complex64::new(re, im))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::expression::Expression;
+    use crate::algebra::parser::parse_expression;
+
+    use super::*;
+
+    fn document(input: &str) -> Document {
+        Document::new(parse_expression(input).unwrap())
+    }
+
+    #[test]
+    fn document_json_schema() {
+        let json = serde_json::to_string(&document("x^2 + 0.5")).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":1,"expr":{"add":[{"power":{"base":{"variable":"x"},"exponent":{"constant":{"exact":"2"}}}},{"constant":{"exact":"1/2"}}]}}"#
+        );
+    }
+
+    #[test]
+    fn document_json_round_trip() {
+        for input in [
+            "2x - 1/3",
+            "sin(pi/6)^2 + e",
+            "3 + 4i",
+            "x / (y + 0.5)",
+            "ln(-x)",
+        ] {
+            let original = document(input);
+            let json = serde_json::to_string(&original).unwrap();
+            let read: Document = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, original);
+            assert_eq!(read.expr.to_typist(), original.expr.to_typist());
+        }
+        let float = Document::new(Expr::from(Constant::new(Number::complex(0.5, 2.5))));
+        let json = serde_json::to_string(&float).unwrap();
+        assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), float);
+    }
+
+    #[test]
+    fn document_non_finite_floats_round_trip() {
+        let infinite = Document::new(Expr::from(Constant::new(Number::from(Complex64::new(
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        )))));
+        let json = serde_json::to_string(&infinite).unwrap();
+        assert!(
+            json.contains(r#"{"float_complex":["inf","-inf"]}"#),
+            "{}",
+            json
+        );
+        assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), infinite);
+
+        let nan = Document::new(Expr::from(Constant::new(f64::NAN)));
+        let json = serde_json::to_string(&nan).unwrap();
+        assert!(json.contains(r#"{"float":"nan"}"#), "{}", json);
+        for read in [
+            serde_json::from_str::<Document>(&json).unwrap(),
+            bincode::deserialize(&bincode::serialize(&nan).unwrap()).unwrap(),
+        ] {
+            match read.expr {
+                Expr::Constant(constant) => {
+                    assert!(matches!(constant.value, Number::Float(value) if value.is_nan()))
+                }
+                expr => panic!("expected a constant, got {:?}", expr),
+            }
+        }
+
+        let unknown = r#"{"version":1,"expr":{"constant":{"float":"infinity"}}}"#;
+        assert!(serde_json::from_str::<Document>(unknown).is_err());
+    }
+
+    #[test]
+    fn document_binary_round_trip() {
+        let original = document("(x + 1)^10 / sqrt(phi + gamma y)");
+        let bytes = bincode::serialize(&original).unwrap();
+        let read: Document = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(read, original);
+    }
+
+    #[test]
+    fn document_rejects_invalid_input() {
+        let newer = r#"{"version":2,"expr":{"variable":"x"}}"#;
+        let err = serde_json::from_str::<Document>(newer).unwrap_err();
+        assert!(err.to_string().contains("unsupported schema version 2"));
+        let zero_denominator = r#"{"version":1,"expr":{"constant":{"exact":"1/0"}}}"#;
+        assert!(serde_json::from_str::<Document>(zero_denominator).is_err());
+        let unknown = r#"{"version":1,"expr":{"integral":[]}}"#;
+        assert!(serde_json::from_str::<Document>(unknown).is_err());
+    }
+}
@@ -1,6 +1,19 @@
 use std::any::Any;
 
+use num_complex::Complex64;
+
+use crate::algebra::bigfloat::BigFloat;
+use crate::algebra::eval::{Environment, EvalError};
+use crate::algebra::expr::Expr;
 use crate::algebra::expression::Expression;
+use crate::algebra::interval::Interval;
+
+/// Names of the Greek letters, which Typst renders as the corresponding symbol.
+const GREEK_LETTERS: [&str; 24] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa",
+    "lambda", "mu", "nu", "xi", "omicron", "pi", "rho", "sigma", "tau", "upsilon", "phi", "chi",
+    "psi", "omega",
+];
 
 /// `Variable` struct represents a variable in an expression tree.
 /// It contains a `name` field which is a `String`.
@@ -20,19 +33,143 @@ impl Variable {
             name: value.to_string(),
         }
     }
+
+    /// Splits the name into its base, optional subscript and trailing primes,
+    /// e.g. `v_max'` becomes `("v", Some("max"), "'")`.
+    pub(crate) fn parts(&self) -> (&str, Option<&str>, &str) {
+        let unprimed = self.name.trim_end_matches('\'');
+        let primes = &self.name[unprimed.len()..];
+        match unprimed.split_once('_') {
+            Some((base, subscript)) => (base, Some(subscript), primes),
+            None => (unprimed, None, primes),
+        }
+    }
+}
+
+/// Upper case Greek letters that look like a Latin letter, which LaTeX has no command for.
+const LATIN_LOOKING_GREEK_LETTERS: [(&str, &str); 13] = [
+    ("Alpha", "A"),
+    ("Beta", "B"),
+    ("Epsilon", "E"),
+    ("Zeta", "Z"),
+    ("Eta", "H"),
+    ("Iota", "I"),
+    ("Kappa", "K"),
+    ("Mu", "M"),
+    ("Nu", "N"),
+    ("Omicron", "O"),
+    ("Rho", "P"),
+    ("Tau", "T"),
+    ("Chi", "X"),
+];
+
+/// Returns the position in the Greek alphabet of the letter named `name`, in lower case or
+/// capitalized, and whether it is capitalized, e.g. `(23, true)` for `Omega`. Only an ASCII
+/// first letter may be capitalized, so `None` is returned for any other name.
+pub(crate) fn greek_letter_index(name: &str) -> Option<(usize, bool)> {
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let rest = chars.as_str();
+    GREEK_LETTERS
+        .iter()
+        .position(|letter| letter[1..] == *rest && letter.starts_with(first.to_ascii_lowercase()))
+        .map(|index| (index, first.is_ascii_uppercase()))
+}
+
+/// Returns `true` if `segment` is the name of a Greek letter, in lower case or capitalized.
+fn is_greek(segment: &str) -> bool {
+    greek_letter_index(segment).is_some()
+}
+
+/// Returns `true` if `segment` is written as it is: a single character or a number.
+fn is_plain(segment: &str) -> bool {
+    segment.chars().count() == 1 || segment.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns a Typist string for a single name segment. Single characters and Greek letter names
+/// are emitted as-is, anything longer is wrapped so Typst doesn't try to resolve it as a symbol.
+fn segment_to_typist(segment: &str, longer: impl Fn(&str) -> String) -> String {
+    if is_greek(segment) || is_plain(segment) {
+        segment.to_string()
+    } else {
+        longer(segment)
+    }
+}
+
+/// Returns a LaTeX string for a single name segment. Greek letter names become commands such as
+/// `\alpha`, single characters and numbers are emitted as-is, and anything longer is wrapped
+/// so it is not read as a product of single-letter variables.
+fn segment_to_latex(segment: &str, longer: impl Fn(&str) -> String) -> String {
+    if let Some((_, latin)) = LATIN_LOOKING_GREEK_LETTERS
+        .iter()
+        .find(|(letter, _)| *letter == segment)
+    {
+        format!("\\mathrm{{{}}}", latin)
+    } else if segment == "omicron" {
+        "o".to_string()
+    } else if is_greek(segment) {
+        format!("\\{}", segment)
+    } else if is_plain(segment) {
+        segment.to_string()
+    } else {
+        longer(segment)
+    }
 }
 
 impl Expression for Variable {
-    /// Evaluates the expression and returns a new expression.
-    /// For a `Variable`, it returns a clone of itself.
-    fn eval(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    /// Evaluates the expression to a number.
+    /// For a `Variable`, it returns the value bound to its name in `env`.
+    /// Returns an `EvalError::Domain` if the variable is bound to a value that is not real.
+    fn eval(&self, env: &Environment) -> Result<f64, EvalError> {
+        let value = self.eval_complex(env)?;
+        if value.im != 0.0 {
+            return Err(EvalError::Domain(format!(
+                "variable '{}' is bound to the non-real value {}",
+                self.name, value
+            )));
+        }
+        Ok(value.re)
+    }
+
+    /// Evaluates the expression to a complex number.
+    /// For a `Variable`, it returns the value bound to its name in `env`.
+    fn eval_complex(&self, env: &Environment) -> Result<Complex64, EvalError> {
+        env.get(&self.name)
+            .ok_or_else(|| EvalError::UnboundVariable(self.name.clone()))
+    }
+
+    /// Evaluates the expression to `precision` bits with a guaranteed error bound.
+    /// For a `Variable`, it returns the exact value of the `f64` it is bound to.
+    fn eval_big(&self, env: &Environment, _precision: u64) -> Result<BigFloat, EvalError> {
+        let value = self.eval(env)?;
+        BigFloat::from_f64(value).ok_or_else(|| {
+            EvalError::Domain(format!(
+                "variable '{}' is bound to the non-finite value {}",
+                self.name, value
+            ))
+        })
+    }
+
+    /// Evaluates the expression to an interval enclosing every value it takes in `env`.
+    /// For a `Variable`, it returns the interval bound to its name, or the single value it is bound to.
+    fn eval_interval(&self, env: &Environment) -> Result<Interval, EvalError> {
+        if let Some(interval) = env.get_interval(&self.name) {
+            return Ok(interval);
+        }
+        let value = self.eval(env)?;
+        if value.is_nan() {
+            return Err(EvalError::Domain(format!(
+                "variable '{}' is bound to the non-finite value {}",
+                self.name, value
+            )));
+        }
+        Ok(Interval::point(value))
     }
 
     /// Simplifies the expression and returns a new simplified expression.
     /// For a `Variable`, it returns a clone of itself.
-    fn simplify(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    fn simplify(&self) -> Expr {
+        Expr::from(self.clone())
     }
 
     /// Returns a reference to the expression as a `dyn Any`, which can be downcast to its concrete type.
@@ -46,8 +183,72 @@ impl Expression for Variable {
     }
 
     /// Returns a Typist string for the expression.
-    /// For a `Variable`, it directly returns the variable name.
+    /// For a `Variable`, Greek letter names become symbols, other multi-letter names are set in italics,
+    /// descriptive subscripts are set upright and primes are kept as-is.
     fn to_typist(&self) -> String {
-        self.name.clone() // Directly return the variable name
+        let (base, subscript, primes) = self.parts();
+        let mut output = segment_to_typist(base, |s| format!("italic(\"{}\")", s));
+        if let Some(subscript) = subscript {
+            output.push('_');
+            output.push_str(&segment_to_typist(subscript, |s| format!("\"{}\"", s)));
+        }
+        output.push_str(primes);
+        output
+    }
+
+    /// Returns a LaTeX string for the expression.
+    /// For a `Variable`, Greek letter names become commands, other multi-letter names are set
+    /// with `\mathit`, descriptive subscripts are set upright and primes are kept as-is.
+    fn to_latex(&self) -> String {
+        let (base, subscript, primes) = self.parts();
+        let mut output = segment_to_latex(base, |s| format!("\\mathit{{{}}}", s));
+        if let Some(subscript) = subscript {
+            let subscript = segment_to_latex(subscript, |s| format!("\\mathrm{{{}}}", s));
+            output.push_str(&format!("_{{{}}}", subscript));
+        }
+        output.push_str(primes);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variable_to_typist() {
+        assert_eq!(Variable::new("x").to_typist(), "x");
+        assert_eq!(Variable::new("θ").to_typist(), "θ");
+        assert_eq!(Variable::new("alpha").to_typist(), "alpha");
+        assert_eq!(Variable::new("Omega").to_typist(), "Omega");
+        assert_eq!(Variable::new("mass").to_typist(), "italic(\"mass\")");
+        assert_eq!(Variable::new("x_1").to_typist(), "x_1");
+        assert_eq!(Variable::new("x_12").to_typist(), "x_12");
+        assert_eq!(Variable::new("v_max").to_typist(), "v_\"max\"");
+        assert_eq!(Variable::new("theta_0").to_typist(), "theta_0");
+        assert_eq!(Variable::new("f''").to_typist(), "f''");
+    }
+
+    #[test]
+    fn variable_to_latex() {
+        assert_eq!(Variable::new("x").to_latex(), "x");
+        assert_eq!(Variable::new("θ").to_latex(), "θ");
+        assert_eq!(Variable::new("alpha").to_latex(), "\\alpha");
+        assert_eq!(Variable::new("Omega").to_latex(), "\\Omega");
+        assert_eq!(Variable::new("Beta").to_latex(), "\\mathrm{B}");
+        assert_eq!(Variable::new("mass").to_latex(), "\\mathit{mass}");
+        assert_eq!(Variable::new("x_12").to_latex(), "x_{12}");
+        assert_eq!(Variable::new("v_max").to_latex(), "v_{\\mathrm{max}}");
+        assert_eq!(Variable::new("theta_0").to_latex(), "\\theta_{0}");
+        assert_eq!(Variable::new("f''").to_latex(), "f''");
+    }
+
+    #[test]
+    fn variable_eval_interval_rejects_nan() {
+        let x = Variable::new("x");
+        let env = Environment::new().with("x", 2.0);
+        assert_eq!(x.eval_interval(&env), Ok(Interval::point(2.0)));
+        let env = Environment::new().with("x", f64::NAN);
+        assert!(matches!(x.eval_interval(&env), Err(EvalError::Domain(_))));
     }
 }
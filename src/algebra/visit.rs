@@ -0,0 +1,419 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::algebra::add::Add;
+use crate::algebra::divide::Divide;
+use crate::algebra::expr::Expr;
+use crate::algebra::function::Function;
+use crate::algebra::interner::Operand;
+use crate::algebra::multiply::Multiply;
+use crate::algebra::negate::Negate;
+use crate::algebra::power::Power;
+
+/// A read-only pass over an expression tree, see [`Expr::accept`].
+/// Nodes are visited depth-first, with `enter` called before and `leave` after the operands of a node.
+/// Constants, variables and custom nodes have no operands.
+/// A node shared by several parents, as in an interned expression, is visited once under each of
+/// them, unless the pass is run with [`Expr::accept_distinct`].
+pub trait Visitor {
+    /// Called when entering `expr`, before its operands are visited.
+    /// Returns `false` to skip the operands of `expr`, in which case `leave` is still called.
+    fn enter(&mut self, expr: &Expr) -> bool {
+        let _ = expr;
+        true
+    }
+
+    /// Called when leaving `expr`, after its operands have been visited.
+    fn leave(&mut self, expr: &Expr) {
+        let _ = expr;
+    }
+}
+
+/// A pass that rewrites an expression tree, see [`Expr::rewrite`].
+/// `rewrite_down` can replace a node before its operands are rewritten, and `rewrite_up` can replace
+/// a node after its operands have been rewritten. Both keep the node unchanged by default.
+pub trait Rewriter {
+    /// Called top-down on `expr` before its operands are rewritten.
+    /// Returns the replacement of `expr`, whose operands are then not rewritten, or `None` to
+    /// rewrite the operands of `expr`.
+    fn rewrite_down(&mut self, expr: &Expr) -> Option<Expr> {
+        let _ = expr;
+        None
+    }
+
+    /// Called bottom-up on `expr` after its operands have been rewritten, and returns its replacement.
+    fn rewrite_up(&mut self, expr: Expr) -> Expr {
+        expr
+    }
+}
+
+/// A `Rewriter` that applies a closure bottom-up.
+struct BottomUp<F>(F);
+
+impl<F: FnMut(Expr) -> Expr> Rewriter for BottomUp<F> {
+    fn rewrite_up(&mut self, expr: Expr) -> Expr {
+        (self.0)(expr)
+    }
+}
+
+/// A `Rewriter` that applies a closure top-down.
+struct TopDown<F>(F);
+
+impl<F: FnMut(&Expr) -> Option<Expr>> Rewriter for TopDown<F> {
+    fn rewrite_down(&mut self, expr: &Expr) -> Option<Expr> {
+        (self.0)(expr)
+    }
+}
+
+impl Expr {
+    /// Returns the operands of the expression in order, e.g. the numerator and the denominator
+    /// of a `Divide`. Constants, variables and custom nodes have no operands.
+    pub fn operands(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Constant(_) | Expr::NamedConstant(_) | Expr::Variable(_) | Expr::Custom(_) => {
+                Vec::new()
+            }
+            Expr::Add(add) => add.ops.iter().collect(),
+            Expr::Multiply(multiply) => multiply.ops.iter().collect(),
+            Expr::Divide(divide) => vec![&divide.numerator, &divide.denominator],
+            Expr::Power(power) => vec![&power.base, &power.exponent],
+            Expr::Negate(negate) => vec![&negate.op],
+            Expr::Function(function) => function.args.iter().collect(),
+        }
+    }
+
+    /// Returns a node of the same kind as the expression with its operands replaced by `operands`.
+    /// Expressions without operands are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `operands` - The new operands, in the order returned by [`Expr::operands`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `Divide`, `Power` or `Negate` is given the wrong number of operands.
+    pub fn with_operands(&self, operands: Vec<Expr>) -> Expr {
+        let wrong_arity = || panic!("wrong number of operands for {:?}", self);
+        match self {
+            Expr::Constant(_) | Expr::NamedConstant(_) | Expr::Variable(_) | Expr::Custom(_) => {
+                self.clone()
+            }
+            Expr::Add(_) => Expr::from(Add::new(operands)),
+            Expr::Multiply(_) => Expr::from(Multiply::new(operands)),
+            Expr::Divide(_) => match <[Expr; 2]>::try_from(operands) {
+                Ok([numerator, denominator]) => Expr::from(Divide::new(numerator, denominator)),
+                Err(_) => wrong_arity(),
+            },
+            Expr::Power(_) => match <[Expr; 2]>::try_from(operands) {
+                Ok([base, exponent]) => Expr::from(Power::new(base, exponent)),
+                Err(_) => wrong_arity(),
+            },
+            Expr::Negate(_) => match <[Expr; 1]>::try_from(operands) {
+                Ok([op]) => Expr::from(Negate::new(op)),
+                Err(_) => wrong_arity(),
+            },
+            Expr::Function(function) => Expr::from(Function::new(&function.name, operands)),
+        }
+    }
+
+    /// Walks the expression depth-first, calling `visitor` on every node.
+    /// A node shared by several parents is visited under each of them, so the visitor sees the
+    /// same nodes however the expression was built, e.g. `x + x` visits `x` twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - The pass to run, see [`Visitor`].
+    pub fn accept(&self, visitor: &mut impl Visitor) {
+        if visitor.enter(self) {
+            for op in self.operands() {
+                op.accept(visitor);
+            }
+        }
+        visitor.leave(self);
+    }
+
+    /// Walks the expression depth-first like [`Expr::accept`], but visits each distinct node once.
+    /// Nodes are identified as in the [`Interner`](crate::algebra::interner::Interner), by the
+    /// shared allocation they are stored in and named constants by their value, so this takes time
+    /// proportional to the number of distinct nodes of a DAG. Which nodes count as the same therefore
+    /// depends on how the expression was built: `x + x` built from one `x` visits it once, while
+    /// parsing `x + x` gives two nodes. A node whose operands were skipped is not entered again.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - The pass to run, see [`Visitor`].
+    pub fn accept_distinct(&self, visitor: &mut impl Visitor) {
+        self.accept_shared(visitor, &mut HashSet::new());
+    }
+
+    /// Walks the expression, skipping the nodes that were already visited in this call, in `seen`.
+    fn accept_shared(&self, visitor: &mut impl Visitor, seen: &mut HashSet<Operand>) {
+        if !seen.insert(Operand::of(self)) {
+            return;
+        }
+        if visitor.enter(self) {
+            for op in self.operands() {
+                op.accept_shared(visitor, seen);
+            }
+        }
+        visitor.leave(self);
+    }
+
+    /// Returns the expression rewritten by `rewriter`, see [`Rewriter`].
+    /// Nodes whose operands are left unchanged are shared with the original expression,
+    /// and a node shared by several parents is rewritten once.
+    ///
+    /// # Arguments
+    ///
+    /// * `rewriter` - The pass to run.
+    pub fn rewrite(&self, rewriter: &mut impl Rewriter) -> Expr {
+        self.rewrite_shared(rewriter, &mut HashMap::new())
+    }
+
+    /// Rewrites the expression, looking up nodes that were already rewritten in this call in `seen`.
+    fn rewrite_shared(
+        &self,
+        rewriter: &mut impl Rewriter,
+        seen: &mut HashMap<Operand, Expr>,
+    ) -> Expr {
+        let identity = Operand::of(self);
+        if let Some(rewritten) = seen.get(&identity) {
+            return rewritten.clone();
+        }
+        let rewritten = match rewriter.rewrite_down(self) {
+            Some(replacement) => replacement,
+            None => {
+                let operands = self.operands();
+                let rewritten: Vec<Expr> = operands
+                    .iter()
+                    .map(|op| op.rewrite_shared(rewriter, seen))
+                    .collect();
+                let unchanged = operands
+                    .iter()
+                    .zip(&rewritten)
+                    .all(|(op, new)| op.ptr_eq(new));
+                let node = if unchanged {
+                    self.clone()
+                } else {
+                    self.with_operands(rewritten)
+                };
+                rewriter.rewrite_up(node)
+            }
+        };
+        seen.insert(identity, rewritten.clone());
+        rewritten
+    }
+
+    /// Returns the expression with `rewrite` applied to every node bottom-up,
+    /// so each node is passed to `rewrite` after its operands have been rewritten.
+    pub fn rewrite_bottom_up(&self, rewrite: impl FnMut(Expr) -> Expr) -> Expr {
+        self.rewrite(&mut BottomUp(rewrite))
+    }
+
+    /// Returns the expression with `rewrite` applied to every node top-down. A node for which
+    /// `rewrite` returns a replacement is replaced as a whole, otherwise its operands are rewritten.
+    pub fn rewrite_top_down(&self, rewrite: impl FnMut(&Expr) -> Option<Expr>) -> Expr {
+        self.rewrite(&mut TopDown(rewrite))
+    }
+
+    /// Folds the expression bottom-up into a single value, calling `combine` on every node
+    /// with the values its operands were folded into, e.g. to compute the depth of a tree.
+    /// A node shared by several parents is folded once, and its value is cloned for the others.
+    pub fn fold<T: Clone>(&self, combine: &mut impl FnMut(&Expr, Vec<T>) -> T) -> T {
+        self.fold_shared(combine, &mut HashMap::new())
+    }
+
+    /// Folds the expression, looking up nodes that were already folded in this call in `seen`.
+    fn fold_shared<T: Clone>(
+        &self,
+        combine: &mut impl FnMut(&Expr, Vec<T>) -> T,
+        seen: &mut HashMap<Operand, T>,
+    ) -> T {
+        let identity = Operand::of(self);
+        if let Some(value) = seen.get(&identity) {
+            return value.clone();
+        }
+        let values = self
+            .operands()
+            .into_iter()
+            .map(|op| op.fold_shared(combine, seen))
+            .collect();
+        let value = combine(self, values);
+        seen.insert(identity, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::algebra::constant::Constant;
+    use crate::algebra::expression::Expression;
+    use crate::algebra::interner::Interner;
+    use crate::algebra::parser::parse_expression;
+    use crate::algebra::variable::Variable;
+
+    use super::*;
+
+    /// Collects the names of the variables in an expression.
+    #[derive(Default)]
+    struct Variables(BTreeSet<String>);
+
+    impl Visitor for Variables {
+        fn enter(&mut self, expr: &Expr) -> bool {
+            if let Expr::Variable(variable) = expr {
+                self.0.insert(variable.name.clone());
+            }
+            true
+        }
+    }
+
+    #[test]
+    fn visitor_collects_variables() {
+        let expr = parse_expression("x^2 + sin(y) / x").unwrap();
+        let mut variables = Variables::default();
+        expr.accept(&mut variables);
+        assert_eq!(
+            variables.0,
+            BTreeSet::from(["x".to_string(), "y".to_string()])
+        );
+    }
+
+    #[test]
+    fn visitor_enter_and_leave() {
+        struct Depth {
+            current: usize,
+            max: usize,
+            nodes: usize,
+        }
+        impl Visitor for Depth {
+            fn enter(&mut self, expr: &Expr) -> bool {
+                self.current += 1;
+                self.max = self.max.max(self.current);
+                self.nodes += 1;
+                // Function arguments are not counted
+                !matches!(expr, Expr::Function(_))
+            }
+            fn leave(&mut self, _expr: &Expr) {
+                self.current -= 1;
+            }
+        }
+        let expr = parse_expression("2 x + sin(x^2)").unwrap();
+        let mut depth = Depth {
+            current: 0,
+            max: 0,
+            nodes: 0,
+        };
+        expr.accept(&mut depth);
+        assert_eq!((depth.max, depth.nodes), (3, 5));
+        assert_eq!(depth.current, 0);
+    }
+
+    #[test]
+    fn visitor_counts_every_occurrence() {
+        struct Count(usize);
+        impl Visitor for Count {
+            fn enter(&mut self, _expr: &Expr) -> bool {
+                self.0 += 1;
+                true
+            }
+        }
+        let count = |expr: &Expr, distinct: bool| {
+            let mut count = Count(0);
+            if distinct {
+                expr.accept_distinct(&mut count);
+            } else {
+                expr.accept(&mut count);
+            }
+            count.0
+        };
+        let x = Expr::from(Variable::new("x"));
+        let shared = &x + &x;
+        let parsed = parse_expression("x + x").unwrap();
+        assert_eq!((count(&shared, false), count(&parsed, false)), (3, 3));
+        assert_eq!(count(&parse_expression("pi + pi").unwrap(), false), 3);
+        assert_eq!((count(&shared, true), count(&parsed, true)), (2, 3));
+    }
+
+    #[test]
+    fn rewrite_bottom_up_substitutes_variables() {
+        let expr = parse_expression("x^2 + x").unwrap();
+        let substituted = expr.rewrite_bottom_up(|node| match &node {
+            Expr::Variable(variable) if variable.name == "x" => Expr::from(Constant::new(3)),
+            _ => node,
+        });
+        assert_eq!(substituted.simplify().to_typist(), "12");
+    }
+
+    #[test]
+    fn rewrite_top_down_replaces_subtrees() {
+        let expr = parse_expression("sin(x)^2 + cos(x)^2").unwrap();
+        let mut calls = 0;
+        let rewritten = expr.rewrite_top_down(|node| {
+            calls += 1;
+            match node {
+                Expr::Function(function) if function.name == "sin" => {
+                    Some(Function::new("cos", function.args.clone()).into())
+                }
+                _ => None,
+            }
+        });
+        assert_eq!(rewritten.simplify().to_typist(), "2 cos(x)^2");
+        // The arguments of the replaced call are not visited
+        assert_eq!(calls, 8);
+    }
+
+    #[test]
+    fn rewrite_shares_unchanged_nodes() {
+        let expr = parse_expression("(a + b) * c").unwrap();
+        let rewritten = expr.rewrite_bottom_up(|node| node);
+        assert!(rewritten.ptr_eq(&expr));
+        let rewritten = expr.rewrite_bottom_up(|node| match &node {
+            Expr::Variable(variable) if variable.name == "c" => Expr::from(Constant::new(2)),
+            _ => node,
+        });
+        assert!(rewritten.operands()[0].ptr_eq(expr.operands()[0]));
+    }
+
+    #[test]
+    fn fold_counts_nodes_and_depth() {
+        let expr = parse_expression("1 / (x + y^2)").unwrap();
+        let count = expr.fold(&mut |_, counts: Vec<usize>| 1 + counts.iter().sum::<usize>());
+        assert_eq!(count, 7);
+        let depth =
+            expr.fold(&mut |_, depths: Vec<usize>| 1 + depths.into_iter().max().unwrap_or(0));
+        assert_eq!(depth, 4);
+    }
+
+    #[test]
+    fn passes_visit_shared_nodes_once() {
+        // Each level adds the previous level to itself, so the tree has 2^64 leaves
+        let mut expr = Expr::from(Variable::new("x"));
+        for _ in 0..64 {
+            expr = Expr::from(Add::new(vec![expr.clone(), expr]));
+        }
+        let expr = Interner::new().intern(&expr);
+
+        let mut variables = Variables::default();
+        expr.accept_distinct(&mut variables);
+        assert_eq!(variables.0, BTreeSet::from(["x".to_string()]));
+
+        let mut calls = 0;
+        let rewritten = expr.rewrite_bottom_up(|node| {
+            calls += 1;
+            match &node {
+                Expr::Variable(_) => Expr::from(Variable::new("y")),
+                _ => node,
+            }
+        });
+        assert_eq!(calls, 65);
+        assert!(rewritten.operands()[0].ptr_eq(rewritten.operands()[1]));
+        let mut variables = Variables::default();
+        rewritten.accept_distinct(&mut variables);
+        assert_eq!(variables.0, BTreeSet::from(["y".to_string()]));
+
+        let depth =
+            expr.fold(&mut |_, depths: Vec<usize>| 1 + depths.into_iter().max().unwrap_or(0));
+        assert_eq!(depth, 65);
+    }
+}
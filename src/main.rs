@@ -3,12 +3,12 @@ use std::env;
 use std::path::PathBuf;
 use std::process::Command;
 
-use crate::algebra::expression::Expression;
+use atium::algebra;
+use atium::algebra::expr::Expr;
+use atium::algebra::expression::Expression;
 use image::GenericImageView;
 use tempfile::tempdir;
 
-mod algebra;
-
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
     if args.len() != 2 {
@@ -16,11 +16,17 @@ fn main() -> Result<()> {
         std::process::exit(1);
     }
 
-    let (_, expr) = algebra::parser::parse_expression(&args[1]).unwrap();
+    let expr = match algebra::parser::parse_expression(&args[1]) {
+        Ok(expr) => expr,
+        Err(err) => {
+            eprintln!("{}", err.render(&args[1]));
+            std::process::exit(1);
+        }
+    };
 
     let simplified_expr = expr.simplify();
 
-    println!("Simplified Expression: {:?}\n", simplified_expr.to_typist());
+    println!("Simplified Expression: {}\n", simplified_expr);
     let imgcat_path = find_imgcat();
     if let Some(imgcat_path) = imgcat_path {
         print_expr_as_img(simplified_expr, imgcat_path).unwrap();
@@ -34,7 +40,7 @@ fn main() -> Result<()> {
 ///
 /// # Arguments
 ///
-/// * `simplified_expr` - A boxed expression that has been simplified.
+/// * `simplified_expr` - An expression that has been simplified.
 /// * `imgcat_path` - A `PathBuf` that specifies the location of the `imgcat` executable.
 ///
 /// # Errors
@@ -46,13 +52,13 @@ fn main() -> Result<()> {
 /// * The `typst` command fails.
 /// * There is a problem cropping and scaling the image.
 /// * The `imgcat` command fails.
-fn print_expr_as_img(simplified_expr: Box<dyn Expression>, imgcat_path: PathBuf) -> Result<()> {
+fn print_expr_as_img(simplified_expr: Expr, imgcat_path: PathBuf) -> Result<()> {
     let temp_dir = tempdir()?;
     let temp_dir_path = temp_dir.path();
 
     let typist_file_path = temp_dir_path.join("expression.typ");
-    let mut typist_expression = simplified_expr.to_typist();
-    typist_expression.push_str("\n\n");
+    // Typeset the expression as a display equation
+    let typist_expression = format!("$ {} $\n\n", simplified_expr.to_typist());
     std::fs::write(&typist_file_path, typist_expression)?;
 
     // Compile the Typist file to PNG